@@ -0,0 +1,222 @@
+//! Будильники: включение станции из избранного по расписанию.
+//!
+//! Расписание хранится в `AppSettings::alarms`, планировщик работает в бэкенде,
+//! поэтому будильник срабатывает и при свёрнутом в трей окне. Что делать при
+//! срабатывании, решает `lib.rs` (показать окно, включить станцию).
+
+#[cfg(test)]
+mod tests;
+
+use crate::error::{AppError, AppResult};
+use crate::player::{Player, PlayerRequest};
+use chrono::{DateTime, Datelike, Days, Local, NaiveTime, TimeZone, Weekday};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{oneshot, Mutex};
+
+/// Проверять расписание не реже, чем раз в 30 секунд (сон ноутбука, перевод часов)
+const MAX_WAIT: Duration = Duration::from_secs(30);
+
+/// Будильник, пропущенный дольше этого времени (компьютер спал), не включается
+const MISSED_GRACE: chrono::Duration = chrono::Duration::minutes(10);
+
+/// Шагов громкости при плавном включении
+const RAMP_STEPS: u32 = 20;
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_start_volume() -> u8 {
+    20
+}
+
+/// Будильник
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Alarm {
+    /// Уникальный ID (создаётся при сохранении)
+    #[serde(default)]
+    pub id: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Местное время срабатывания "ЧЧ:ММ"
+    pub time: String,
+    /// Дни недели: 1 — понедельник … 7 — воскресенье. Пусто — каждый день
+    #[serde(default)]
+    pub weekdays: Vec<u8>,
+    /// Станция из избранного (будильники общие — любого профиля)
+    pub station_id: String,
+    /// Громкость при включении (0–100)
+    #[serde(default = "default_start_volume")]
+    pub start_volume: u8,
+    /// За сколько секунд громкость поднимается до обычной (0 — остаётся стартовой)
+    #[serde(default)]
+    pub ramp_seconds: u32,
+}
+
+impl Alarm {
+    /// Проверить время, дни недели и громкость
+    pub fn validate(&self) -> AppResult<()> {
+        parse_time(&self.time)?;
+        if let Some(day) = self.weekdays.iter().find(|d| !(1..=7).contains(*d)) {
+            return Err(AppError::invalid_input(format!(
+                "Неверный день недели: {} (ожидается 1–7)",
+                day
+            )));
+        }
+        if self.start_volume > 100 {
+            return Err(AppError::invalid_input("Громкость должна быть от 0 до 100"));
+        }
+        if self.station_id.is_empty() {
+            return Err(AppError::missing_field(
+                "station_id",
+                "Не выбрана станция будильника",
+            ));
+        }
+        Ok(())
+    }
+
+    fn runs_on(&self, weekday: Weekday) -> bool {
+        self.weekdays.is_empty()
+            || self
+                .weekdays
+                .contains(&(weekday.number_from_monday() as u8))
+    }
+}
+
+fn parse_time(time: &str) -> AppResult<NaiveTime> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| {
+        AppError::invalid_input(format!(
+            "Неверное время будильника: {} (ожидается ЧЧ:ММ)",
+            time
+        ))
+    })
+}
+
+/// Ближайшее срабатывание строго после `after`.
+/// Несуществующее из-за перевода часов время пропускается.
+pub fn next_occurrence<Tz: TimeZone>(alarm: &Alarm, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+    let time = parse_time(&alarm.time).ok()?;
+    let timezone = after.timezone();
+    let today = after.date_naive();
+    (0..=7)
+        .filter_map(|days| today.checked_add_days(Days::new(days)))
+        .filter(|date| alarm.runs_on(date.weekday()))
+        .filter_map(|date| {
+            date.and_time(time)
+                .and_local_timezone(timezone.clone())
+                .earliest()
+        })
+        .find(|at| at > after)
+}
+
+/// Обработчик срабатывания будильника
+pub type AlarmCallback = Arc<dyn Fn(Alarm) + Send + Sync>;
+
+struct ActiveScheduler {
+    stop: oneshot::Sender<()>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+/// Планировщик будильников
+pub struct AlarmScheduler {
+    active: Mutex<Option<ActiveScheduler>>,
+}
+
+impl AlarmScheduler {
+    pub fn new() -> Self {
+        Self {
+            active: Mutex::new(None),
+        }
+    }
+
+    /// Запустить планировщик с новым расписанием (старое отменяется)
+    pub async fn apply(&self, alarms: &[Alarm], on_fire: AlarmCallback) {
+        let mut active = self.active.lock().await;
+        if let Some(previous) = active.take() {
+            let _ = previous.stop.send(());
+            let _ = previous.task.await;
+        }
+
+        let alarms: Vec<Alarm> = alarms.iter().filter(|a| a.enabled).cloned().collect();
+        if alarms.is_empty() {
+            return;
+        }
+        eprintln!("⏰ Будильников: {}", alarms.len());
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let task = tokio::spawn(run_scheduler(alarms, on_fire, stop_rx));
+        *active = Some(ActiveScheduler {
+            stop: stop_tx,
+            task,
+        });
+    }
+}
+
+async fn run_scheduler(
+    alarms: Vec<Alarm>,
+    on_fire: AlarmCallback,
+    mut stop: oneshot::Receiver<()>,
+) {
+    let now = Local::now();
+    let mut pending: Vec<(Alarm, Option<DateTime<Local>>)> = alarms
+        .into_iter()
+        .map(|alarm| {
+            let at = next_occurrence(&alarm, &now);
+            (alarm, at)
+        })
+        .collect();
+
+    loop {
+        let now = Local::now();
+        for (alarm, at) in pending.iter_mut() {
+            let Some(fire_at) = *at else {
+                continue;
+            };
+            if fire_at > now {
+                continue;
+            }
+            if now - fire_at <= MISSED_GRACE {
+                eprintln!("⏰ Будильник {}: {}", alarm.time, alarm.station_id);
+                on_fire(alarm.clone());
+            } else {
+                eprintln!("⚠️ Будильник {} пропущен", alarm.time);
+            }
+            *at = next_occurrence(alarm, &now);
+        }
+
+        let wait = pending
+            .iter()
+            .filter_map(|(_, at)| *at)
+            .min()
+            .and_then(|at| (at - now).to_std().ok())
+            .unwrap_or(MAX_WAIT)
+            .min(MAX_WAIT);
+        tokio::select! {
+            _ = &mut stop => return,
+            _ = tokio::time::sleep(wait) => {}
+        }
+    }
+}
+
+/// Плавно изменить громкость от `from` до `to` за `duration`.
+/// Промежуточные шаги не сохраняются, в настройки попадает только итоговая громкость.
+/// Останавливается, если громкость поменяли вручную или воспроизведение выключили.
+pub async fn ramp_volume(player: &Player, from: u8, to: u8, duration: Duration) {
+    let mut current = from;
+    for step in 1..=RAMP_STEPS {
+        tokio::time::sleep(duration / RAMP_STEPS).await;
+        let state = player.now_playing();
+        if state.station.is_none() || state.volume != current {
+            return;
+        }
+        let progress = f64::from(step) / f64::from(RAMP_STEPS);
+        let volume = (f64::from(from) + (f64::from(to) - f64::from(from)) * progress).round() as u8;
+        if step == RAMP_STEPS {
+            player.request(PlayerRequest::SetVolume(volume));
+        } else if volume != current {
+            player.request(PlayerRequest::RampVolume(volume));
+            current = volume;
+        }
+    }
+}
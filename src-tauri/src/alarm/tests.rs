@@ -0,0 +1,111 @@
+use super::*;
+use chrono::FixedOffset;
+use std::sync::Mutex;
+
+fn alarm(time: &str, weekdays: &[u8]) -> Alarm {
+    Alarm {
+        id: "alarm_1".to_string(),
+        enabled: true,
+        time: time.to_string(),
+        weekdays: weekdays.to_vec(),
+        station_id: "amg_ruwave".to_string(),
+        start_volume: 10,
+        ramp_seconds: 0,
+    }
+}
+
+/// 2024-05-15 — среда
+fn at(date: &str, time: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(&format!("{}T{}:00+03:00", date, time)).unwrap()
+}
+
+#[test]
+fn next_occurrence_respects_time_and_weekdays() {
+    let now = at("2024-05-15", "06:00");
+    // Каждый день: сегодня, если время ещё не прошло, иначе завтра
+    assert_eq!(
+        next_occurrence(&alarm("07:30", &[]), &now),
+        Some(at("2024-05-15", "07:30"))
+    );
+    assert_eq!(
+        next_occurrence(&alarm("05:30", &[]), &now),
+        Some(at("2024-05-16", "05:30"))
+    );
+    // Только выходные
+    assert_eq!(
+        next_occurrence(&alarm("07:30", &[6, 7]), &now),
+        Some(at("2024-05-18", "07:30"))
+    );
+    // Только среда, время прошло — через неделю
+    assert_eq!(
+        next_occurrence(&alarm("05:00", &[3]), &now),
+        Some(at("2024-05-22", "05:00"))
+    );
+    // Ровно в момент срабатывания следующий — завтра
+    assert_eq!(
+        next_occurrence(&alarm("06:00", &[]), &now),
+        Some(at("2024-05-16", "06:00"))
+    );
+}
+
+#[test]
+fn validate_rejects_bad_alarms() {
+    assert!(alarm("07:30", &[1, 5]).validate().is_ok());
+    assert!(alarm("7:30", &[]).validate().is_ok());
+    assert!(alarm("25:00", &[]).validate().is_err());
+    assert!(alarm("утро", &[]).validate().is_err());
+    assert!(alarm("07:30", &[0]).validate().is_err());
+    assert!(alarm("07:30", &[8]).validate().is_err());
+
+    let mut loud = alarm("07:30", &[]);
+    loud.start_volume = 101;
+    assert!(loud.validate().is_err());
+}
+
+#[tokio::test]
+async fn ramp_raises_volume_until_changed_by_hand() {
+    let player = Arc::new(Player::new(10));
+    let requests: Arc<Mutex<Vec<u8>>> = Default::default();
+    let saved: Arc<Mutex<Vec<u8>>> = Default::default();
+    let sink = requests.clone();
+    let saved_sink = saved.clone();
+    let weak = Arc::downgrade(&player);
+    player.set_handler(Arc::new(move |request| {
+        let Some(player) = weak.upgrade() else {
+            return;
+        };
+        match request {
+            PlayerRequest::RampVolume(volume) => {
+                player.set_volume(volume);
+                sink.lock().unwrap().push(volume);
+            }
+            PlayerRequest::SetVolume(volume) => {
+                player.set_volume(volume);
+                sink.lock().unwrap().push(volume);
+                saved_sink.lock().unwrap().push(volume);
+            }
+            _ => {}
+        }
+    }));
+    let station = crate::sources::AmgSource::new_station("ruwave", "Русская Волна", "http://s");
+    player.set_playback(Some(station), true);
+
+    ramp_volume(&player, 10, 50, Duration::from_millis(200)).await;
+    let volumes = requests.lock().unwrap().clone();
+    assert!(volumes.windows(2).all(|w| w[0] < w[1]), "{:?}", volumes);
+    assert_eq!(volumes.last(), Some(&50));
+    // Сохраняется только итоговая громкость
+    assert_eq!(*saved.lock().unwrap(), [50]);
+
+    // Громкость изменили вручную — подъём прекращается
+    requests.lock().unwrap().clear();
+    let ramp = ramp_volume(&player, 50, 90, Duration::from_millis(400));
+    let manual = async {
+        tokio::time::sleep(Duration::from_millis(90)).await;
+        player.set_volume(30);
+    };
+    tokio::join!(ramp, manual);
+    assert_eq!(player.now_playing().volume, 30);
+    assert!(requests.lock().unwrap().iter().all(|v| *v < 90));
+    assert_eq!(*saved.lock().unwrap(), [50]);
+}
@@ -0,0 +1,180 @@
+//! Резервная копия для переноса на другой компьютер: избранное, свои станции, история треков
+//! и настройки уведомлений и потока. Кэш каталогов не сохраняется — он загрузится заново.
+//!
+//! Один JSON-файл с форматом и версией. Импорт заменяет данные целиком или объединяет
+//! их с текущими: станции с одинаковым ID берутся из копии, записи истории не дублируются.
+
+#[cfg(test)]
+mod tests;
+
+use crate::error::{AppError, AppResult};
+use crate::models::RadioStation;
+use crate::services::{TrackHistory, TrackHistoryEntry};
+use crate::sources::CustomSource;
+use crate::store::Store;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Метка формата файла
+const FORMAT: &str = "intyrnet-radivo-backup";
+
+/// Текущая версия формата
+pub const BACKUP_VERSION: u32 = 1;
+
+/// Сколько записей истории сохранять в копию
+const MAX_HISTORY: usize = 5000;
+
+/// Настройки, которые переносятся вместе с копией
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupPreferences {
+    pub streaming_mode: String,
+    pub show_notifications: bool,
+    pub show_station_notifications: bool,
+}
+
+/// Содержимое файла резервной копии
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Backup {
+    pub format: String,
+    pub version: u32,
+    /// Когда создана (Unix, мс)
+    pub created_at_ms: i64,
+    pub preferences: BackupPreferences,
+    #[serde(default)]
+    pub favorites: Vec<RadioStation>,
+    #[serde(default)]
+    pub custom_stations: Vec<RadioStation>,
+    /// От старых записей к новым
+    #[serde(default)]
+    pub track_history: Vec<TrackHistoryEntry>,
+}
+
+/// Как импортировать копию
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Объединить с текущими данными
+    Merge,
+    /// Заменить текущие данные
+    Replace,
+}
+
+/// Итог экспорта или импорта
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupSummary {
+    pub favorites: usize,
+    pub custom_stations: usize,
+    /// Сохранено записей истории (при импорте — добавлено новых)
+    pub history_entries: usize,
+}
+
+impl Backup {
+    /// Собрать копию из базы и настроек
+    pub fn collect(store: &Store, preferences: BackupPreferences) -> AppResult<Self> {
+        let mut track_history = store.history(None, None, MAX_HISTORY)?;
+        track_history.reverse();
+        Ok(Self {
+            format: FORMAT.to_string(),
+            version: BACKUP_VERSION,
+            created_at_ms: chrono::Utc::now().timestamp_millis(),
+            preferences,
+            favorites: store.favorites()?,
+            custom_stations: store.source_stations(CustomSource::ID)?.unwrap_or_default(),
+            track_history,
+        })
+    }
+
+    pub fn summary(&self) -> BackupSummary {
+        BackupSummary {
+            favorites: self.favorites.len(),
+            custom_stations: self.custom_stations.len(),
+            history_entries: self.track_history.len(),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> AppResult<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .map_err(|e| AppError::from(e).context("Ошибка записи резервной копии"))
+    }
+
+    /// Прочитать копию и проверить формат и версию
+    pub fn read(path: &Path) -> AppResult<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| AppError::from(e).context("Ошибка чтения резервной копии"))?;
+        Self::parse(&content)
+    }
+
+    pub fn parse(content: &str) -> AppResult<Self> {
+        let value: serde_json::Value = serde_json::from_str(content)
+            .map_err(|_| AppError::invalid_input("Файл не является резервной копией"))?;
+        if value.get("format").and_then(|f| f.as_str()) != Some(FORMAT) {
+            return Err(AppError::invalid_input(
+                "Файл не является резервной копией Интырнэт Радиво",
+            ));
+        }
+        match value.get("version").and_then(|v| v.as_u64()) {
+            Some(version) if version > u64::from(BACKUP_VERSION) => {
+                return Err(AppError::invalid_input(format!(
+                    "Резервная копия создана более новой версией приложения (формат {})",
+                    version
+                )))
+            }
+            Some(_) => {}
+            None => return Err(AppError::missing_field("version", "Нет версии формата")),
+        }
+        serde_json::from_value(value)
+            .map_err(|e| AppError::from(e).context("Повреждённая резервная копия"))
+    }
+
+    /// Перенести станции и историю в базу (одной транзакцией: при ошибке база не меняется).
+    /// Настройки применяет вызывающий.
+    pub async fn restore(
+        &self,
+        store: &Store,
+        track_history: &TrackHistory,
+        mode: ImportMode,
+    ) -> AppResult<BackupSummary> {
+        let replace = mode == ImportMode::Replace;
+
+        let current = if replace {
+            Vec::new()
+        } else {
+            store.favorites()?
+        };
+        let favorites = merge_by_id(current, &self.favorites);
+
+        let custom = CustomSource::ID;
+        let current = if replace {
+            Vec::new()
+        } else {
+            store.source_stations(custom)?.unwrap_or_default()
+        };
+        let custom_stations = merge_by_id(current, &self.custom_stations);
+
+        let history_entries = track_history
+            .import(
+                self.track_history.clone(),
+                replace,
+                |history, max_entries| {
+                    store.restore_backup(&favorites, custom, &custom_stations, history, max_entries)
+                },
+            )
+            .await?;
+
+        Ok(BackupSummary {
+            history_entries,
+            ..self.summary()
+        })
+    }
+}
+
+/// Станции из копии заменяют текущие с тем же ID (на их месте), новые — в конец
+fn merge_by_id(mut current: Vec<RadioStation>, imported: &[RadioStation]) -> Vec<RadioStation> {
+    for station in imported {
+        match current.iter_mut().find(|s| s.id == station.id) {
+            Some(existing) => *existing = station.clone(),
+            None => current.push(station.clone()),
+        }
+    }
+    current
+}
@@ -0,0 +1,148 @@
+use super::*;
+use std::sync::Arc;
+
+fn station(id: &str, name: &str) -> RadioStation {
+    let mut station = CustomSource::new_station(&format!("http://stream/{}", id), name);
+    station.id = id.to_string();
+    station
+}
+
+fn entry(station_id: &str, played_at_ms: i64) -> TrackHistoryEntry {
+    TrackHistoryEntry {
+        station_id: station_id.to_string(),
+        station_name: station_id.to_string(),
+        artist: None,
+        title: Some(format!("трек {}", played_at_ms)),
+        cover_url: None,
+        played_at_ms,
+        stop_at_ms: None,
+    }
+}
+
+fn names(stations: &[RadioStation]) -> Vec<(&str, &str)> {
+    stations
+        .iter()
+        .map(|s| (s.id.as_str(), s.name.as_str()))
+        .collect()
+}
+
+fn preferences() -> BackupPreferences {
+    BackupPreferences {
+        streaming_mode: "hls".to_string(),
+        show_notifications: false,
+        show_station_notifications: true,
+    }
+}
+
+/// База «домашнего» компьютера, с которой сделана копия
+fn home_backup() -> Backup {
+    let store = Store::open(None).unwrap();
+    store
+        .replace_favorites(&[station("a", "A (дом)"), station("c", "C")])
+        .unwrap();
+    store
+        .replace_source_stations("custom", &[station("c", "C")])
+        .unwrap();
+    store
+        .append_history(&[entry("a", 100), entry("c", 300)], 100)
+        .unwrap();
+    Backup::collect(&store, preferences()).unwrap()
+}
+
+/// База «рабочего» компьютера, куда копия импортируется
+fn work_store() -> Arc<Store> {
+    let store = Arc::new(Store::open(None).unwrap());
+    store
+        .replace_favorites(&[station("b", "B"), station("a", "A (работа)")])
+        .unwrap();
+    store
+        .replace_source_stations("custom", &[station("b", "B")])
+        .unwrap();
+    store
+        .append_history(&[entry("a", 100), entry("b", 200)], 100)
+        .unwrap();
+    store
+}
+
+#[test]
+fn backup_round_trips_and_rejects_foreign_files() {
+    let backup = home_backup();
+    assert_eq!(backup.version, BACKUP_VERSION);
+    assert_eq!(backup.preferences, preferences());
+    assert_eq!(backup.favorites.len(), 2);
+    assert_eq!(backup.custom_stations.len(), 1);
+    // История в копии — от старых к новым
+    let played: Vec<i64> = backup
+        .track_history
+        .iter()
+        .map(|e| e.played_at_ms)
+        .collect();
+    assert_eq!(played, vec![100, 300]);
+
+    let json = serde_json::to_string(&backup).unwrap();
+    let parsed = Backup::parse(&json).unwrap();
+    assert_eq!(names(&parsed.favorites), names(&backup.favorites));
+
+    assert!(Backup::parse("{ не json").is_err());
+    assert!(Backup::parse(r#"{ "volume": 50 }"#).is_err());
+    let newer = json.replacen(
+        &format!("\"version\":{}", BACKUP_VERSION),
+        &format!("\"version\":{}", BACKUP_VERSION + 1),
+        1,
+    );
+    let error = Backup::parse(&newer).unwrap_err();
+    assert!(error.message.contains("более новой версией"), "{}", error);
+}
+
+#[tokio::test]
+async fn merge_resolves_conflicts_by_station_id() {
+    let backup = home_backup();
+    let store = work_store();
+    let history = TrackHistory::new(store.clone());
+
+    let summary = backup
+        .restore(&store, &history, ImportMode::Merge)
+        .await
+        .unwrap();
+    assert_eq!(summary.favorites, 2);
+    // Запись "a" в 100 уже была — добавлена только одна
+    assert_eq!(summary.history_entries, 1);
+
+    assert_eq!(
+        names(&store.favorites().unwrap()),
+        vec![("b", "B"), ("a", "A (дом)"), ("c", "C")]
+    );
+    assert_eq!(
+        names(&store.source_stations("custom").unwrap().unwrap()),
+        vec![("b", "B"), ("c", "C")]
+    );
+    let played: Vec<i64> = history
+        .get(None, None, 10)
+        .await
+        .iter()
+        .map(|e| e.played_at_ms)
+        .collect();
+    assert_eq!(played, vec![300, 200, 100]);
+}
+
+#[tokio::test]
+async fn replace_drops_current_data() {
+    let backup = home_backup();
+    let store = work_store();
+    let history = TrackHistory::new(store.clone());
+
+    backup
+        .restore(&store, &history, ImportMode::Replace)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        names(&store.favorites().unwrap()),
+        vec![("a", "A (дом)"), ("c", "C")]
+    );
+    assert_eq!(
+        names(&store.source_stations("custom").unwrap().unwrap()),
+        vec![("c", "C")]
+    );
+    assert_eq!(history.get(None, None, 10).await.len(), 2);
+}
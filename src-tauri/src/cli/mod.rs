@@ -0,0 +1,344 @@
+//! Консольный режим без окна: станции, URL потоков, текущие треки, экспорт избранного.
+//!
+//! Использует тот же `StationService` и базу станций, что и приложение, а загруженные
+//! каталоги попадают в общий кэш. Базу открывает `open_store`, как и приложение: станции
+//! прежних версий и история треков переносятся в неё из старых файлов (settings.json при
+//! этом переписывается). Других изменений настроек нет.
+
+use crate::error::{AppError, AppResult};
+use crate::models::RadioStation;
+use crate::playlist::{self, PlaylistFormat};
+use crate::services::StationService;
+use crate::store::Store;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[cfg(test)]
+mod tests;
+
+const USAGE: &str = "Интырнэт Радиво — консольный режим
+
+Использование:
+  intyrnet-radivo sources [--json]
+  intyrnet-radivo stations [--source ID] [--cached] [--json]
+  intyrnet-radivo stream-url STATION_ID
+  intyrnet-radivo now-playing STATION_ID [--json]
+  intyrnet-radivo favorites [list] [--json]
+  intyrnet-radivo favorites export [--format m3u8|pls|xspf] [--output FILE]
+
+Без аргументов запускается окно приложения.
+Пример: mpv \"$(intyrnet-radivo stream-url ru101_100)\"";
+
+/// Команда консольного режима
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Help,
+    Sources {
+        json: bool,
+    },
+    Stations {
+        source: Option<String>,
+        cached: bool,
+        json: bool,
+    },
+    StreamUrl {
+        station_id: String,
+    },
+    NowPlaying {
+        station_id: String,
+        json: bool,
+    },
+    Favorites {
+        json: bool,
+    },
+    ExportFavorites {
+        format: PlaylistFormat,
+        output: Option<PathBuf>,
+    },
+}
+
+/// Выполнить команду, если аргументы — команда консольного режима.
+/// Возвращает код завершения или None, если нужно запустить окно.
+pub fn run(args: &[String]) -> Option<i32> {
+    let command = parse(args)?;
+    #[cfg(windows)]
+    attach_parent_console();
+
+    let command = match command {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("❌ {}\n\n{}", e.message, USAGE);
+            return Some(2);
+        }
+    };
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("не удалось запустить tokio");
+    match runtime.block_on(execute(command)) {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("❌ {}", e.message);
+            Some(1)
+        }
+    }
+}
+
+/// Разобрать аргументы (без имени программы). None — это не команда консольного режима.
+fn parse(args: &[String]) -> Option<AppResult<Command>> {
+    let (name, rest) = args.split_first()?;
+    let mut flags = Flags::new(rest);
+    let command = match name.as_str() {
+        "help" | "--help" | "-h" => Ok(Command::Help),
+        "sources" => Ok(Command::Sources {
+            json: flags.switch("--json"),
+        }),
+        "stations" => flags.value("--source").map(|source| Command::Stations {
+            source,
+            cached: flags.switch("--cached"),
+            json: flags.switch("--json"),
+        }),
+        "stream-url" => flags
+            .positional("STATION_ID")
+            .map(|station_id| Command::StreamUrl { station_id }),
+        "now-playing" => {
+            let json = flags.switch("--json");
+            flags
+                .positional("STATION_ID")
+                .map(|station_id| Command::NowPlaying { station_id, json })
+        }
+        "favorites" => match flags.subcommand().as_deref() {
+            None | Some("list") => Ok(Command::Favorites {
+                json: flags.switch("--json"),
+            }),
+            Some("export") => flags.value("--format").and_then(|format| {
+                let format = format.unwrap_or_else(|| "m3u8".to_string());
+                let format = PlaylistFormat::from_name(&format).ok_or_else(|| {
+                    AppError::invalid_input(format!("Неизвестный формат плейлиста: {}", format))
+                })?;
+                let output = flags.value("--output")?.map(PathBuf::from);
+                Ok(Command::ExportFavorites { format, output })
+            }),
+            Some(other) => Err(AppError::invalid_input(format!(
+                "Неизвестная команда: favorites {}",
+                other
+            ))),
+        },
+        _ => return None,
+    };
+    Some(command.and_then(|command| flags.finish().map(|_| command)))
+}
+
+/// Разбор флагов команды: `--name value`, `--name=value`, `--switch` и позиционные аргументы
+struct Flags {
+    args: Vec<String>,
+}
+
+impl Flags {
+    fn new(args: &[String]) -> Self {
+        Self {
+            args: args.to_vec(),
+        }
+    }
+
+    fn switch(&mut self, name: &str) -> bool {
+        let before = self.args.len();
+        self.args.retain(|a| a != name);
+        self.args.len() != before
+    }
+
+    fn value(&mut self, name: &str) -> AppResult<Option<String>> {
+        let prefix = format!("{}=", name);
+        if let Some(pos) = self.args.iter().position(|a| a.starts_with(&prefix)) {
+            let arg = self.args.remove(pos);
+            return Ok(Some(arg[prefix.len()..].to_string()));
+        }
+        let Some(pos) = self.args.iter().position(|a| a == name) else {
+            return Ok(None);
+        };
+        // Следующий флаг — не значение
+        if self
+            .args
+            .get(pos + 1)
+            .is_none_or(|next| next.starts_with("--"))
+        {
+            return Err(AppError::invalid_input(format!(
+                "Не указано значение {}",
+                name
+            )));
+        }
+        self.args.remove(pos);
+        Ok(Some(self.args.remove(pos)))
+    }
+
+    /// Первый аргумент, если это не флаг
+    fn subcommand(&mut self) -> Option<String> {
+        match self.args.first() {
+            Some(arg) if !arg.starts_with('-') => Some(self.args.remove(0)),
+            _ => None,
+        }
+    }
+
+    /// Первый аргумент, который не флаг (флаги могут стоять перед ним)
+    fn positional(&mut self, name: &str) -> AppResult<String> {
+        match self.args.iter().position(|a| !a.starts_with('-')) {
+            Some(pos) => Ok(self.args.remove(pos)),
+            None => Err(AppError::invalid_input(format!("Не указан {}", name))),
+        }
+    }
+
+    /// Ошибка, если остались неразобранные аргументы
+    fn finish(self) -> AppResult<()> {
+        match self.args.first() {
+            Some(arg) => Err(AppError::invalid_input(format!(
+                "Неизвестный аргумент: {}",
+                arg
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+async fn execute(command: Command) -> AppResult<()> {
+    let store = crate::open_store();
+    // Избранное — текущего профиля приложения
+    if let Ok((settings, _)) = crate::AppSettings::load() {
+        store.set_profile(&settings.profiles.active);
+    }
+    let station_service = Arc::new(StationService::new(store.clone()));
+
+    match command {
+        Command::Help => println!("{}", USAGE),
+        Command::Sources { json } => {
+            let sources = station_service.list_sources();
+            if json {
+                print_json(&sources)?;
+            } else {
+                for source in sources {
+                    println!("{}\t{}", source.id, source.name);
+                }
+            }
+        }
+        Command::Stations {
+            source,
+            cached,
+            json,
+        } => {
+            let source_ids = match source {
+                Some(source) if station_service.has_source(&source) => vec![source],
+                Some(source) => {
+                    return Err(AppError::not_found(format!(
+                        "Неизвестный источник: {}",
+                        source
+                    )))
+                }
+                None => station_service
+                    .list_sources()
+                    .into_iter()
+                    .map(|info| info.id)
+                    .collect(),
+            };
+            let mut stations = Vec::new();
+            for source_id in source_ids {
+                if cached {
+                    stations.extend(
+                        station_service
+                            .get_cached_stations(&source_id)
+                            .await
+                            .unwrap_or_default(),
+                    );
+                } else {
+                    stations.extend(station_service.fetch_stations(&source_id).await?);
+                }
+            }
+            print_stations(&stations, json)?;
+        }
+        Command::StreamUrl { station_id } => {
+            let station = find_station(&station_service, &store, &station_id).await?;
+            println!("{}", station_service.get_stream_url(&station).await?);
+        }
+        Command::NowPlaying { station_id, json } => {
+            let mut station = find_station(&station_service, &store, &station_id).await?;
+            station_service.update_metadata(&mut station).await?;
+            if json {
+                print_json(&station)?;
+            } else {
+                let track = station.current_track.as_deref().filter(|t| !t.is_empty());
+                let artist = station.current_artist.as_deref().filter(|a| !a.is_empty());
+                match (artist, track) {
+                    (Some(artist), Some(track)) => println!("{} — {}", artist, track),
+                    (None, Some(track)) => println!("{}", track),
+                    _ => println!("{}", station.name),
+                }
+            }
+        }
+        Command::Favorites { json } => print_stations(&store.favorites()?, json)?,
+        Command::ExportFavorites { format, output } => {
+            let entries = crate::playlist_entries(&station_service, &store.favorites()?).await;
+            let content = playlist::write_playlist(format, &entries);
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, content)?;
+                    eprintln!("💾 Экспортировано станций: {}", entries.len());
+                }
+                None => print!("{}", content),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Станция по ID: избранное, кэш каталогов, затем свежий каталог источника (по префиксу ID)
+async fn find_station(
+    station_service: &StationService,
+    store: &Store,
+    station_id: &str,
+) -> AppResult<RadioStation> {
+    if let Some(station) = store.find_favorite(station_id)? {
+        return Ok(station);
+    }
+    if let Some(station) = station_service.find_station_by_id(station_id).await {
+        return Ok(station);
+    }
+
+    let source = station_service
+        .list_sources()
+        .into_iter()
+        .filter(|info| info.capabilities.catalog)
+        .find(|info| station_id.starts_with(&format!("{}_", info.id)));
+    if let Some(source) = source {
+        let stations = station_service.fetch_stations(&source.id).await?;
+        if let Some(station) = stations.into_iter().find(|s| s.id == station_id) {
+            return Ok(station);
+        }
+    }
+    Err(AppError::not_found(format!(
+        "Станция {} не найдена",
+        station_id
+    )))
+}
+
+fn print_stations(stations: &[RadioStation], json: bool) -> AppResult<()> {
+    if json {
+        return print_json(&stations);
+    }
+    for station in stations {
+        println!("{}\t{}", station.id, station.name);
+    }
+    Ok(())
+}
+
+fn print_json<T: Serialize + ?Sized>(value: &T) -> AppResult<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// В release-сборке под Windows у приложения нет своей консоли — пишем в консоль, из которой запустили
+#[cfg(windows)]
+fn attach_parent_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
@@ -0,0 +1,139 @@
+use super::*;
+
+fn parsed(args: &str) -> Option<AppResult<Command>> {
+    let args: Vec<String> = args.split_whitespace().map(String::from).collect();
+    parse(&args)
+}
+
+fn command(args: &str) -> Command {
+    parsed(args).expect("не команда").expect(args)
+}
+
+fn error(args: &str) -> String {
+    match parsed(args).expect("не команда") {
+        Ok(command) => panic!("{}: ожидалась ошибка, а получено {:?}", args, command),
+        Err(e) => e.message,
+    }
+}
+
+#[test]
+fn window_starts_without_a_known_command() {
+    for args in ["", "--minimized", "play ru101_100", "Stations"] {
+        assert!(parsed(args).is_none(), "{:?}", args);
+    }
+}
+
+#[test]
+fn every_subcommand_is_parsed() {
+    let cases = [
+        ("help", Command::Help),
+        ("--help", Command::Help),
+        ("-h", Command::Help),
+        ("sources", Command::Sources { json: false }),
+        ("sources --json", Command::Sources { json: true }),
+        (
+            "stations",
+            Command::Stations {
+                source: None,
+                cached: false,
+                json: false,
+            },
+        ),
+        (
+            "stations --json --source ru101 --cached",
+            Command::Stations {
+                source: Some("ru101".to_string()),
+                cached: true,
+                json: true,
+            },
+        ),
+        (
+            "stations --source=radiobrowser",
+            Command::Stations {
+                source: Some("radiobrowser".to_string()),
+                cached: false,
+                json: false,
+            },
+        ),
+        (
+            "stream-url ru101_100",
+            Command::StreamUrl {
+                station_id: "ru101_100".to_string(),
+            },
+        ),
+        (
+            "now-playing --json amg_ruwave",
+            Command::NowPlaying {
+                station_id: "amg_ruwave".to_string(),
+                json: true,
+            },
+        ),
+        (
+            "now-playing amg_ruwave",
+            Command::NowPlaying {
+                station_id: "amg_ruwave".to_string(),
+                json: false,
+            },
+        ),
+        ("favorites", Command::Favorites { json: false }),
+        ("favorites --json", Command::Favorites { json: true }),
+        ("favorites list --json", Command::Favorites { json: true }),
+        (
+            "favorites export",
+            Command::ExportFavorites {
+                format: PlaylistFormat::M3u,
+                output: None,
+            },
+        ),
+        (
+            "favorites export --output out.pls --format PLS",
+            Command::ExportFavorites {
+                format: PlaylistFormat::Pls,
+                output: Some(PathBuf::from("out.pls")),
+            },
+        ),
+        (
+            "favorites export --format=.xspf",
+            Command::ExportFavorites {
+                format: PlaylistFormat::Xspf,
+                output: None,
+            },
+        ),
+    ];
+
+    for (args, expected) in cases {
+        assert_eq!(command(args), expected, "{}", args);
+    }
+}
+
+#[test]
+fn unknown_flags_and_missing_arguments_are_errors() {
+    let cases = [
+        ("sources --verbose", "Неизвестный аргумент: --verbose"),
+        ("stations --json extra", "Неизвестный аргумент: extra"),
+        ("stations --source", "Не указано значение --source"),
+        ("stations --source --json", "Не указано значение --source"),
+        ("stream-url", "Не указан STATION_ID"),
+        (
+            "stream-url --json ru101_100",
+            "Неизвестный аргумент: --json",
+        ),
+        (
+            "stream-url ru101_100 ru101_200",
+            "Неизвестный аргумент: ru101_200",
+        ),
+        ("now-playing --json", "Не указан STATION_ID"),
+        ("favorites remove", "Неизвестная команда: favorites remove"),
+        ("favorites list --cached", "Неизвестный аргумент: --cached"),
+        (
+            "favorites export --format wav",
+            "Неизвестный формат плейлиста: wav",
+        ),
+        ("favorites export --output", "Не указано значение --output"),
+        ("help me", "Неизвестный аргумент: me"),
+    ];
+
+    for (args, expected) in cases {
+        assert_eq!(error(args), expected, "{}", args);
+    }
+}
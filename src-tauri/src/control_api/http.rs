@@ -0,0 +1,174 @@
+//! Минимальный разбор HTTP/1.1: один запрос на соединение, ответ с `Connection: close`.
+
+use crate::error::{AppError, AppResult, ErrorKind};
+use serde::Serialize;
+use std::collections::HashMap;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Максимальный размер заголовков запроса
+const MAX_HEAD_SIZE: usize = 16 * 1024;
+
+/// Максимальный размер тела запроса
+const MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// Запрос к API
+#[derive(Debug)]
+pub struct Request {
+    pub method: String,
+    /// Путь без query
+    pub path: String,
+    pub query: HashMap<String, String>,
+    /// Заголовки (имена в нижнем регистре)
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+
+    /// Токен из `Authorization: Bearer ...` или `X-Api-Token`
+    pub fn token(&self) -> Option<&str> {
+        self.header("authorization")
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .or_else(|| self.header("x-api-token"))
+            .map(str::trim)
+    }
+
+    /// Разобрать JSON-тело (пустое тело — `{}`)
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> AppResult<T> {
+        let body = if self.body.iter().all(u8::is_ascii_whitespace) {
+            b"{}".as_slice()
+        } else {
+            &self.body
+        };
+        serde_json::from_slice(body)
+            .map_err(|e| AppError::invalid_input(format!("Неверное тело запроса: {}", e)))
+    }
+}
+
+/// Ответ API (всегда JSON)
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    pub fn json<T: Serialize>(value: &T) -> Self {
+        match serde_json::to_string(value) {
+            Ok(body) => Self { status: 200, body },
+            Err(e) => Self::error(&AppError::from(e)),
+        }
+    }
+
+    pub fn error(error: &AppError) -> Self {
+        let status = match error.kind {
+            ErrorKind::NotFound => 404,
+            ErrorKind::InvalidInput | ErrorKind::MissingField(_) => 400,
+            ErrorKind::RateLimited(_) => 429,
+            ErrorKind::Network | ErrorKind::Http(_) => 502,
+            ErrorKind::Parse | ErrorKind::Io | ErrorKind::Unsupported => 500,
+        };
+        Self::with_status(status, error)
+    }
+
+    pub fn with_status<T: Serialize>(status: u16, value: &T) -> Self {
+        Self {
+            status,
+            body: serde_json::to_string(value).unwrap_or_default(),
+        }
+    }
+}
+
+/// Прочитать запрос: заголовки и тело по Content-Length
+pub async fn read_request<S: AsyncRead + Unpin>(stream: &mut S) -> AppResult<Request> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if buf.len() > MAX_HEAD_SIZE {
+            return Err(AppError::invalid_input("Слишком большие заголовки"));
+        }
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Err(AppError::network("Соединение закрыто до конца заголовков"));
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let (method, target) = match (request_line.next(), request_line.next()) {
+        (Some(method), Some(target)) => (method.to_uppercase(), target),
+        _ => return Err(AppError::invalid_input("Неверная строка запроса")),
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = url::form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect();
+
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let length: usize = match headers.get("content-length") {
+        Some(value) => value
+            .parse()
+            .map_err(|_| AppError::invalid_input("Неверный Content-Length"))?,
+        None => 0,
+    };
+    if length > MAX_BODY_SIZE {
+        return Err(AppError::invalid_input("Слишком большое тело запроса"));
+    }
+    while buf.len() < head_end + length {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Err(AppError::network("Соединение закрыто до конца тела"));
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let body = buf[head_end..head_end + length].to_vec();
+
+    Ok(Request {
+        method,
+        path: path.trim_end_matches('/').to_string(),
+        query,
+        headers,
+        body,
+    })
+}
+
+/// Записать ответ и закрыть запись
+pub async fn write_response<S: AsyncWrite + Unpin>(
+    stream: &mut S,
+    response: &Response,
+) -> std::io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason_phrase(response.status),
+        response.body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(response.body.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        429 => "Too Many Requests",
+        502 => "Bad Gateway",
+        _ => "Internal Server Error",
+    }
+}
@@ -0,0 +1,273 @@
+//! Локальный HTTP/JSON API для управления плеером из скриптов и других программ.
+//!
+//! Выключен по умолчанию, слушает только 127.0.0.1, каждый запрос — с токеном из настроек
+//! (`Authorization: Bearer <token>` или `X-Api-Token: <token>`).
+//!
+//! - `GET /now-playing` — текущая станция, трек, громкость
+//! - `GET /stations[?source=amg]` — станции из кэша каталогов и пользовательские
+//! - `GET /favorites` — избранное
+//! - `POST /play` `{"station_id": "ru101_100"}` (без тела — продолжить)
+//! - `POST /stop`, `POST /next-favorite`, `POST /volume` `{"volume": 40}`
+
+mod http;
+
+#[cfg(test)]
+mod tests;
+
+use crate::error::{AppError, AppResult};
+use crate::player::PlayerRequest;
+use crate::AppState;
+use http::{Request, Response};
+use rand::distributions::{Alphanumeric, DistString};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{oneshot, Mutex};
+
+/// Порт по умолчанию
+pub const DEFAULT_PORT: u16 = 17380;
+
+/// Сколько ждать запрос от клиента
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Длина генерируемого токена
+const TOKEN_LENGTH: usize = 32;
+
+fn default_port() -> u16 {
+    DEFAULT_PORT
+}
+
+/// Настройки HTTP API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ControlApiSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// Токен доступа (создаётся при включении)
+    #[serde(default)]
+    pub token: String,
+}
+
+impl Default for ControlApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+            token: String::new(),
+        }
+    }
+}
+
+/// Новый случайный токен
+pub fn generate_token() -> String {
+    Alphanumeric.sample_string(&mut rand::thread_rng(), TOKEN_LENGTH)
+}
+
+struct ActiveServer {
+    settings: ControlApiSettings,
+    stop: oneshot::Sender<()>,
+}
+
+/// Сервер HTTP API (запускается и останавливается по настройкам)
+pub struct ControlApi {
+    active: Mutex<Option<ActiveServer>>,
+}
+
+impl ControlApi {
+    pub fn new() -> Self {
+        Self {
+            active: Mutex::new(None),
+        }
+    }
+
+    /// Привести сервер в соответствие с настройками: запустить, перезапустить или остановить
+    pub async fn apply(&self, settings: &ControlApiSettings, app: &AppHandle) -> AppResult<()> {
+        let mut active = self.active.lock().await;
+        if active.as_ref().map(|a| &a.settings) == Some(settings) {
+            return Ok(());
+        }
+        if let Some(server) = active.take() {
+            let _ = server.stop.send(());
+        }
+        if !settings.enabled {
+            return Ok(());
+        }
+        if settings.token.is_empty() {
+            return Err(AppError::invalid_input("Не задан токен HTTP API"));
+        }
+
+        let listener = TcpListener::bind(("127.0.0.1", settings.port))
+            .await
+            .map_err(|e| {
+                AppError::io(format!("Не удалось открыть порт {}: {}", settings.port, e))
+            })?;
+        let (stop, stop_rx) = oneshot::channel();
+        tauri::async_runtime::spawn(serve(
+            listener,
+            settings.token.clone(),
+            app.clone(),
+            stop_rx,
+        ));
+        eprintln!("🌐 HTTP API: http://127.0.0.1:{}", settings.port);
+
+        *active = Some(ActiveServer {
+            settings: settings.clone(),
+            stop,
+        });
+        Ok(())
+    }
+}
+
+impl Default for ControlApi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn serve(
+    listener: TcpListener,
+    token: String,
+    app: AppHandle,
+    mut stop: oneshot::Receiver<()>,
+) {
+    loop {
+        tokio::select! {
+            _ = &mut stop => break,
+            accepted = listener.accept() => match accepted {
+                Ok((socket, _)) => {
+                    let token = token.clone();
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        handle_connection(socket, &token, &app).await;
+                    });
+                }
+                Err(e) => eprintln!("⚠️ HTTP API: ошибка соединения: {}", e),
+            },
+        }
+    }
+    eprintln!("🌐 HTTP API остановлен");
+}
+
+async fn handle_connection(mut socket: TcpStream, token: &str, app: &AppHandle) {
+    let Ok(request) = tokio::time::timeout(REQUEST_TIMEOUT, http::read_request(&mut socket)).await
+    else {
+        return;
+    };
+    let response = match check_request(request, token) {
+        Ok(route) => execute(route, app)
+            .await
+            .unwrap_or_else(|e| Response::error(&e)),
+        Err(response) => response,
+    };
+    let _ = http::write_response(&mut socket, &response).await;
+}
+
+/// Проверить токен и определить маршрут; ошибка — готовый ответ клиенту
+fn check_request(request: AppResult<Request>, token: &str) -> Result<Route, Response> {
+    let request = request.map_err(|e| Response::error(&e))?;
+    if !token_matches(request.token(), token) {
+        return Err(Response::with_status(
+            401,
+            &serde_json::json!({ "code": "unauthorized", "message": "Неверный токен" }),
+        ));
+    }
+    resolve(&request)
+}
+
+/// Сравнение токенов за постоянное время
+fn token_matches(given: Option<&str>, expected: &str) -> bool {
+    let Some(given) = given else {
+        return false;
+    };
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+#[derive(Deserialize)]
+struct PlayBody {
+    station_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct VolumeBody {
+    volume: u8,
+}
+
+/// Запрошенное действие
+#[derive(Debug, PartialEq, Eq)]
+enum Route {
+    NowPlaying,
+    Favorites,
+    /// Станции источника (None — всех)
+    Stations(Option<String>),
+    Player(PlayerRequest),
+}
+
+/// Определить действие по методу и пути
+fn resolve(request: &Request) -> Result<Route, Response> {
+    let invalid = |e: AppError| Response::error(&e);
+    Ok(match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/now-playing") => Route::NowPlaying,
+        ("GET", "/favorites") => Route::Favorites,
+        ("GET", "/stations") => Route::Stations(request.query.get("source").cloned()),
+        ("POST", "/play") => Route::Player(PlayerRequest::Play {
+            station_id: request.json::<PlayBody>().map_err(invalid)?.station_id,
+        }),
+        ("POST", "/stop") => Route::Player(PlayerRequest::Stop),
+        ("POST", "/next-favorite") => Route::Player(PlayerRequest::Next),
+        ("POST", "/volume") => Route::Player(PlayerRequest::SetVolume(
+            request.json::<VolumeBody>().map_err(invalid)?.volume,
+        )),
+        (
+            _,
+            "/now-playing" | "/favorites" | "/stations" | "/play" | "/stop" | "/next-favorite"
+            | "/volume",
+        ) => {
+            return Err(Response::with_status(
+                405,
+                &serde_json::json!({ "code": "method_not_allowed", "message": "Метод не поддерживается" }),
+            ))
+        }
+        _ => {
+            return Err(invalid(AppError::not_found(format!(
+                "Неизвестный путь: {}",
+                request.path
+            ))))
+        }
+    })
+}
+
+async fn execute(route: Route, app: &AppHandle) -> AppResult<Response> {
+    let state = app.state::<AppState>();
+    let player_request = match route {
+        Route::NowPlaying => return Ok(Response::json(&state.player.now_playing())),
+        Route::Favorites => return Ok(Response::json(&state.store.favorites()?)),
+        Route::Stations(source) => {
+            let stations = match source {
+                Some(source) if state.station_service.has_source(&source) => state
+                    .station_service
+                    .get_cached_stations(&source)
+                    .await
+                    .unwrap_or_default(),
+                Some(source) => {
+                    return Err(AppError::not_found(format!(
+                        "Неизвестный источник: {}",
+                        source
+                    )))
+                }
+                None => state.station_service.get_all_stations().await,
+            };
+            return Ok(Response::json(&stations));
+        }
+        Route::Player(request) => request,
+    };
+
+    crate::handle_player_request(app, player_request).await?;
+    Ok(Response::json(&state.player.now_playing()))
+}
@@ -0,0 +1,227 @@
+use super::*;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+const TOKEN: &str = "Zq4kP0s9TnW2yB7mLx1cVd8hRf3jGe6a";
+
+/// Разобрать сырой запрос без сети; ошибка — код ответа
+async fn check(raw: &[u8]) -> Result<Route, u16> {
+    check_request(http::read_request(&mut &raw[..]).await, TOKEN).map_err(|r| r.status)
+}
+
+fn authorized(method: &str, target: &str, body: &str) -> Vec<u8> {
+    format!(
+        "{} {} HTTP/1.1\r\nHost: 127.0.0.1\r\nAuthorization: Bearer {}\r\nContent-Length: {}\r\n\r\n{}",
+        method,
+        target,
+        TOKEN,
+        body.len(),
+        body
+    )
+    .into_bytes()
+}
+
+/// Отправить запрос частями через TCP. Ok — маршрут, Err — ответ сервера целиком.
+async fn exchange(parts: Vec<Vec<u8>>) -> Result<Route, String> {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let client = tokio::spawn(async move {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        for part in parts {
+            stream.write_all(&part).await.unwrap();
+            stream.flush().await.unwrap();
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        stream.shutdown().await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    });
+
+    let (mut socket, _) = listener.accept().await.unwrap();
+    let checked = check_request(http::read_request(&mut socket).await, TOKEN);
+    let response = match &checked {
+        Ok(_) => Response::json(&"ok"),
+        Err(response) => Response {
+            status: response.status,
+            body: response.body.clone(),
+        },
+    };
+    http::write_response(&mut socket, &response).await.unwrap();
+    let raw_response = client.await.unwrap();
+    checked.map_err(|_| raw_response)
+}
+
+fn status_line(response: &str) -> &str {
+    response.lines().next().unwrap_or_default()
+}
+
+#[tokio::test]
+async fn request_split_across_tcp_writes_is_assembled() {
+    let raw = authorized("post", "/volume/?x=1", r#"{"volume": 40}"#);
+    let head_end = raw.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+    let parts = vec![
+        raw[..10].to_vec(),
+        raw[10..head_end + 2].to_vec(),
+        raw[head_end + 2..head_end + 6].to_vec(),
+        raw[head_end + 6..].to_vec(),
+    ];
+    assert_eq!(
+        exchange(parts).await,
+        Ok(Route::Player(PlayerRequest::SetVolume(40)))
+    );
+}
+
+#[tokio::test]
+async fn request_line_query_and_headers_are_parsed() {
+    let raw = b"get /stations/?source=ru101&q=%D0%A0%D1%83+fm HTTP/1.1\r\nX-API-Token:  value \r\nContent-Type: application/json\r\nBroken header line\r\n\r\n";
+    let request = http::read_request(&mut &raw[..]).await.unwrap();
+    assert_eq!(request.method, "GET");
+    assert_eq!(request.path, "/stations");
+    assert_eq!(request.query.get("source").unwrap(), "ru101");
+    assert_eq!(request.query.get("q").unwrap(), "Ру fm");
+    assert_eq!(request.header("content-type"), Some("application/json"));
+    assert_eq!(request.token(), Some("value"));
+    assert!(request.body.is_empty());
+    assert_eq!(request.json::<PlayBody>().unwrap().station_id, None);
+
+    let error = http::read_request(&mut &b"\r\n\r\n"[..]).await.unwrap_err();
+    assert_eq!(error.kind, crate::error::ErrorKind::InvalidInput);
+}
+
+#[tokio::test]
+async fn missing_or_wrong_token_is_rejected_before_routing() {
+    let with_header =
+        |header: &str| format!("GET /nowhere HTTP/1.1\r\n{}\r\n\r\n", header).into_bytes();
+    for raw in [
+        with_header("Host: 127.0.0.1"),
+        with_header("Authorization: Bearer wrong"),
+        with_header(&format!("Authorization: bearer {}", TOKEN)),
+        with_header(&format!("Authorization: Bearer {}x", TOKEN)),
+        with_header(&format!("X-Api-Token: {}", &TOKEN[..TOKEN.len() - 1])),
+    ] {
+        assert_eq!(
+            check(&raw).await,
+            Err(401),
+            "{}",
+            String::from_utf8_lossy(&raw)
+        );
+    }
+
+    let response = exchange(vec![with_header("Authorization: Basic dXNlcg==")])
+        .await
+        .unwrap_err();
+    assert_eq!(status_line(&response), "HTTP/1.1 401 Unauthorized");
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    assert!(head.contains(&format!("Content-Length: {}", body.len())));
+    assert!(head.contains("Connection: close"));
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(body).unwrap()["code"],
+        "unauthorized"
+    );
+
+    assert_eq!(
+        check(&with_header(&format!("X-Api-Token: {}", TOKEN))).await,
+        Err(404)
+    );
+}
+
+#[tokio::test]
+async fn routes_map_to_actions_or_error_statuses() {
+    let play = |id: Option<&str>| {
+        Ok(Route::Player(PlayerRequest::Play {
+            station_id: id.map(String::from),
+        }))
+    };
+    let cases: Vec<(&str, &str, &str, Result<Route, u16>)> = vec![
+        ("GET", "/now-playing", "", Ok(Route::NowPlaying)),
+        ("GET", "/favorites/", "", Ok(Route::Favorites)),
+        ("GET", "/stations", "", Ok(Route::Stations(None))),
+        (
+            "GET",
+            "/stations?source=amg",
+            "",
+            Ok(Route::Stations(Some("amg".to_string()))),
+        ),
+        ("POST", "/play", "", play(None)),
+        ("POST", "/play", " \n", play(None)),
+        (
+            "POST",
+            "/play",
+            r#"{"station_id":"ru101_100"}"#,
+            play(Some("ru101_100")),
+        ),
+        ("POST", "/stop", "", Ok(Route::Player(PlayerRequest::Stop))),
+        (
+            "POST",
+            "/next-favorite",
+            "",
+            Ok(Route::Player(PlayerRequest::Next)),
+        ),
+        (
+            "POST",
+            "/volume",
+            r#"{"volume":0}"#,
+            Ok(Route::Player(PlayerRequest::SetVolume(0))),
+        ),
+        // Неверное тело
+        ("POST", "/volume", "", Err(400)),
+        ("POST", "/volume", r#"{"volume":300}"#, Err(400)),
+        ("POST", "/volume", r#"{"volume":"loud"}"#, Err(400)),
+        ("POST", "/play", "[", Err(400)),
+        // Известный путь, другой метод
+        ("GET", "/play", "", Err(405)),
+        ("DELETE", "/volume", "", Err(405)),
+        ("POST", "/now-playing", "", Err(405)),
+        ("PUT", "/stations", "", Err(405)),
+        // Неизвестный путь
+        ("GET", "/", "", Err(404)),
+        ("GET", "/now-playing/extra", "", Err(404)),
+    ];
+
+    for (method, target, body, expected) in cases {
+        assert_eq!(
+            check(&authorized(method, target, body)).await,
+            expected,
+            "{} {} {}",
+            method,
+            target,
+            body
+        );
+    }
+
+    let response = exchange(vec![authorized("PATCH", "/stop", "")])
+        .await
+        .unwrap_err();
+    assert_eq!(status_line(&response), "HTTP/1.1 405 Method Not Allowed");
+}
+
+#[tokio::test]
+async fn oversized_or_truncated_requests_are_refused() {
+    // Заголовки длиннее 16 КБ без конца блока
+    let mut huge_head = b"GET /now-playing HTTP/1.1\r\nX-Padding: ".to_vec();
+    huge_head.resize(20_000, b'a');
+    let response = exchange(vec![huge_head]).await.unwrap_err();
+    assert_eq!(status_line(&response), "HTTP/1.1 400 Bad Request");
+
+    // Тело больше 64 КБ отклоняется до чтения
+    let head = format!(
+        "POST /play HTTP/1.1\r\nAuthorization: Bearer {}\r\nContent-Length: 70000\r\n\r\n",
+        TOKEN
+    );
+    let response = exchange(vec![head.into_bytes()]).await.unwrap_err();
+    assert_eq!(status_line(&response), "HTTP/1.1 400 Bad Request");
+
+    let bad_length = b"POST /play HTTP/1.1\r\nContent-Length: -1\r\n\r\n";
+    assert_eq!(check(bad_length).await, Err(400));
+
+    // Соединение закрыто раньше, чем пришло всё тело
+    let mut truncated = authorized("POST", "/volume", r#"{"volume":40}"#);
+    truncated.truncate(truncated.len() - 3);
+    let response = exchange(vec![truncated]).await.unwrap_err();
+    assert_eq!(status_line(&response), "HTTP/1.1 502 Bad Gateway");
+
+    let response = exchange(vec![b"GET /now-playing HTTP/1.1\r\n".to_vec()])
+        .await
+        .unwrap_err();
+    assert_eq!(status_line(&response), "HTTP/1.1 502 Bad Gateway");
+}
@@ -0,0 +1,222 @@
+#[cfg(test)]
+mod tests;
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+/// Вид ошибки (машиночитаемый код — `ErrorKind::code`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Сеть недоступна, таймаут, ошибка соединения
+    Network,
+    /// Сервер ответил HTTP-ошибкой
+    Http(u16),
+    /// Ответ не удалось разобрать (вероятно, изменился API или вёрстка)
+    Parse,
+    /// У станции нет нужного поля (например, channel_id)
+    MissingField(String),
+    /// Станция, источник или файл не найдены
+    NotFound,
+    /// Сервер ограничил частоту запросов (429), через сколько секунд можно повторить
+    RateLimited(Option<u64>),
+    /// Ошибка файловой системы
+    Io,
+    /// Неверные параметры команды
+    InvalidInput,
+    /// Данные созданы более новой версией приложения
+    Unsupported,
+}
+
+impl ErrorKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorKind::Network => "network",
+            ErrorKind::Http(_) => "http",
+            ErrorKind::Parse => "parse",
+            ErrorKind::MissingField(_) => "missing_field",
+            ErrorKind::NotFound => "not_found",
+            ErrorKind::RateLimited(_) => "rate_limited",
+            ErrorKind::Io => "io",
+            ErrorKind::InvalidInput => "invalid_input",
+            ErrorKind::Unsupported => "unsupported",
+        }
+    }
+}
+
+/// Ошибка источников, сервисов и команд.
+/// Во фронтенд уходит как `{ code, source, message, status?, field?, retry_after? }`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppError {
+    pub kind: ErrorKind,
+    /// ID источника, в котором произошла ошибка
+    pub source_id: Option<String>,
+    /// Сообщение для пользователя
+    pub message: String,
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            source_id: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn network(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Network, message)
+    }
+
+    pub fn http(status: u16) -> Self {
+        Self::new(
+            ErrorKind::Http(status),
+            format!("Сервер вернул ошибку: {}", status),
+        )
+    }
+
+    pub fn parse(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Parse, message)
+    }
+
+    pub fn missing_field(field: &str, message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::MissingField(field.to_string()), message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::NotFound, message)
+    }
+
+    pub fn rate_limited(retry_after: Option<u64>) -> Self {
+        Self::new(
+            ErrorKind::RateLimited(retry_after),
+            "Слишком много запросов, попробуйте позже",
+        )
+    }
+
+    pub fn io(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Io, message)
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::InvalidInput, message)
+    }
+
+    pub fn unsupported(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Unsupported, message)
+    }
+
+    /// Указать источник (если он ещё не указан)
+    pub fn with_source(mut self, source_id: &str) -> Self {
+        if self.source_id.is_none() {
+            self.source_id = Some(source_id.to_string());
+        }
+        self
+    }
+
+    /// Добавить контекст к сообщению: "контекст: сообщение"
+    pub fn context(mut self, context: &str) -> Self {
+        self.message = format!("{}: {}", context, self.message);
+        self
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+
+    /// Вернуть ответ, если статус успешный, иначе ошибку Http / RateLimited
+    pub fn check_status(response: reqwest::Response) -> AppResult<reqwest::Response> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse().ok());
+            return Err(Self::rate_limited(retry_after));
+        }
+        Err(Self::http(status.as_u16()))
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.source_id {
+            Some(ref source) => write!(f, "[{}] {}", source, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 6)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("source", &self.source_id)?;
+        state.serialize_field("message", &self.message)?;
+        match self.kind {
+            ErrorKind::Http(status) => state.serialize_field("status", &status)?,
+            ErrorKind::MissingField(ref field) => state.serialize_field("field", field)?,
+            ErrorKind::RateLimited(retry_after) => {
+                state.serialize_field("retry_after", &retry_after)?
+            }
+            _ => {}
+        }
+        state.end()
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(e: reqwest::Error) -> Self {
+        if let Some(status) = e.status() {
+            if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                return Self::rate_limited(None);
+            }
+            return Self::http(status.as_u16());
+        }
+        if e.is_decode() {
+            return Self::parse(format!("Неожиданный формат ответа: {}", e));
+        }
+        Self::network(format!("Ошибка сети: {}", e))
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::parse(format!("Ошибка разбора JSON: {}", e))
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            return Self::not_found(format!("Файл не найден: {}", e));
+        }
+        Self::io(e.to_string())
+    }
+}
+
+impl From<url::ParseError> for AppError {
+    fn from(e: url::ParseError) -> Self {
+        Self::parse(format!("Неверный URL: {}", e))
+    }
+}
+
+impl From<roxmltree::Error> for AppError {
+    fn from(e: roxmltree::Error) -> Self {
+        Self::parse(format!("Ошибка разбора XML: {}", e))
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        Self::io(format!("Ошибка базы данных: {}", e))
+    }
+}
@@ -0,0 +1,95 @@
+use super::*;
+use serde_json::{json, Value};
+
+fn serialized(error: &AppError) -> Value {
+    serde_json::to_value(error).unwrap()
+}
+
+#[test]
+fn every_kind_is_serialized_with_its_code_and_fields() {
+    let cases = [
+        (
+            AppError::network("Ошибка сети"),
+            json!({ "code": "network", "source": null, "message": "Ошибка сети" }),
+        ),
+        (
+            AppError::http(503),
+            json!({
+                "code": "http",
+                "source": null,
+                "message": "Сервер вернул ошибку: 503",
+                "status": 503
+            }),
+        ),
+        (
+            AppError::parse("Неожиданный ответ"),
+            json!({ "code": "parse", "source": null, "message": "Неожиданный ответ" }),
+        ),
+        (
+            AppError::missing_field("channel_id", "Нет channel_id"),
+            json!({
+                "code": "missing_field",
+                "source": null,
+                "message": "Нет channel_id",
+                "field": "channel_id"
+            }),
+        ),
+        (
+            AppError::not_found("Станция не найдена"),
+            json!({ "code": "not_found", "source": null, "message": "Станция не найдена" }),
+        ),
+        (
+            AppError::rate_limited(Some(30)),
+            json!({
+                "code": "rate_limited",
+                "source": null,
+                "message": "Слишком много запросов, попробуйте позже",
+                "retry_after": 30
+            }),
+        ),
+        (
+            AppError::io("Нет доступа"),
+            json!({ "code": "io", "source": null, "message": "Нет доступа" }),
+        ),
+        (
+            AppError::invalid_input("Неверный URL"),
+            json!({ "code": "invalid_input", "source": null, "message": "Неверный URL" }),
+        ),
+        (
+            AppError::unsupported("Настройки новее"),
+            json!({ "code": "unsupported", "source": null, "message": "Настройки новее" }),
+        ),
+    ];
+
+    for (error, expected) in cases {
+        assert_eq!(serialized(&error), expected, "{:?}", error.kind);
+    }
+}
+
+#[test]
+fn unknown_retry_after_is_null() {
+    assert_eq!(
+        serialized(&AppError::rate_limited(None))["retry_after"],
+        Value::Null
+    );
+}
+
+#[test]
+fn source_and_context_are_kept() {
+    let error = AppError::http(500)
+        .with_source("ru101")
+        .with_source("amg")
+        .context("Ошибка загрузки станций");
+
+    let value = serialized(&error);
+    assert_eq!(value["source"], "ru101");
+    assert_eq!(
+        value["message"],
+        "Ошибка загрузки станций: Сервер вернул ошибку: 500"
+    );
+    assert_eq!(value["status"], 500);
+    assert_eq!(
+        error.to_string(),
+        "[ru101] Ошибка загрузки станций: Сервер вернул ошибку: 500"
+    );
+}
@@ -0,0 +1,59 @@
+use super::{parse_playlist, HlsPlaylist, HlsVariant};
+use crate::error::{AppError, AppResult};
+
+/// Клиент для загрузки HLS плейлистов
+pub struct HlsClient {
+    client: reqwest::Client,
+}
+
+impl HlsClient {
+    pub fn new() -> Self {
+        let client = reqwest::Client::builder()
+            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+
+        Self { client }
+    }
+
+    /// Загрузить и разобрать плейлист (относительные URI — от итогового URL после редиректов)
+    pub async fn fetch(&self, playlist_url: &str) -> AppResult<HlsPlaylist> {
+        let response = AppError::check_status(self.client.get(playlist_url).send().await?)?;
+        let base_url = response.url().to_string();
+        let content = response.text().await?;
+        parse_playlist(&content, &base_url)
+    }
+
+    /// Варианты потока, от лучшего качества к худшему.
+    /// Для media-плейлиста возвращается один вариант без битрейта.
+    pub async fn fetch_variants(&self, playlist_url: &str) -> AppResult<Vec<HlsVariant>> {
+        match self.fetch(playlist_url).await? {
+            HlsPlaylist::Master(mut variants) => {
+                if variants.is_empty() {
+                    return Err(AppError::parse("Master-плейлист не содержит вариантов"));
+                }
+                variants.sort_by_key(|v| std::cmp::Reverse(v.bandwidth));
+                Ok(variants)
+            }
+            HlsPlaylist::Media(media) => {
+                if media.segments.is_empty() {
+                    return Err(AppError::parse("Плейлист не содержит сегментов"));
+                }
+                Ok(vec![HlsVariant {
+                    uri: playlist_url.to_string(),
+                    bandwidth: None,
+                    average_bandwidth: None,
+                    codecs: None,
+                    resolution: None,
+                }])
+            }
+        }
+    }
+}
+
+impl Default for HlsClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,23 @@
+mod client;
+mod playlist;
+
+#[cfg(test)]
+mod tests;
+
+pub use client::HlsClient;
+pub use playlist::{parse_playlist, HlsPlaylist, HlsVariant};
+
+use serde::Serialize;
+
+/// Доступные варианты воспроизведения станции
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamVariants {
+    /// URL HLS плейлиста станции
+    pub hls_url: Option<String>,
+    /// Варианты HLS, от лучшего качества к худшему (пусто если HLS нет или он сломан)
+    pub variants: Vec<HlsVariant>,
+    /// Почему HLS недоступен
+    pub hls_error: Option<String>,
+    /// Обычный поток (MP3/AAC) для fallback
+    pub fallback_url: Option<String>,
+}
@@ -0,0 +1,151 @@
+use crate::error::{AppError, AppResult};
+use serde::Serialize;
+
+/// Вариант потока из master-плейлиста (`#EXT-X-STREAM-INF`)
+#[derive(Debug, Clone, Serialize)]
+pub struct HlsVariant {
+    /// Абсолютный URL media-плейлиста
+    pub uri: String,
+    /// BANDWIDTH, бит/с (нет для одиночного media-плейлиста)
+    pub bandwidth: Option<u64>,
+    /// AVERAGE-BANDWIDTH, бит/с
+    pub average_bandwidth: Option<u64>,
+    /// CODECS, например "mp4a.40.2"
+    pub codecs: Option<String>,
+    /// RESOLUTION (для видео)
+    pub resolution: Option<String>,
+}
+
+/// Сегмент media-плейлиста
+#[derive(Debug, Clone, Serialize)]
+pub struct HlsSegment {
+    /// Абсолютный URL сегмента
+    pub uri: String,
+    /// Длительность, секунды
+    pub duration: f64,
+    /// Название из `#EXTINF` (часто "Исполнитель - Трек")
+    pub title: Option<String>,
+}
+
+/// Media-плейлист (список сегментов)
+#[derive(Debug, Clone, Serialize)]
+pub struct HlsMediaPlaylist {
+    /// `#EXT-X-TARGETDURATION`, секунды
+    pub target_duration: Option<u64>,
+    /// `#EXT-X-MEDIA-SEQUENCE`
+    pub media_sequence: u64,
+    pub segments: Vec<HlsSegment>,
+    /// `#EXT-X-ENDLIST` (не живой поток)
+    pub ended: bool,
+}
+
+/// Разобранный HLS плейлист
+#[derive(Debug, Clone)]
+pub enum HlsPlaylist {
+    Master(Vec<HlsVariant>),
+    Media(HlsMediaPlaylist),
+}
+
+/// Разобрать HLS плейлист. Относительные URI разрешаются относительно `base_url`.
+pub fn parse_playlist(content: &str, base_url: &str) -> AppResult<HlsPlaylist> {
+    let content = content.trim_start_matches('\u{feff}');
+    if !content.trim_start().starts_with("#EXTM3U") {
+        return Err(AppError::parse("Нет заголовка #EXTM3U"));
+    }
+    let base = url::Url::parse(base_url)?;
+    let resolve =
+        |uri: &str| -> Result<String, url::ParseError> { base.join(uri).map(|u| u.to_string()) };
+
+    if content.contains("#EXT-X-STREAM-INF") {
+        let mut variants = Vec::new();
+        let mut pending: Option<HlsVariant> = None;
+
+        for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if let Some(attrs) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+                let attrs = parse_attributes(attrs);
+                let get = |name: &str| {
+                    attrs
+                        .iter()
+                        .find(|(k, _)| k == name)
+                        .map(|(_, v)| v.clone())
+                };
+                pending = Some(HlsVariant {
+                    uri: String::new(),
+                    bandwidth: get("BANDWIDTH").and_then(|v| v.parse().ok()),
+                    average_bandwidth: get("AVERAGE-BANDWIDTH").and_then(|v| v.parse().ok()),
+                    codecs: get("CODECS"),
+                    resolution: get("RESOLUTION"),
+                });
+            } else if !line.starts_with('#') {
+                if let Some(mut variant) = pending.take() {
+                    variant.uri = resolve(line)?;
+                    variants.push(variant);
+                }
+            }
+        }
+
+        return Ok(HlsPlaylist::Master(variants));
+    }
+
+    let mut playlist = HlsMediaPlaylist {
+        target_duration: None,
+        media_sequence: 0,
+        segments: Vec::new(),
+        ended: false,
+    };
+    let mut pending: Option<(f64, Option<String>)> = None;
+
+    for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Some(value) = line.strip_prefix("#EXT-X-TARGETDURATION:") {
+            playlist.target_duration = value.trim().parse().ok();
+        } else if let Some(value) = line.strip_prefix("#EXT-X-MEDIA-SEQUENCE:") {
+            playlist.media_sequence = value.trim().parse().unwrap_or(0);
+        } else if line.starts_with("#EXT-X-ENDLIST") {
+            playlist.ended = true;
+        } else if let Some(value) = line.strip_prefix("#EXTINF:") {
+            let (duration, title) = value.split_once(',').unwrap_or((value, ""));
+            let title = title.trim();
+            pending = Some((
+                duration.trim().parse().unwrap_or(0.0),
+                if title.is_empty() {
+                    None
+                } else {
+                    Some(title.to_string())
+                },
+            ));
+        } else if !line.starts_with('#') {
+            let (duration, title) = pending.take().unwrap_or((0.0, None));
+            playlist.segments.push(HlsSegment {
+                uri: resolve(line)?,
+                duration,
+                title,
+            });
+        }
+    }
+
+    Ok(HlsPlaylist::Media(playlist))
+}
+
+/// Список атрибутов `KEY=value,KEY="quoted, value"`
+fn parse_attributes(input: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let mut rest = input.trim();
+
+    while !rest.is_empty() {
+        let (key, after_key) = match rest.split_once('=') {
+            Some(kv) => kv,
+            None => break,
+        };
+        let (value, after_value) = if let Some(quoted) = after_key.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            (&quoted[..end], quoted.get(end + 1..).unwrap_or(""))
+        } else {
+            let end = after_key.find(',').unwrap_or(after_key.len());
+            (&after_key[..end], &after_key[end..])
+        };
+        attributes.push((key.trim().to_string(), value.to_string()));
+        rest = after_value.trim_start_matches(',').trim_start();
+    }
+
+    attributes
+}
@@ -0,0 +1,162 @@
+use super::*;
+use crate::mock_server::{MockResponse, MockServer};
+
+/// Master-плейлист в духе AMG: аудио разного качества и видеоклип
+const AMG_MASTER: &str = "#EXTM3U
+#EXT-X-VERSION:3
+#EXT-X-INDEPENDENT-SEGMENTS
+#EXT-X-STREAM-INF:BANDWIDTH=69000,AVERAGE-BANDWIDTH=64000,CODECS=\"mp4a.40.5\"
+RuWave_64/playlist.m3u8?token=abc
+#EXT-X-STREAM-INF:BANDWIDTH=1330000,AVERAGE-BANDWIDTH=1200000,CODECS=\"avc1.4d401f,mp4a.40.2\",RESOLUTION=640x360,FRAME-RATE=25.000
+/hls/RuWave_video/playlist.m3u8
+
+#EXT-X-STREAM-INF:CODECS=\"mp4a.40.2\",BANDWIDTH=138000
+https://cdn.volna.top/RuWave_128/playlist.m3u8
+";
+
+const MEDIA: &str = "\u{feff}#EXTM3U\r
+#EXT-X-VERSION:3\r
+#EXT-X-TARGETDURATION:10\r
+#EXT-X-MEDIA-SEQUENCE:4021\r
+#EXTINF:9.984,Кино - Группа крови, live\r
+seg_4021.aac\r
+#EXTINF:10.005,\r
+../shared/seg_4022.aac\r
+#EXTINF:10\r
+https://cdn.volna.top/seg_4023.aac?t=1\r
+";
+
+fn master(content: &str, base_url: &str) -> Vec<HlsVariant> {
+    match parse_playlist(content, base_url).unwrap() {
+        HlsPlaylist::Master(variants) => variants,
+        HlsPlaylist::Media(_) => panic!("ожидался master-плейлист"),
+    }
+}
+
+#[test]
+fn master_playlist_variants_keep_quoted_codecs_and_resolve_uris() {
+    let variants = master(
+        AMG_MASTER,
+        "https://hls.amgradio.ru/hls/RuWave.m3u8?token=abc",
+    );
+    assert_eq!(variants.len(), 3);
+
+    let low = &variants[0];
+    assert_eq!(
+        low.uri,
+        "https://hls.amgradio.ru/hls/RuWave_64/playlist.m3u8?token=abc"
+    );
+    assert_eq!(low.bandwidth, Some(69000));
+    assert_eq!(low.average_bandwidth, Some(64000));
+    assert_eq!(low.codecs.as_deref(), Some("mp4a.40.5"));
+    assert_eq!(low.resolution, None);
+
+    // Запятая внутри кавычек не разделяет атрибуты
+    let video = &variants[1];
+    assert_eq!(
+        video.uri,
+        "https://hls.amgradio.ru/hls/RuWave_video/playlist.m3u8"
+    );
+    assert_eq!(video.codecs.as_deref(), Some("avc1.4d401f,mp4a.40.2"));
+    assert_eq!(video.resolution.as_deref(), Some("640x360"));
+    assert_eq!(video.bandwidth, Some(1330000));
+
+    // Порядок атрибутов не важен, абсолютный URI не меняется
+    let high = &variants[2];
+    assert_eq!(high.uri, "https://cdn.volna.top/RuWave_128/playlist.m3u8");
+    assert_eq!(high.bandwidth, Some(138000));
+    assert_eq!(high.average_bandwidth, None);
+}
+
+#[test]
+fn media_playlist_reads_target_duration_and_segments() {
+    let media = match parse_playlist(
+        MEDIA,
+        "https://hls.amgradio.ru/hls/RuWave_128/playlist.m3u8",
+    )
+    .unwrap()
+    {
+        HlsPlaylist::Media(media) => media,
+        HlsPlaylist::Master(_) => panic!("ожидался media-плейлист"),
+    };
+
+    assert_eq!(media.target_duration, Some(10));
+    assert_eq!(media.media_sequence, 4021);
+    assert!(!media.ended);
+
+    let segments: Vec<_> = media
+        .segments
+        .iter()
+        .map(|s| (s.uri.as_str(), s.duration, s.title.as_deref()))
+        .collect();
+    assert_eq!(
+        segments,
+        [
+            (
+                "https://hls.amgradio.ru/hls/RuWave_128/seg_4021.aac",
+                9.984,
+                Some("Кино - Группа крови, live")
+            ),
+            (
+                "https://hls.amgradio.ru/hls/shared/seg_4022.aac",
+                10.005,
+                None
+            ),
+            ("https://cdn.volna.top/seg_4023.aac?t=1", 10.0, None),
+        ]
+    );
+
+    let ended = format!("{}#EXT-X-ENDLIST\n", MEDIA);
+    match parse_playlist(&ended, "https://hls.amgradio.ru/").unwrap() {
+        HlsPlaylist::Media(media) => assert!(media.ended),
+        HlsPlaylist::Master(_) => panic!("ожидался media-плейлист"),
+    }
+}
+
+#[test]
+fn invalid_playlists_are_rejected() {
+    let error = parse_playlist("<html>404</html>", "https://hls.amgradio.ru/").unwrap_err();
+    assert_eq!(error.kind, crate::error::ErrorKind::Parse);
+    assert!(parse_playlist(AMG_MASTER, "не url").is_err());
+}
+
+#[tokio::test]
+async fn fetch_variants_sorts_by_bandwidth_and_resolves_from_request_url() {
+    let server = MockServer::start().await;
+    server.route(
+        "/hls/RuWave.m3u8",
+        MockResponse::status(200)
+            .with_content_type("application/vnd.apple.mpegurl")
+            .with_body(AMG_MASTER),
+    );
+    server.route(
+        "/hls/media.m3u8",
+        MockResponse::status(200).with_body(MEDIA),
+    );
+    server.route(
+        "/hls/empty.m3u8",
+        MockResponse::status(200).with_body("#EXTM3U\n#EXT-X-TARGETDURATION:10\n"),
+    );
+    let client = HlsClient::new();
+
+    let variants = client
+        .fetch_variants(&format!("{}/hls/RuWave.m3u8", server.url()))
+        .await
+        .unwrap();
+    let bandwidths: Vec<_> = variants.iter().map(|v| v.bandwidth).collect();
+    assert_eq!(bandwidths, [Some(1330000), Some(138000), Some(69000)]);
+    assert_eq!(
+        variants[2].uri,
+        format!("{}/hls/RuWave_64/playlist.m3u8?token=abc", server.url())
+    );
+
+    // Одиночный media-плейлист — единственный вариант без битрейта
+    let media_url = format!("{}/hls/media.m3u8", server.url());
+    let variants = client.fetch_variants(&media_url).await.unwrap();
+    assert_eq!(variants.len(), 1);
+    assert_eq!(variants[0].uri, media_url);
+    assert_eq!(variants[0].bandwidth, None);
+
+    let empty = format!("{}/hls/empty.m3u8", server.url());
+    assert!(client.fetch_variants(&empty).await.is_err());
+}
@@ -0,0 +1,90 @@
+use super::{IcyMetadata, IcyMetadataReader};
+use crate::error::{AppError, AppResult};
+use crate::models::RadioStation;
+
+/// Сколько интервалов метаданных читать, прежде чем сдаться
+const MAX_INTERVALS: usize = 3;
+
+/// Клиент для чтения ICY-метаданных прямо из аудиопотока (Icecast/Shoutcast)
+pub struct IcyClient {
+    client: reqwest::Client,
+}
+
+impl IcyClient {
+    pub fn new() -> Self {
+        let client = reqwest::Client::builder()
+            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+
+        Self { client }
+    }
+
+    /// Прочитать первый блок метаданных из потока.
+    /// `Ok(None)` — сервер не отдаёт ICY-метаданные или не прислал их за несколько интервалов.
+    pub async fn fetch_metadata(&self, stream_url: &str) -> AppResult<Option<IcyMetadata>> {
+        let mut response = AppError::check_status(
+            self.client
+                .get(stream_url)
+                .header("Icy-MetaData", "1")
+                .send()
+                .await?,
+        )?;
+
+        let metaint: usize = match response
+            .headers()
+            .get("icy-metaint")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse().ok())
+        {
+            Some(m) if m > 0 => m,
+            _ => return Ok(None),
+        };
+
+        let mut reader = IcyMetadataReader::new(metaint);
+        // Интервал аудио + максимальный блок метаданных (255 × 16) + байт длины
+        let budget = (metaint + 255 * 16 + 1) * MAX_INTERVALS;
+        let mut read = 0;
+
+        while read < budget {
+            let chunk = match response.chunk().await? {
+                Some(c) => c,
+                None => break,
+            };
+            read += chunk.len();
+
+            if let Some(metadata) = reader.push(&chunk).into_iter().next() {
+                return Ok(Some(metadata));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Обновить текущий трек станции из ICY-метаданных её потока.
+    /// HLS потоки ICY-метаданных не содержат и пропускаются.
+    pub async fn update_station(&self, station: &mut RadioStation) -> AppResult<()> {
+        if station.stream_url.is_empty()
+            || station.stream_hls.as_deref() == Some(station.stream_url.as_str())
+        {
+            return Ok(());
+        }
+
+        if let Some(metadata) = self.fetch_metadata(&station.stream_url).await? {
+            let (artist, title) = metadata.artist_and_title();
+            if title.is_some() {
+                station.current_artist = artist;
+                station.current_track = title;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for IcyClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,8 @@
+mod client;
+mod reader;
+
+#[cfg(test)]
+mod tests;
+
+pub use client::IcyClient;
+pub use reader::{decode_text, IcyMetadata, IcyMetadataReader};
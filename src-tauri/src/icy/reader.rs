@@ -0,0 +1,131 @@
+/// Метаданные из ICY-блока (`StreamTitle='...';StreamUrl='...';`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IcyMetadata {
+    pub stream_title: Option<String>,
+    pub stream_url: Option<String>,
+}
+
+impl IcyMetadata {
+    /// Разобрать текст блока метаданных. Байты не в UTF-8 декодируются как Windows-1251.
+    pub fn parse(block: &[u8]) -> Self {
+        // Блок дополняется нулями до кратности 16
+        let end = block.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+        let text = decode_text(&block[..end]);
+
+        Self {
+            stream_title: Self::field(&text, "StreamTitle"),
+            stream_url: Self::field(&text, "StreamUrl"),
+        }
+    }
+
+    /// Значение поля `Name='value';` (значение может содержать апострофы)
+    fn field(text: &str, name: &str) -> Option<String> {
+        let prefix = format!("{}='", name);
+        let start = text.find(&prefix)? + prefix.len();
+        let rest = &text[start..];
+        let end = rest
+            .find("';")
+            .or_else(|| rest.rfind('\''))
+            .unwrap_or(rest.len());
+        let value = rest[..end].trim();
+        if value.is_empty() {
+            None
+        } else {
+            Some(value.to_string())
+        }
+    }
+
+    /// Разделить `StreamTitle` вида "Исполнитель - Трек" на (исполнитель, трек)
+    pub fn artist_and_title(&self) -> (Option<String>, Option<String>) {
+        let title = match self.stream_title.as_deref() {
+            Some(t) => t,
+            None => return (None, None),
+        };
+        match title.split_once(" - ") {
+            Some((artist, track)) if !artist.trim().is_empty() && !track.trim().is_empty() => (
+                Some(artist.trim().to_string()),
+                Some(track.trim().to_string()),
+            ),
+            _ => (None, Some(title.to_string())),
+        }
+    }
+}
+
+/// Текст из ICY (метаданные, заголовки `icy-*`): UTF-8, иначе Windows-1251
+pub fn decode_text(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => s.to_string(),
+        Err(_) => encoding_rs::WINDOWS_1251.decode(bytes).0.into_owned(),
+    }
+}
+
+/// Разбор ICY-потока: `metaint` байт аудио, байт длины (×16), блок метаданных, и так далее.
+/// Данные можно подавать кусками произвольного размера.
+pub struct IcyMetadataReader {
+    metaint: usize,
+    state: ReaderState,
+    block: Vec<u8>,
+}
+
+enum ReaderState {
+    /// Осталось пропустить байт аудио
+    Audio(usize),
+    /// Ожидается байт длины блока
+    Length,
+    /// Осталось прочитать байт метаданных
+    Metadata(usize),
+}
+
+impl IcyMetadataReader {
+    pub fn new(metaint: usize) -> Self {
+        Self {
+            metaint,
+            state: ReaderState::Audio(metaint),
+            block: Vec::new(),
+        }
+    }
+
+    /// Подать очередной кусок потока. Возвращает все блоки метаданных, завершённые в этом куске
+    /// (пустые блоки — признак «метаданные не изменились» — пропускаются).
+    pub fn push(&mut self, mut data: &[u8]) -> Vec<IcyMetadata> {
+        let mut found = Vec::new();
+
+        while !data.is_empty() {
+            match self.state {
+                ReaderState::Audio(remaining) => {
+                    let skip = remaining.min(data.len());
+                    data = &data[skip..];
+                    self.state = if remaining == skip {
+                        ReaderState::Length
+                    } else {
+                        ReaderState::Audio(remaining - skip)
+                    };
+                }
+                ReaderState::Length => {
+                    let length = data[0] as usize * 16;
+                    data = &data[1..];
+                    self.block.clear();
+                    self.state = if length == 0 {
+                        ReaderState::Audio(self.metaint)
+                    } else {
+                        ReaderState::Metadata(length)
+                    };
+                }
+                ReaderState::Metadata(remaining) => {
+                    let take = remaining.min(data.len());
+                    self.block.extend_from_slice(&data[..take]);
+                    data = &data[take..];
+                    if remaining == take {
+                        found.push(IcyMetadata::parse(&self.block));
+                        self.block.clear();
+                        self.state = ReaderState::Audio(self.metaint);
+                    } else {
+                        self.state = ReaderState::Metadata(remaining - take);
+                    }
+                }
+            }
+        }
+
+        found
+    }
+}
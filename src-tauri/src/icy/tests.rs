@@ -0,0 +1,163 @@
+use super::*;
+use crate::mock_server::{MockResponse, MockServer};
+use crate::sources::CustomSource;
+
+const METAINT: usize = 16;
+const STREAM_PATH: &str = "/live.mp3";
+
+/// Блок метаданных с байтом длины, дополненный нулями до кратности 16
+fn metadata_block(text: &[u8]) -> Vec<u8> {
+    let blocks = text.len().div_ceil(16);
+    let mut block = vec![blocks as u8];
+    block.extend_from_slice(text);
+    block.resize(1 + blocks * 16, 0);
+    block
+}
+
+/// Аудио, пустой блок, аудио, блок с треком, аудио, блок со следующим треком
+fn icy_stream() -> Vec<u8> {
+    let audio = [0xffu8; METAINT];
+    let mut stream = Vec::new();
+    stream.extend_from_slice(&audio);
+    stream.push(0);
+    stream.extend_from_slice(&audio);
+    stream.extend(metadata_block(b"StreamTitle='Kino - Gruppa krovi';"));
+    stream.extend_from_slice(&audio);
+    stream.extend(metadata_block(
+        b"StreamTitle='Aria - Bespechnyj angel';StreamUrl='http://a/cover.jpg';",
+    ));
+    stream.extend_from_slice(&audio[..5]);
+    stream
+}
+
+fn titles(found: &[IcyMetadata]) -> Vec<&str> {
+    found
+        .iter()
+        .map(|m| m.stream_title.as_deref().unwrap_or_default())
+        .collect()
+}
+
+#[test]
+fn reader_handles_chunks_split_at_any_byte() {
+    let stream = icy_stream();
+    let expected = ["Kino - Gruppa krovi", "Aria - Bespechnyj angel"];
+
+    let mut whole = IcyMetadataReader::new(METAINT);
+    let found = whole.push(&stream);
+    assert_eq!(titles(&found), expected);
+    assert_eq!(found[1].stream_url.as_deref(), Some("http://a/cover.jpg"));
+
+    // Разрез на каждой границе: внутри аудио, на байте длины и внутри блока метаданных
+    for split in 0..=stream.len() {
+        let mut reader = IcyMetadataReader::new(METAINT);
+        let mut found = reader.push(&stream[..split]);
+        found.extend(reader.push(&stream[split..]));
+        assert_eq!(titles(&found), expected, "разрез на байте {}", split);
+    }
+
+    // По одному байту
+    let mut reader = IcyMetadataReader::new(METAINT);
+    let found: Vec<IcyMetadata> = stream.iter().flat_map(|b| reader.push(&[*b])).collect();
+    assert_eq!(titles(&found), expected);
+}
+
+#[test]
+fn metadata_text_is_decoded_and_split() {
+    // "Кино - Группа крови" в Windows-1251
+    let (cp1251, _, _) = encoding_rs::WINDOWS_1251.encode("StreamTitle='Кино - Группа крови';");
+    let metadata = IcyMetadata::parse(&cp1251);
+    assert_eq!(
+        metadata.stream_title.as_deref(),
+        Some("Кино - Группа крови")
+    );
+    assert_eq!(
+        metadata.artist_and_title(),
+        (Some("Кино".to_string()), Some("Группа крови".to_string()))
+    );
+
+    let utf8 = IcyMetadata::parse("StreamTitle='Ария - Беспечный ангел';\0\0\0".as_bytes());
+    assert_eq!(utf8.stream_title.as_deref(), Some("Ария - Беспечный ангел"));
+
+    // Пустой StreamTitle — нет трека (реклама, джинглы)
+    let empty = IcyMetadata::parse(b"StreamTitle='';StreamUrl='';\0\0");
+    assert_eq!(empty, IcyMetadata::default());
+    assert_eq!(empty.artist_and_title(), (None, None));
+
+    // Апостроф внутри названия, без исполнителя
+    let apostrophe = IcyMetadata::parse(b"StreamTitle='Don't Stop';");
+    assert_eq!(
+        apostrophe.artist_and_title(),
+        (None, Some("Don't Stop".to_string()))
+    );
+    assert_eq!(
+        IcyMetadata::parse(b"StreamTitle=' - Jingle';").artist_and_title(),
+        (None, Some("- Jingle".to_string()))
+    );
+}
+
+async fn icy_server(stream: Vec<u8>, metaint: Option<usize>) -> (MockServer, String) {
+    let server = MockServer::start().await;
+    // Тело мок-сервера — строка: аудио и байты длины здесь в пределах ASCII
+    let mut response = MockResponse::status(200)
+        .with_content_type("audio/mpeg")
+        .with_body(&String::from_utf8(stream).unwrap());
+    if let Some(metaint) = metaint {
+        response = response.with_header("icy-metaint", &metaint.to_string());
+    }
+    server.route(STREAM_PATH, response);
+    let url = format!("{}{}", server.url(), STREAM_PATH);
+    (server, url)
+}
+
+fn ascii_stream() -> Vec<u8> {
+    icy_stream()
+        .into_iter()
+        .map(|b| if b == 0xff { b'a' } else { b })
+        .collect()
+}
+
+#[tokio::test]
+async fn fetch_metadata_reads_first_block_from_stream() {
+    let (server, url) = icy_server(ascii_stream(), Some(METAINT)).await;
+    let client = IcyClient::new();
+
+    let metadata = client.fetch_metadata(&url).await.unwrap().unwrap();
+    assert_eq!(
+        metadata.stream_title.as_deref(),
+        Some("Kino - Gruppa krovi")
+    );
+    let request = &server.requests(STREAM_PATH)[0];
+    assert_eq!(
+        request.headers.get("icy-metadata").map(String::as_str),
+        Some("1")
+    );
+
+    let mut station = CustomSource::new_station(&url, "Тест");
+    client.update_station(&mut station).await.unwrap();
+    assert_eq!(station.current_artist.as_deref(), Some("Kino"));
+    assert_eq!(station.current_track.as_deref(), Some("Gruppa krovi"));
+}
+
+#[tokio::test]
+async fn fetch_metadata_without_icy_is_none() {
+    // Сервер не поддерживает ICY
+    let (_server, url) = icy_server(vec![b'a'; 64], None).await;
+    assert!(IcyClient::new()
+        .fetch_metadata(&url)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Поток закончился раньше первого блока
+    let (_server, url) = icy_server(vec![b'a'; METAINT + 1], Some(METAINT)).await;
+    assert!(IcyClient::new()
+        .fetch_metadata(&url)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Ошибка сервера — ошибка, а не «нет метаданных»
+    let server = MockServer::start().await;
+    let url = format!("{}/missing", server.url());
+    assert!(IcyClient::new().fetch_metadata(&url).await.is_err());
+}
@@ -1,16 +1,50 @@
+mod alarm;
+mod backup;
+mod cli;
+mod control_api;
+mod error;
+mod hls;
+mod icy;
+#[cfg(test)]
+mod mock_server;
 mod models;
+#[cfg(target_os = "linux")]
+mod mpris;
+mod player;
+mod playlist;
+mod profiles;
+mod recording;
+mod scrobble;
 mod services;
+mod settings_file;
 mod sources;
-
-use models::{RadioSource, RadioStation};
+mod store;
+mod track_poller;
+
+use alarm::{Alarm, AlarmCallback, AlarmScheduler};
+use backup::{Backup, BackupPreferences, BackupSummary, ImportMode};
+use control_api::{ControlApi, ControlApiSettings};
+use error::{AppError, AppResult};
+use hls::StreamVariants;
+use models::RadioStation;
+use player::{
+    NowPlaying, Player, PlayerCommand, PlayerRequest, SleepAction, SleepTimer, SleepTimerStatus,
+    PLAYER_COMMAND_EVENT,
+};
+use playlist::PlaylistFormat;
+use profiles::{Profile, ProfilePreferences, Profiles};
+use recording::{Recorder, RecordingStatus};
+use scrobble::{ScrobbleSettings, ScrobbleStatus, Scrobbler};
 use serde::{Deserialize, Serialize};
-use services::StationService;
-use std::collections::HashMap;
-use std::fs;
-use std::path::PathBuf;
-use std::sync::Arc;
-use tauri::Manager;
+use services::{StationService, TrackHistory, TrackHistoryEntry};
+use sources::{CustomSource, Ru101Source, SourceInfo, StreamCandidate, StreamProbe};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use store::Store;
+use tauri::{Emitter, Manager};
 use tokio::sync::RwLock;
+use track_poller::{TrackUpdate, TRACK_CHANGED_EVENT};
 
 fn default_true() -> bool {
     true
@@ -18,6 +52,9 @@ fn default_true() -> bool {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct AppSettings {
+    /// Версия схемы файла (см. `settings_file`)
+    #[serde(default)]
+    schema_version: u32,
     volume: u8,
     last_station_id: Option<String>,
     last_station_stream_url: Option<String>,
@@ -34,15 +71,30 @@ struct AppSettings {
     show_notifications: bool,
     #[serde(default = "default_true")]
     show_station_notifications: bool,
-    /// Избранные станции (полная информация для разных источников)
-    favorite_stations: Vec<RadioStation>,
-    /// Кэш станций по источникам (ключ: "amg" или "ru101")
-    cached_stations: HashMap<String, Vec<RadioStation>>,
+    /// Папка для записей (None — папка "Музыка" пользователя)
+    #[serde(default)]
+    recordings_dir: Option<String>,
+    /// Скробблинг в Last.fm / ListenBrainz
+    #[serde(default)]
+    scrobbling: ScrobbleSettings,
+    /// Локальный HTTP API для управления плеером
+    #[serde(default)]
+    control_api: ControlApiSettings,
+    /// Будильники
+    #[serde(default)]
+    alarms: Vec<Alarm>,
+    /// Профили слушателей (громкость, поток и уведомления выше — текущего профиля)
+    #[serde(default)]
+    profiles: Profiles,
+    /// Станции прежних версий, ещё не перенесённые в базу (фронтенду не отправляются)
+    #[serde(flatten)]
+    legacy_stations: LegacyStationData,
 }
 
 impl Default for AppSettings {
     fn default() -> Self {
         AppSettings {
+            schema_version: settings_file::SCHEMA_VERSION,
             volume: 50,
             last_station_id: None,
             last_station_stream_url: None,
@@ -57,74 +109,189 @@ impl Default for AppSettings {
             window_height: None,
             show_notifications: true,
             show_station_notifications: true,
-            favorite_stations: Vec::new(),
-            cached_stations: HashMap::new(),
+            recordings_dir: None,
+            scrobbling: ScrobbleSettings::default(),
+            control_api: ControlApiSettings::default(),
+            alarms: Vec::new(),
+            profiles: Profiles::default(),
+            legacy_stations: LegacyStationData::default(),
         }
     }
 }
 
 impl AppSettings {
-    fn load() -> Self {
-        if let Some(path) = get_settings_path() {
-            if let Ok(content) = fs::read_to_string(&path) {
-                if let Ok(settings) = serde_json::from_str::<AppSettings>(&content) {
-                    return settings;
-                }
+    /// Загрузить настройки (с миграцией старых версий). Второе значение — отчёт о
+    /// восстановлении из резервной копии. Ошибка — файл есть, но ни он, ни копии не читаются,
+    /// или он сохранён более новой версией (тогда настройки не сохраняются до перезапуска).
+    fn load() -> AppResult<(Self, Option<String>)> {
+        let loaded = match get_settings_path() {
+            Some(path) => settings_file::load(&path)?,
+            None => None,
+        };
+        Ok(loaded
+            .map(|loaded| (loaded.settings, loaded.recovered))
+            .unwrap_or_default())
+    }
+
+    /// Загрузить настройки, а при ошибке или восстановлении из копии — сообщение для фронтенда
+    fn load_or_default() -> (Self, Option<AppError>) {
+        match Self::load() {
+            Ok((settings, recovered)) => (settings, recovered.map(AppError::parse)),
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                (Self::default(), Some(e))
             }
         }
-        AppSettings::default()
     }
 
-    fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+    /// Настройки текущего профиля
+    fn profile_preferences(&self) -> ProfilePreferences {
+        ProfilePreferences {
+            volume: self.volume,
+            streaming_mode: self.streaming_mode.clone(),
+            show_notifications: self.show_notifications,
+            show_station_notifications: self.show_station_notifications,
+        }
+    }
+
+    fn apply_profile_preferences(&mut self, preferences: ProfilePreferences) {
+        self.volume = preferences.volume;
+        self.streaming_mode = preferences.streaming_mode;
+        self.show_notifications = preferences.show_notifications;
+        self.show_station_notifications = preferences.show_station_notifications;
+    }
+
+    fn save(&self) -> AppResult<()> {
         if let Some(path) = get_settings_path() {
-            if let Some(parent) = path.parent() {
-                fs::create_dir_all(parent)?;
-            }
-            let content = serde_json::to_string_pretty(self)?;
-            fs::write(&path, content)?;
+            settings_file::save(&path, self)?;
         }
         Ok(())
     }
+}
+
+/// Станции, которые раньше хранились в settings.json (теперь — в базе).
+/// Остаются в файле, пока перенос в базу не удастся.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct LegacyStationData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    favorite_stations: Option<Vec<RadioStation>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cached_stations: Option<HashMap<String, Vec<RadioStation>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    custom_stations: Option<Vec<RadioStation>>,
+}
+
+impl LegacyStationData {
+    fn is_empty(&self) -> bool {
+        self.favorite_stations.is_none()
+            && self.cached_stations.is_none()
+            && self.custom_stations.is_none()
+    }
+}
+
+/// Открыть базу станций и перенести в неё данные из старых файлов
+fn open_store() -> Arc<Store> {
+    let store = Arc::new(Store::open_or_memory(get_store_path().as_deref()));
+    if let Err(e) = migrate_legacy_settings(&store) {
+        eprintln!("⚠️ Не удалось перенести станции из настроек: {}", e);
+    }
+    if let Some(path) = get_track_history_path() {
+        match TrackHistory::new(store.clone()).migrate_file(&path) {
+            Ok(0) => {}
+            Ok(count) => eprintln!("📦 История треков перенесена в базу: {} записей", count),
+            Err(e) => eprintln!("⚠️ Не удалось перенести историю треков: {}", e),
+        }
+    }
+    store
+}
 
-    fn get_cached_stations(&self, source: &str) -> Option<&Vec<RadioStation>> {
-        self.cached_stations.get(source)
+/// Перенести избранное, кэш каталогов и пользовательские станции из settings.json в базу.
+/// Из settings.json они убираются только после успешного переноса. В базу в памяти
+/// не переносим: станции пропали бы при выходе, а в файле они дождутся следующего запуска.
+fn migrate_legacy_settings(store: &Store) -> AppResult<()> {
+    if store.is_in_memory() {
+        return Ok(());
+    }
+    let (mut settings, _) = AppSettings::load()?;
+    if settings.legacy_stations.is_empty() {
+        return Ok(());
     }
 
-    fn set_cached_stations(&mut self, source: String, stations: Vec<RadioStation>) {
-        self.cached_stations.insert(source, stations);
+    let legacy = std::mem::take(&mut settings.legacy_stations);
+    for (source_id, stations) in legacy.cached_stations.unwrap_or_default() {
+        store.replace_source_stations(&source_id, &stations)?;
+    }
+    if let Some(custom) = legacy.custom_stations {
+        store.replace_source_stations(CustomSource::ID, &custom)?;
+    }
+    if let Some(favorites) = legacy.favorite_stations {
+        store.replace_favorites(&favorites)?;
+    }
+
+    settings.save()?;
+    eprintln!("📦 Станции и избранное перенесены из настроек в базу");
+    Ok(())
+}
+
+/// Контекст Tauri: tauri.conf.json и ресурсы фронтенда (макрос разворачивается один раз)
+fn app_context() -> tauri::Context<tauri::Wry> {
+    tauri::generate_context!()
+}
+
+/// Папка данных из path resolver Tauri (задаётся при запуске окна)
+static APP_DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Папка настроек, базы и очередей.
+/// Установки прежних версий продолжают использовать свою папку `radio-app`.
+fn get_data_dir() -> Option<PathBuf> {
+    if let Some(dir) = legacy_data_dir().filter(|dir| dir.is_dir()) {
+        return Some(dir);
     }
+    APP_DATA_DIR.get().cloned().or_else(desktop_app_data_dir)
+}
 
-    fn add_favorite_station(&mut self, station: RadioStation) {
-        if !self.favorite_stations.iter().any(|s| s.id == station.id) {
-            self.favorite_stations.push(station);
+/// Запомнить папку данных приложения (вызывается из `setup`, до загрузки настроек)
+fn init_data_dir(app: &tauri::App) {
+    match app.path().app_data_dir() {
+        Ok(dir) => {
+            let _ = APP_DATA_DIR.set(dir);
         }
+        Err(e) => eprintln!("⚠️ Не найдена папка данных приложения: {}", e),
     }
+    match get_data_dir() {
+        Some(dir) => eprintln!("📁 Папка данных: {}", dir.display()),
+        None => eprintln!("⚠️ Настройки не будут сохраняться: нет папки данных"),
+    }
+}
 
-    fn remove_favorite_station(&mut self, station_id: &str) {
-        self.favorite_stations.retain(|s| s.id != station_id);
+/// То же, что `app_data_dir()` Tauri на десктопе — для консольного режима без окна
+fn desktop_app_data_dir() -> Option<PathBuf> {
+    #[cfg(desktop)]
+    {
+        // Идентификатор — `identifier` из tauri.conf.json, как у path resolver
+        dirs::data_dir().map(|dir| dir.join(&app_context().config().identifier))
     }
 
-    fn is_favorite(&self, station_id: &str) -> bool {
-        self.favorite_stations.iter().any(|s| s.id == station_id)
+    #[cfg(not(desktop))]
+    {
+        None
     }
 }
 
-fn get_settings_path() -> Option<PathBuf> {
+/// Папка настроек версий, которые сохраняли их по путям для каждой ОС вручную
+fn legacy_data_dir() -> Option<PathBuf> {
     #[cfg(target_os = "windows")]
     {
         std::env::var("APPDATA")
             .ok()
-            .map(|p| PathBuf::from(p).join("radio-app").join("settings.json"))
+            .map(|p| PathBuf::from(p).join("radio-app"))
     }
 
     #[cfg(target_os = "linux")]
     {
-        std::env::var("HOME").ok().map(|p| {
-            PathBuf::from(p)
-                .join(".config")
-                .join("radio-app")
-                .join("settings.json")
-        })
+        std::env::var("HOME")
+            .ok()
+            .map(|p| PathBuf::from(p).join(".config").join("radio-app"))
     }
 
     #[cfg(target_os = "macos")]
@@ -134,36 +301,91 @@ fn get_settings_path() -> Option<PathBuf> {
                 .join("Library")
                 .join("Application Support")
                 .join("radio-app")
-                .join("settings.json")
         })
     }
 
-    #[cfg(target_os = "android")]
+    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
     {
-        // Android: используем app data dir
         None
     }
+}
 
-    #[cfg(target_os = "ios")]
-    {
-        None
-    }
+fn get_settings_path() -> Option<PathBuf> {
+    get_data_dir().map(|dir| dir.join("settings.json"))
+}
 
-    #[cfg(not(any(
-        target_os = "windows",
-        target_os = "linux",
-        target_os = "macos",
-        target_os = "android",
-        target_os = "ios"
-    )))]
-    {
-        None
+/// База станций и истории (рядом с настройками)
+fn get_store_path() -> Option<PathBuf> {
+    get_settings_path().map(|p| p.with_file_name("radio.db"))
+}
+
+/// Старый файл истории треков (переносится в базу)
+fn get_track_history_path() -> Option<PathBuf> {
+    get_settings_path().map(|p| p.with_file_name("track_history.json"))
+}
+
+/// Файл очереди неотправленных прослушиваний (рядом с настройками)
+fn get_scrobble_queue_path() -> Option<PathBuf> {
+    get_settings_path().map(|p| p.with_file_name("scrobble_queue.json"))
+}
+
+/// Результат импорта плейлиста
+#[derive(Debug, Clone, Serialize)]
+struct PlaylistImportResult {
+    /// Добавленные в избранное станции
+    added: Vec<RadioStation>,
+    /// Пропущено дубликатов (по нормализованному URL потока)
+    duplicates: usize,
+}
+
+/// Результат экспорта станций
+#[derive(Debug, Clone, Serialize)]
+struct PlaylistExportResult {
+    /// Записано станций
+    exported: usize,
+    /// Пропущено станций без URL потока
+    skipped: usize,
+}
+
+/// Настройки для фронтенда вместе с избранным (оно хранится в базе)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SettingsPayload {
+    #[serde(flatten)]
+    settings: AppSettings,
+    /// При сохранении — новый список и порядок избранного (None — не менять)
+    #[serde(default)]
+    favorite_stations: Option<Vec<RadioStation>>,
+    /// Профиль, для которого получены настройки: сохранение для другого профиля отклоняется
+    #[serde(default)]
+    profile_id: Option<String>,
+}
+
+impl SettingsPayload {
+    /// Настройки (без станций прежних версий) и избранное текущего профиля
+    fn new(settings: &AppSettings, store: &Store) -> AppResult<Self> {
+        let mut settings = settings.clone();
+        settings.legacy_stations = LegacyStationData::default();
+        Ok(Self {
+            profile_id: Some(settings.profiles.active.clone()),
+            settings,
+            favorite_stations: Some(store.favorites()?),
+        })
     }
 }
 
 struct AppState {
+    store: Arc<Store>,
     station_service: Arc<StationService>,
+    recorder: Arc<Recorder>,
+    track_history: Arc<TrackHistory>,
+    scrobbler: Arc<Scrobbler>,
+    player: Arc<Player>,
+    sleep_timer: Arc<SleepTimer>,
+    control_api: Arc<ControlApi>,
+    alarms: Arc<AlarmScheduler>,
     settings: Arc<RwLock<AppSettings>>,
+    /// Ошибка загрузки настроек при запуске (работаем с настройками по умолчанию)
+    settings_error: Option<AppError>,
     #[cfg(desktop)]
     tray_icon: Arc<tokio::sync::Mutex<Option<tauri::tray::TrayIcon<tauri::Wry>>>>,
 }
@@ -175,22 +397,25 @@ struct AppState {
 async fn fetch_stations(
     source: String,
     state: tauri::State<'_, AppState>,
-) -> Result<Vec<RadioStation>, String> {
-    let radio_source = match source.as_str() {
-        "amg" => RadioSource::Amg,
-        "ru101" => RadioSource::Ru101,
-        _ => return Err(format!("Неизвестный источник: {}", source)),
-    };
-
-    match state.station_service.fetch_stations(radio_source).await {
-        Ok(stations) => {
-            let mut settings = state.settings.write().await;
-            settings.set_cached_stations(source.clone(), stations.clone());
-            let _ = settings.save();
-            Ok(stations)
-        }
-        Err(e) => Err(format!("Ошибка загрузки станций: {}", e)),
+) -> AppResult<Vec<RadioStation>> {
+    if !state.station_service.has_source(&source) {
+        return Err(AppError::not_found(format!(
+            "Неизвестный источник: {}",
+            source
+        )));
     }
+
+    state
+        .station_service
+        .fetch_stations(&source)
+        .await
+        .map_err(|e| e.context("Ошибка загрузки станций"))
+}
+
+/// Получить список источников станций
+#[tauri::command]
+fn list_sources(state: tauri::State<'_, AppState>) -> Vec<SourceInfo> {
+    state.station_service.list_sources()
 }
 
 /// Получить кэшированные станции
@@ -198,12 +423,72 @@ async fn fetch_stations(
 async fn get_cached_stations(
     source: String,
     state: tauri::State<'_, AppState>,
-) -> Result<Vec<RadioStation>, String> {
-    let settings = state.settings.read().await;
-    match settings.get_cached_stations(&source) {
-        Some(stations) => Ok(stations.clone()),
-        None => Ok(Vec::new()),
+) -> AppResult<Vec<RadioStation>> {
+    Ok(state
+        .station_service
+        .get_cached_stations(&source)
+        .await
+        .unwrap_or_default())
+}
+
+/// Поиск станций по началу названия в кэше каталогов и пользовательских станциях
+#[tauri::command]
+async fn search_stations(
+    query: String,
+    source: Option<String>,
+    limit: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<RadioStation>> {
+    state
+        .station_service
+        .search_stations(&query, source.as_deref(), limit.unwrap_or(50))
+        .await
+        .map_err(|e| e.context("Ошибка поиска"))
+}
+
+/// Добавить пользовательскую станцию по URL потока
+#[tauri::command]
+async fn add_custom_station(
+    stream_url: String,
+    name: Option<String>,
+    logo: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<RadioStation> {
+    let stream_url = stream_url.trim().to_string();
+    let probe = CustomSource::new().probe(&stream_url).await.map_err(|e| {
+        e.with_source(CustomSource::ID)
+            .context("Ошибка проверки потока")
+    })?;
+
+    let station = CustomSource::build_station(&stream_url, name, logo, &probe);
+    state
+        .station_service
+        .upsert_cached_station(station.clone())
+        .await
+        .map_err(|e| e.context("Ошибка сохранения"))?;
+
+    Ok(station)
+}
+
+/// Удалить пользовательскую станцию
+#[tauri::command]
+async fn remove_custom_station(
+    station_id: String,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<bool> {
+    let is_custom = state
+        .station_service
+        .find_station_by_id(&station_id)
+        .await
+        .is_some_and(|s| s.source == CustomSource::ID);
+    if !is_custom {
+        return Ok(false);
     }
+    state
+        .station_service
+        .remove_cached_station(&station_id)
+        .await
+        .map_err(|e| e.context("Ошибка сохранения"))
 }
 
 /// Получить платформу (android, ios, linux, windows, macos)
@@ -214,22 +499,20 @@ fn get_platform() -> String {
 
 /// Проксировать видео (скачать полностью и вернуть как base64)
 #[tauri::command]
-async fn proxy_video(video_url: String) -> Result<String, String> {
+async fn proxy_video(video_url: String) -> AppResult<String> {
     let client = reqwest::Client::builder()
         .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
         .timeout(std::time::Duration::from_secs(30))
         .build()
-        .map_err(|e| format!("Ошибка создания клиента: {}", e))?;
+        .map_err(|e| AppError::from(e).context("Ошибка создания клиента"))?;
 
     let response = client
         .get(&video_url)
         .send()
         .await
-        .map_err(|e| format!("Ошибка загрузки видео: {}", e))?;
+        .map_err(|e| AppError::from(e).context("Ошибка загрузки видео"))?;
 
-    if !response.status().is_success() {
-        return Err(format!("Сервер вернул ошибку: {}", response.status()));
-    }
+    let response = AppError::check_status(response)?;
 
     let content_type = response
         .headers()
@@ -241,7 +524,7 @@ async fn proxy_video(video_url: String) -> Result<String, String> {
     let bytes = response
         .bytes()
         .await
-        .map_err(|e| format!("Ошибка чтения данных: {}", e))?;
+        .map_err(|e| AppError::from(e).context("Ошибка чтения данных"))?;
 
     use base64::Engine;
     let base64_data = base64::engine::general_purpose::STANDARD.encode(&bytes);
@@ -254,33 +537,141 @@ async fn proxy_video(video_url: String) -> Result<String, String> {
 async fn get_stream_url(
     station: RadioStation,
     state: tauri::State<'_, AppState>,
-) -> Result<String, String> {
+) -> AppResult<String> {
     state
         .station_service
         .get_stream_url(&station)
         .await
-        .map_err(|e| format!("Ошибка получения потока: {}", e))
+        .map_err(|e| e.context("Ошибка получения потока"))
+}
+
+/// Получить все серверы вещания станции, от лучшего к худшему
+#[tauri::command]
+async fn get_stream_candidates(
+    station: RadioStation,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<StreamCandidate>> {
+    state
+        .station_service
+        .get_stream_candidates(&station)
+        .await
+        .map_err(|e| e.context("Ошибка получения потока"))
 }
 
-/// Обновить метаданные станции
+/// Сообщить, что URL потока не воспроизводится
+#[tauri::command]
+async fn report_stream_failure(
+    station: RadioStation,
+    url: String,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<()> {
+    state
+        .station_service
+        .report_stream_failure(&station, &url)
+        .await;
+    Ok(())
+}
+
+/// Получить варианты HLS потока (битрейты) и MP3 поток для fallback
+#[tauri::command]
+async fn get_stream_variants(
+    station: RadioStation,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<StreamVariants> {
+    Ok(state.station_service.get_stream_variants(&station).await)
+}
+
+/// Обновить метаданные станции (историю и скробблинг ведёт опрос в `track_poller`)
 #[tauri::command]
 async fn update_station_metadata(
     mut station: RadioStation,
     state: tauri::State<'_, AppState>,
-) -> Result<RadioStation, String> {
+) -> AppResult<RadioStation> {
     state
         .station_service
         .update_metadata(&mut station)
         .await
-        .map_err(|e| format!("Ошибка обновления метаданных: {}", e))?;
+        .map_err(|e| e.context("Ошибка обновления метаданных"))?;
+    state.player.update_metadata(&station);
     Ok(station)
 }
 
+/// История треков (от новых к старым)
+#[tauri::command]
+async fn get_track_history(
+    station_id: Option<String>,
+    since: Option<i64>,
+    limit: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<TrackHistoryEntry>> {
+    Ok(state
+        .track_history
+        .get(station_id.as_deref(), since, limit.unwrap_or(100))
+        .await)
+}
+
+/// Очистить историю треков (всю или одной станции)
+#[tauri::command]
+async fn clear_track_history(
+    station_id: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<usize> {
+    state
+        .track_history
+        .clear(station_id.as_deref())
+        .await
+        .map_err(|e| e.context("Ошибка очистки истории"))
+}
+
+/// Сохранить резервную копию: избранное, свои станции, история и настройки уведомлений/потока
+#[tauri::command]
+async fn export_backup(
+    path: String,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<BackupSummary> {
+    let preferences = {
+        let settings = state.settings.read().await;
+        BackupPreferences {
+            streaming_mode: settings.streaming_mode.clone(),
+            show_notifications: settings.show_notifications,
+            show_station_notifications: settings.show_station_notifications,
+        }
+    };
+    let backup = Backup::collect(&state.store, preferences)?;
+    backup.write(Path::new(&path))?;
+    eprintln!("💾 Резервная копия сохранена: {}", path);
+    Ok(backup.summary())
+}
+
+/// Восстановить резервную копию (`merge` — объединить с текущими данными, `replace` — заменить)
+#[tauri::command]
+async fn import_backup(
+    path: String,
+    mode: ImportMode,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<BackupSummary> {
+    let backup = Backup::read(Path::new(&path))?;
+    let summary = backup
+        .restore(&state.store, &state.track_history, mode)
+        .await
+        .map_err(|e| e.context("Ошибка восстановления"))?;
+
+    let mut settings = state.settings.write().await;
+    settings.streaming_mode = backup.preferences.streaming_mode;
+    settings.show_notifications = backup.preferences.show_notifications;
+    settings.show_station_notifications = backup.preferences.show_station_notifications;
+    settings
+        .save()
+        .map_err(|e| e.context("Ошибка сохранения"))?;
+
+    eprintln!("📦 Резервная копия восстановлена: {}", path);
+    Ok(summary)
+}
+
 /// Получить избранные станции
 #[tauri::command]
-async fn get_favorites(state: tauri::State<'_, AppState>) -> Result<Vec<RadioStation>, String> {
-    let settings = state.settings.read().await;
-    Ok(settings.favorite_stations.clone())
+async fn get_favorites(state: tauri::State<'_, AppState>) -> AppResult<Vec<RadioStation>> {
+    state.store.favorites()
 }
 
 /// Добавить/удалить станцию из избранного
@@ -288,74 +679,309 @@ async fn get_favorites(state: tauri::State<'_, AppState>) -> Result<Vec<RadioSta
 async fn toggle_favorite(
     station: RadioStation,
     state: tauri::State<'_, AppState>,
-) -> Result<bool, String> {
-    let mut settings = state.settings.write().await;
-    let is_favorite = settings.is_favorite(&station.id);
+) -> AppResult<bool> {
+    let is_favorite = state.store.is_favorite(&station.id)?;
 
     if is_favorite {
-        settings.remove_favorite_station(&station.id);
+        state.store.remove_favorite(&station.id)
     } else {
-        settings.add_favorite_station(station.clone());
+        state.store.add_favorite(&station)
     }
-
-    settings
-        .save()
-        .map_err(|e| format!("Ошибка сохранения: {}", e))?;
+    .map_err(|e| e.context("Ошибка сохранения"))?;
 
     Ok(!is_favorite)
 }
 
-/// Проверить, в избранном ли станция
+/// Импортировать плейлист (M3U/M3U8/PLS/XSPF) в избранное
 #[tauri::command]
-async fn is_favorite(
-    station_id: String,
+async fn import_playlist(
+    path: String,
     state: tauri::State<'_, AppState>,
-) -> Result<bool, String> {
-    let settings = state.settings.read().await;
-    Ok(settings.is_favorite(&station_id))
+) -> AppResult<PlaylistImportResult> {
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|e| AppError::from(e).context("Ошибка чтения файла"))?;
+    let entries = playlist::parse_playlist(&bytes, PlaylistFormat::from_path(Path::new(&path)))
+        .map_err(|e| e.context("Ошибка разбора плейлиста"))?;
+
+    let mut known_urls: HashSet<String> = state
+        .store
+        .favorites()?
+        .iter()
+        .flat_map(|s| [Some(&s.stream_url), s.stream_hls.as_ref()])
+        .flatten()
+        .filter(|url| !url.is_empty())
+        .map(|url| playlist::normalize_stream_url(url))
+        .collect();
+
+    let mut added = Vec::new();
+    let mut duplicates = 0;
+    for entry in entries {
+        if !known_urls.insert(playlist::normalize_stream_url(&entry.url)) {
+            duplicates += 1;
+            continue;
+        }
+        let station = CustomSource::build_station(
+            &entry.url,
+            entry.title,
+            entry.logo,
+            &StreamProbe::default(),
+        );
+        // Станция с таким ID уже может быть в избранном (например, с другим URL в данных)
+        let is_new = state
+            .store
+            .add_favorite(&station)
+            .map_err(|e| e.context("Ошибка сохранения"))?;
+        if is_new {
+            added.push(station);
+        } else {
+            duplicates += 1;
+        }
+    }
+
+    Ok(PlaylistImportResult { added, duplicates })
+}
+
+/// Записи плейлиста для станций (станции без URL потока пропускаются).
+/// URL потока 101.ru не хранится — получаем свежие, параллельно по 5 станций.
+async fn playlist_entries(
+    station_service: &Arc<StationService>,
+    stations: &[RadioStation],
+) -> Vec<playlist::PlaylistEntry> {
+    let mut entries = Vec::with_capacity(stations.len());
+    for chunk in stations.chunks(5) {
+        let mut handles = Vec::new();
+        for station in chunk {
+            let station = station.clone();
+            let station_service = station_service.clone();
+            handles.push(tokio::spawn(async move {
+                let url = if station.stream_url.is_empty() {
+                    station_service.get_stream_url(&station).await.ok()
+                } else {
+                    Some(station.stream_url.clone())
+                };
+                url.filter(|u| !u.is_empty())
+                    .map(|url| playlist::PlaylistEntry {
+                        url,
+                        title: Some(station.name.clone()),
+                        logo: station.logo.clone().or(station.artwork_url.clone()),
+                    })
+            }));
+        }
+        for handle in handles {
+            if let Ok(Some(entry)) = handle.await {
+                entries.push(entry);
+            }
+        }
+    }
+    entries
+}
+
+/// Экспортировать станции в плейлист (M3U8/PLS/XSPF).
+/// `scope`: "favorites" или ID источника (кэш каталога / пользовательские станции)
+#[tauri::command]
+async fn export_stations(
+    format: String,
+    scope: String,
+    path: String,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<PlaylistExportResult> {
+    let format = PlaylistFormat::from_name(&format).ok_or_else(|| {
+        AppError::invalid_input(format!("Неизвестный формат плейлиста: {}", format))
+    })?;
+
+    let stations = if scope == "favorites" {
+        state.store.favorites()?
+    } else if state.station_service.has_source(&scope) {
+        state
+            .station_service
+            .get_cached_stations(&scope)
+            .await
+            .unwrap_or_default()
+    } else {
+        return Err(AppError::not_found(format!(
+            "Неизвестный источник: {}",
+            scope
+        )));
+    };
+
+    let entries = playlist_entries(&state.station_service, &stations).await;
+    let content = playlist::write_playlist(format, &entries);
+    tokio::fs::write(&path, content)
+        .await
+        .map_err(|e| AppError::from(e).context("Ошибка записи файла"))?;
+
+    Ok(PlaylistExportResult {
+        exported: entries.len(),
+        skipped: stations.len() - entries.len(),
+    })
+}
+
+/// Проверить, в избранном ли станция
+#[tauri::command]
+async fn is_favorite(station_id: String, state: tauri::State<'_, AppState>) -> AppResult<bool> {
+    state.store.is_favorite(&station_id)
 }
 
 /// Получить настройки
 #[tauri::command]
-async fn get_settings(state: tauri::State<'_, AppState>) -> Result<AppSettings, String> {
-    let settings = state.settings.read().await;
-    Ok(settings.clone())
+async fn get_settings(state: tauri::State<'_, AppState>) -> AppResult<SettingsPayload> {
+    SettingsPayload::new(&*state.settings.read().await, &state.store)
+}
+
+/// Ошибка загрузки настроек при запуске или отчёт о восстановлении из копии (None — всё в порядке)
+#[tauri::command]
+fn get_settings_error(state: tauri::State<'_, AppState>) -> Option<AppError> {
+    state.settings_error.clone()
 }
 
 /// Сохранить настройки
 #[tauri::command]
 async fn save_settings(
-    new_settings: AppSettings,
+    new_settings: SettingsPayload,
+    app: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
+    let SettingsPayload {
+        settings: mut new_settings,
+        favorite_stations,
+        profile_id,
+    } = new_settings;
+    // Под блокировкой настроек профиль не переключится, пока пишется избранное
     let mut settings = state.settings.write().await;
+    if profile_id.is_some_and(|id| id != settings.profiles.active) {
+        return Err(AppError::invalid_input(
+            "Настройки относятся к другому профилю, загрузите их заново",
+        ));
+    }
+    if let Some(favorites) = favorite_stations {
+        state
+            .store
+            .replace_favorites(&favorites)
+            .map_err(|e| e.context("Ошибка сохранения"))?;
+    }
+    state
+        .scrobbler
+        .set_settings(new_settings.scrobbling.clone())
+        .await;
+    if let Err(e) = state
+        .control_api
+        .apply(&new_settings.control_api, &app)
+        .await
+    {
+        eprintln!("⚠️ HTTP API: {}", e);
+    }
+    state.player.set_volume(new_settings.volume);
+    if settings.alarms != new_settings.alarms {
+        schedule_alarms(&app, &new_settings.alarms).await;
+    }
+    // Список профилей меняется только своими командами
+    new_settings.profiles = settings.profiles.clone();
+    new_settings.legacy_stations = settings.legacy_stations.clone();
     *settings = new_settings;
     settings
         .save()
-        .map_err(|e| format!("Ошибка сохранения: {}", e))?;
+        .map_err(|e| e.context("Ошибка сохранения"))?;
+    Ok(())
+}
+
+// ==================== ПРОФИЛИ ====================
+
+/// Профили слушателей
+#[derive(Debug, Clone, Serialize)]
+struct ProfileList {
+    /// ID текущего профиля
+    active: String,
+    profiles: Vec<Profile>,
+}
+
+/// Список профилей
+#[tauri::command]
+async fn list_profiles(state: tauri::State<'_, AppState>) -> AppResult<ProfileList> {
+    let settings = state.settings.read().await;
+    Ok(ProfileList {
+        active: settings.profiles.active.clone(),
+        profiles: settings.profiles.list(&settings.profile_preferences()),
+    })
+}
+
+/// Создать профиль (пустое избранное и история, настройки по умолчанию)
+#[tauri::command]
+async fn create_profile(name: String, state: tauri::State<'_, AppState>) -> AppResult<Profile> {
+    let mut settings = state.settings.write().await;
+    let profile = settings.profiles.create(&name)?;
+    settings
+        .save()
+        .map_err(|e| e.context("Ошибка сохранения"))?;
+    Ok(profile)
+}
+
+/// Переключиться на профиль. Возвращает его настройки и избранное (то же — событием `profile-changed`).
+#[tauri::command]
+async fn switch_profile(
+    profile_id: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<SettingsPayload> {
+    let mut settings = state.settings.write().await;
+    if settings.profiles.active != profile_id {
+        let current = settings.profile_preferences();
+        let next = settings.profiles.switch(&profile_id, current)?;
+        settings.apply_profile_preferences(next);
+        settings
+            .save()
+            .map_err(|e| e.context("Ошибка сохранения"))?;
+        state.store.set_profile(&profile_id);
+
+        let volume = settings.volume;
+        state.player.set_volume(volume);
+        send_player_command(&app, PlayerCommand::SetVolume { volume });
+        eprintln!("👤 Профиль: {}", profile_id);
+    }
+
+    let payload = SettingsPayload::new(&settings, &state.store)?;
+    let _ = app.emit("profile-changed", &payload);
+    Ok(payload)
+}
+
+/// Удалить профиль вместе с его избранным и историей (текущий удалить нельзя)
+#[tauri::command]
+async fn delete_profile(profile_id: String, state: tauri::State<'_, AppState>) -> AppResult<()> {
+    let mut settings = state.settings.write().await;
+    settings.profiles.delete(&profile_id)?;
+    state
+        .store
+        .delete_profile_data(&profile_id)
+        .map_err(|e| e.context("Ошибка удаления профиля"))?;
+    settings
+        .save()
+        .map_err(|e| e.context("Ошибка сохранения"))?;
     Ok(())
 }
 
 /// Установить громкость
 #[tauri::command]
-async fn set_volume(volume: u8, state: tauri::State<'_, AppState>) -> Result<(), String> {
+async fn set_volume(volume: u8, state: tauri::State<'_, AppState>) -> AppResult<()> {
     let mut settings = state.settings.write().await;
     settings.volume = volume;
     let _ = settings.save();
+    state.player.set_volume(volume);
     Ok(())
 }
 
 /// Установить режим стриминга
 #[tauri::command]
-async fn set_streaming_mode(mode: String, state: tauri::State<'_, AppState>) -> Result<(), String> {
+async fn set_streaming_mode(mode: String, state: tauri::State<'_, AppState>) -> AppResult<()> {
     if mode != "mp3" && mode != "hls" {
-        return Err("Неверный режим стриминга. Используйте 'mp3' или 'hls'".to_string());
+        return Err(AppError::invalid_input(
+            "Неверный режим стриминга. Используйте 'mp3' или 'hls'",
+        ));
     }
     let mut settings = state.settings.write().await;
     settings.streaming_mode = mode;
     settings
         .save()
-        .map_err(|e| format!("Ошибка сохранения: {}", e))?;
+        .map_err(|e| e.context("Ошибка сохранения"))?;
     Ok(())
 }
 
@@ -365,13 +991,13 @@ async fn save_window_size(
     width: f64,
     height: f64,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let mut settings = state.settings.write().await;
     settings.window_width = Some(width);
     settings.window_height = Some(height);
     settings
         .save()
-        .map_err(|e| format!("Ошибка сохранения: {}", e))?;
+        .map_err(|e| e.context("Ошибка сохранения"))?;
     Ok(())
 }
 
@@ -387,7 +1013,7 @@ async fn set_last_station(
     track_cover: Option<String>,
     track_video_url: Option<String>,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let mut settings = state.settings.write().await;
     settings.last_station_id = Some(station_id);
     settings.last_station_stream_url = station_stream_url;
@@ -399,42 +1025,589 @@ async fn set_last_station(
     settings.last_track_video_url = track_video_url;
     settings
         .save()
-        .map_err(|e| format!("Ошибка сохранения: {}", e))?;
+        .map_err(|e| e.context("Ошибка сохранения"))?;
     Ok(())
 }
 
+// ==================== ЗАПИСЬ ====================
+
+/// Папка для записей: из настроек или "Музыка/Интырнэт Радиво"
+fn recordings_dir(app: &tauri::AppHandle, settings: &AppSettings) -> AppResult<PathBuf> {
+    if let Some(dir) = settings.recordings_dir.as_deref().filter(|d| !d.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    app.path()
+        .audio_dir()
+        .or_else(|_| app.path().app_data_dir())
+        .map(|dir| dir.join("Интырнэт Радиво"))
+        .map_err(|e| AppError::not_found(format!("Не найдена папка для записей: {}", e)))
+}
+
+/// Начать запись станции (новый файл на каждый трек)
+#[tauri::command]
+async fn start_recording(
+    station: RadioStation,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<RecordingStatus> {
+    let directory = {
+        let settings = state.settings.read().await;
+        recordings_dir(&app, &settings)?
+    };
+
+    let on_update = Arc::new(move |status: &RecordingStatus| {
+        let _ = app.emit("recording-status", status);
+    });
+
+    state
+        .recorder
+        .start(station, &directory, on_update)
+        .await
+        .map_err(|e| e.context("Ошибка записи"))
+}
+
+/// Остановить запись
+#[tauri::command]
+async fn stop_recording(state: tauri::State<'_, AppState>) -> AppResult<Option<RecordingStatus>> {
+    Ok(state.recorder.stop().await)
+}
+
+/// Состояние записи
+#[tauri::command]
+async fn get_recording_status(
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Option<RecordingStatus>> {
+    Ok(state.recorder.status().await)
+}
+
+/// Установить папку для записей (None — по умолчанию)
+#[tauri::command]
+async fn set_recordings_dir(
+    path: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<()> {
+    let mut settings = state.settings.write().await;
+    settings.recordings_dir = path.filter(|p| !p.trim().is_empty());
+    settings
+        .save()
+        .map_err(|e| e.context("Ошибка сохранения"))?;
+    Ok(())
+}
+
+// ==================== СКРОББЛИНГ ====================
+
+/// Состояние скробблинга
+#[tauri::command]
+async fn get_scrobble_status(state: tauri::State<'_, AppState>) -> AppResult<ScrobbleStatus> {
+    Ok(state.scrobbler.status().await)
+}
+
+/// Сохранить настройки скробблинга
+#[tauri::command]
+async fn set_scrobble_settings(
+    scrobbling: ScrobbleSettings,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<()> {
+    scrobble::validate(&scrobbling)?;
+    state.scrobbler.set_settings(scrobbling.clone()).await;
+
+    let mut settings = state.settings.write().await;
+    settings.scrobbling = scrobbling;
+    settings
+        .save()
+        .map_err(|e| e.context("Ошибка сохранения"))?;
+    Ok(())
+}
+
+/// Повторить отправку очереди. Возвращает число оставшихся прослушиваний.
+#[tauri::command]
+async fn flush_scrobble_queue(state: tauri::State<'_, AppState>) -> AppResult<usize> {
+    Ok(state.scrobbler.flush_queue().await)
+}
+
+/// Воспроизведение остановлено (текущий трек засчитывается до этого момента)
+#[tauri::command]
+async fn notify_playback_stopped(state: tauri::State<'_, AppState>) -> AppResult<()> {
+    state.scrobbler.playback_stopped().await;
+    Ok(())
+}
+
+// ==================== УПРАВЛЕНИЕ ПЛЕЕРОМ ====================
+
+/// Отправить команду плееру во фронтенде
+fn send_player_command(app: &tauri::AppHandle, command: PlayerCommand) {
+    if let Err(e) = app.emit(PLAYER_COMMAND_EVENT, &command) {
+        eprintln!("⚠️ Не удалось отправить команду плееру: {}", e);
+    }
+}
+
+/// Найти станцию для воспроизведения: избранное, кэш каталогов, пользовательские
+async fn find_playable_station(state: &AppState, station_id: &str) -> AppResult<RadioStation> {
+    if let Some(station) = state.store.find_favorite(station_id)? {
+        return Ok(station);
+    }
+    state
+        .station_service
+        .find_station_by_id(station_id)
+        .await
+        .ok_or_else(|| AppError::not_found(format!("Станция {} не найдена", station_id)))
+}
+
+/// Включить станцию во фронтенде
+fn start_station(app: &tauri::AppHandle, state: &AppState, station: RadioStation) {
+    state.player.set_playback(Some(station.clone()), true);
+    send_player_command(
+        app,
+        PlayerCommand::Play {
+            station: Box::new(station),
+        },
+    );
+}
+
+/// Выполнить запрос внешнего пульта (HTTP API, MPRIS)
+async fn handle_player_request(app: &tauri::AppHandle, request: PlayerRequest) -> AppResult<()> {
+    let state = app.state::<AppState>();
+    let now_playing = state.player.now_playing();
+
+    match request {
+        PlayerRequest::Play {
+            station_id: Some(station_id),
+        } => {
+            let station = find_playable_station(&state, &station_id).await?;
+            start_station(app, &state, station);
+        }
+        PlayerRequest::Play { station_id: None } => {
+            if now_playing.station.is_some() {
+                state.player.set_playback(now_playing.station, true);
+                send_player_command(app, PlayerCommand::Resume);
+            } else {
+                let last_station_id = state.settings.read().await.last_station_id.clone();
+                let station_id =
+                    last_station_id.ok_or_else(|| AppError::not_found("Нет последней станции"))?;
+                let station = find_playable_station(&state, &station_id).await?;
+                start_station(app, &state, station);
+            }
+        }
+        PlayerRequest::Pause => {
+            state.player.set_playback(now_playing.station, false);
+            send_player_command(app, PlayerCommand::Pause);
+        }
+        PlayerRequest::Stop => {
+            state.player.set_playback(now_playing.station, false);
+            send_player_command(app, PlayerCommand::Stop);
+            state.scrobbler.playback_stopped().await;
+        }
+        PlayerRequest::Next | PlayerRequest::Previous => {
+            let offset = if request == PlayerRequest::Next {
+                1
+            } else {
+                -1
+            };
+            let current_id = now_playing.station.map(|s| s.id);
+            let favorites = state.store.favorites()?;
+            let station = player::adjacent_favorite(&favorites, current_id.as_deref(), offset)
+                .ok_or_else(|| AppError::not_found("В избранном нет станций"))?;
+            start_station(app, &state, station);
+        }
+        PlayerRequest::SetVolume(volume) | PlayerRequest::RampVolume(volume) => {
+            let volume = volume.min(100);
+            if matches!(request, PlayerRequest::SetVolume(_)) {
+                let mut settings = state.settings.write().await;
+                settings.volume = volume;
+                settings
+                    .save()
+                    .map_err(|e| e.context("Ошибка сохранения"))?;
+            }
+            state.player.set_volume(volume);
+            send_player_command(app, PlayerCommand::SetVolume { volume });
+        }
+        PlayerRequest::HideWindow => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.hide();
+            }
+        }
+        PlayerRequest::Quit => app.exit(0),
+    }
+    Ok(())
+}
+
+/// Фронтенд сообщает, какая станция играет (None — ничего)
+#[tauri::command]
+async fn set_playback_state(
+    station: Option<RadioStation>,
+    playing: bool,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<()> {
+    state.player.set_playback(station, playing);
+    #[cfg(desktop)]
+    update_tray_tooltip(&state).await;
+    Ok(())
+}
+
+/// Опрос метаданных (`track_poller`). Новый трек — событие фронтенду, история, скробблинг;
+/// тот же трек — уточнение времени окончания и отметка скробблеру, что он ещё звучит.
+fn on_track_changed(app: &tauri::AppHandle, station: &RadioStation, update: TrackUpdate) {
+    let state = app.state::<AppState>();
+    if update != TrackUpdate::Same {
+        state.player.update_metadata(station);
+    }
+    if update == TrackUpdate::Changed {
+        let _ = app.emit(TRACK_CHANGED_EVENT, station);
+    }
+
+    let app = app.clone();
+    let station = station.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        if update != TrackUpdate::Same {
+            // Для того же трека запись не добавится, уточнится время окончания
+            if let Err(e) = state.track_history.record(&station).await {
+                eprintln!("⚠️ Не удалось сохранить историю треков: {}", e);
+            }
+        }
+        #[cfg(desktop)]
+        if update == TrackUpdate::Changed {
+            update_tray_tooltip(&state).await;
+        }
+        state.scrobbler.on_metadata(&station).await;
+    });
+}
+
+/// Подсказка значка в трее: станция и трек, если что-то играет
+fn now_playing_tooltip(now_playing: &NowPlaying) -> String {
+    let Some(station) = now_playing.station.as_ref().filter(|_| now_playing.playing) else {
+        return "Интырнэт Радиво".to_string();
+    };
+    let artist = station.current_artist.as_deref().filter(|a| !a.is_empty());
+    let track = station.current_track.as_deref().filter(|t| !t.is_empty());
+    match (artist, track) {
+        (Some(artist), Some(track)) => format!("{}\n{} — {}", station.name, artist, track),
+        (None, Some(track)) => format!("{}\n{}", station.name, track),
+        _ => station.name.clone(),
+    }
+}
+
+#[cfg(desktop)]
+async fn update_tray_tooltip(state: &AppState) {
+    let tooltip = now_playing_tooltip(&state.player.now_playing());
+    if let Some(tray) = state.tray_icon.lock().await.as_ref() {
+        let _ = tray.set_tooltip(Some(&tooltip));
+    }
+}
+
+/// Что сейчас играет (по данным бэкенда)
+#[tauri::command]
+fn get_now_playing(state: tauri::State<'_, AppState>) -> NowPlaying {
+    state.player.now_playing()
+}
+
+/// Настройки HTTP API (вместе с токеном)
+#[tauri::command]
+async fn get_control_api_settings(
+    state: tauri::State<'_, AppState>,
+) -> AppResult<ControlApiSettings> {
+    Ok(state.settings.read().await.control_api.clone())
+}
+
+/// Включить/выключить HTTP API. Токен создаётся при первом включении или по запросу.
+#[tauri::command]
+async fn set_control_api_settings(
+    enabled: bool,
+    port: Option<u16>,
+    regenerate_token: Option<bool>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<ControlApiSettings> {
+    let mut settings = state.settings.write().await;
+    let mut control_api = settings.control_api.clone();
+    control_api.enabled = enabled;
+    if let Some(port) = port {
+        if port == 0 {
+            return Err(AppError::invalid_input("Неверный порт"));
+        }
+        control_api.port = port;
+    }
+    if control_api.token.is_empty() || regenerate_token.unwrap_or(false) {
+        control_api.token = control_api::generate_token();
+    }
+
+    state
+        .control_api
+        .apply(&control_api, &app)
+        .await
+        .map_err(|e| e.context("Ошибка запуска HTTP API"))?;
+    settings.control_api = control_api.clone();
+    settings
+        .save()
+        .map_err(|e| e.context("Ошибка сохранения"))?;
+    Ok(control_api)
+}
+
+// ==================== ТАЙМЕР СНА ====================
+
+/// Максимальная длительность таймера сна (сутки)
+const MAX_SLEEP_TIMER_MINUTES: u32 = 24 * 60;
+
+/// Запустить таймер сна: через `minutes` минут остановить воспроизведение,
+/// последние `fade_seconds` секунд плавно убавляя громкость.
+/// Ход таймера приходит событием `sleep-timer`.
+#[tauri::command]
+async fn set_sleep_timer(
+    minutes: u32,
+    fade_seconds: Option<u32>,
+    action: Option<SleepAction>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<SleepTimerStatus> {
+    if minutes == 0 || minutes > MAX_SLEEP_TIMER_MINUTES {
+        return Err(AppError::invalid_input(format!(
+            "Таймер сна: от 1 до {} минут",
+            MAX_SLEEP_TIMER_MINUTES
+        )));
+    }
+
+    let on_update = Arc::new(move |status: &SleepTimerStatus| {
+        let _ = app.emit("sleep-timer", status);
+    });
+    let status = state
+        .sleep_timer
+        .start(
+            std::time::Duration::from_secs(u64::from(minutes) * 60),
+            std::time::Duration::from_secs(u64::from(fade_seconds.unwrap_or(0))),
+            action.unwrap_or_default(),
+            on_update,
+        )
+        .await;
+    eprintln!("😴 Таймер сна: {} мин", minutes);
+    Ok(status)
+}
+
+/// Состояние таймера сна (None — не запущен)
+#[tauri::command]
+async fn get_sleep_timer(state: tauri::State<'_, AppState>) -> AppResult<Option<SleepTimerStatus>> {
+    Ok(state.sleep_timer.status().await)
+}
+
+/// Отменить таймер сна
+#[tauri::command]
+async fn cancel_sleep_timer(
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Option<SleepTimerStatus>> {
+    Ok(state.sleep_timer.cancel().await)
+}
+
+// ==================== БУДИЛЬНИКИ ====================
+
+/// Событие фронтенду о сработавшем будильнике
+#[derive(Debug, Clone, Serialize)]
+struct AlarmFired {
+    alarm: Alarm,
+    station: RadioStation,
+}
+
+/// Перезапустить планировщик будильников с новым расписанием
+async fn schedule_alarms(app: &tauri::AppHandle, alarms: &[Alarm]) {
+    let handle = app.clone();
+    let on_fire: AlarmCallback = Arc::new(move |alarm| {
+        let app = handle.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = fire_alarm(&app, alarm).await {
+                eprintln!("⚠️ Будильник не сработал: {}", e);
+            }
+        });
+    });
+    app.state::<AppState>().alarms.apply(alarms, on_fire).await;
+
+    // Список серверов 101.ru — запасной вариант, если при срабатывании API недоступно
+    let app = app.clone();
+    let station_ids: Vec<String> = alarms
+        .iter()
+        .filter(|a| a.enabled)
+        .map(|a| a.station_id.clone())
+        .collect();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        for station_id in station_ids {
+            let Ok(station) = find_alarm_station(&state, &station_id).await else {
+                continue;
+            };
+            if station.source == Ru101Source::ID {
+                let _ = state.station_service.get_stream_candidates(&station).await;
+            }
+        }
+    });
+}
+
+/// Сработал будильник: показать окно, включить станцию на стартовой громкости
+/// и плавно поднять громкость до обычной (сохранённая громкость не меняется)
+async fn fire_alarm(app: &tauri::AppHandle, alarm: Alarm) -> AppResult<()> {
+    let state = app.state::<AppState>();
+    let station = alarm_station(&state, &alarm.station_id).await?;
+    let target_volume = state.settings.read().await.volume;
+
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    handle_player_request(app, PlayerRequest::RampVolume(alarm.start_volume)).await?;
+    start_station(app, &state, station.clone());
+    let _ = app.emit(
+        "alarm",
+        &AlarmFired {
+            alarm: alarm.clone(),
+            station,
+        },
+    );
+
+    if alarm.ramp_seconds > 0 && alarm.start_volume != target_volume {
+        let player = state.player.clone();
+        tauri::async_runtime::spawn(async move {
+            alarm::ramp_volume(
+                &player,
+                alarm.start_volume,
+                target_volume,
+                std::time::Duration::from_secs(u64::from(alarm.ramp_seconds)),
+            )
+            .await;
+        });
+    }
+    Ok(())
+}
+
+/// Станция будильника. Будильники общие для всех профилей, поэтому станция ищется
+/// в избранном любого профиля (сначала текущего), затем в каталогах.
+async fn find_alarm_station(state: &AppState, station_id: &str) -> AppResult<RadioStation> {
+    if let Some(station) = state.store.find_favorite_in_any_profile(station_id)? {
+        return Ok(station);
+    }
+    find_playable_station(state, station_id).await
+}
+
+/// Станция будильника со свежим URL потока. Если API недоступно, 101.ru отдаёт
+/// последний известный сервер этой станции; если URL нет и так — сохранённая копия
+/// станции или последняя игравшая станция.
+async fn alarm_station(state: &AppState, station_id: &str) -> AppResult<RadioStation> {
+    if let Ok(mut station) = find_alarm_station(state, station_id).await {
+        match state.station_service.get_stream_url(&station).await {
+            Ok(url) => {
+                station.stream_url = url;
+                return Ok(station);
+            }
+            Err(e) => eprintln!("⚠️ Будильник: поток {} недоступен: {}", station_id, e),
+        }
+        if !station.stream_url.is_empty() {
+            return Ok(station);
+        }
+    }
+
+    let (last_station_id, last_stream_url) = {
+        let settings = state.settings.read().await;
+        (
+            settings.last_station_id.clone(),
+            settings.last_station_stream_url.clone(),
+        )
+    };
+    let last_station = match last_station_id {
+        Some(id) => find_playable_station(state, &id).await.ok(),
+        None => None,
+    };
+    let mut station = last_station
+        .ok_or_else(|| AppError::not_found(format!("Станция {} недоступна", station_id)))?;
+    if station.stream_url.is_empty() {
+        station.stream_url = last_stream_url.unwrap_or_default();
+    }
+    if station.stream_url.is_empty() {
+        return Err(AppError::not_found(format!(
+            "Станция {} недоступна",
+            station_id
+        )));
+    }
+    eprintln!("⏰ Будильник: включаю сохранённую станцию {}", station.name);
+    Ok(station)
+}
+
+/// Список будильников
+#[tauri::command]
+async fn get_alarms(state: tauri::State<'_, AppState>) -> AppResult<Vec<Alarm>> {
+    Ok(state.settings.read().await.alarms.clone())
+}
+
+/// Добавить или изменить будильник (без ID — новый)
+#[tauri::command]
+async fn save_alarm(
+    mut alarm: Alarm,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Alarm> {
+    alarm.validate()?;
+    if !state.store.is_favorite(&alarm.station_id)? {
+        return Err(AppError::invalid_input(
+            "Для будильника выберите станцию из избранного",
+        ));
+    }
+    alarm.time = alarm.time.trim().to_string();
+    alarm.weekdays.sort_unstable();
+    alarm.weekdays.dedup();
+
+    let mut settings = state.settings.write().await;
+
+    match settings.alarms.iter_mut().find(|a| a.id == alarm.id) {
+        Some(existing) if !alarm.id.is_empty() => *existing = alarm.clone(),
+        _ => {
+            alarm.id = format!("alarm_{}", chrono::Utc::now().timestamp_millis());
+            settings.alarms.push(alarm.clone());
+        }
+    }
+    settings
+        .save()
+        .map_err(|e| e.context("Ошибка сохранения"))?;
+    schedule_alarms(&app, &settings.alarms).await;
+    Ok(alarm)
+}
+
+/// Удалить будильник
+#[tauri::command]
+async fn remove_alarm(
+    alarm_id: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<bool> {
+    let mut settings = state.settings.write().await;
+    let before = settings.alarms.len();
+    settings.alarms.retain(|a| a.id != alarm_id);
+    if settings.alarms.len() == before {
+        return Ok(false);
+    }
+    settings
+        .save()
+        .map_err(|e| e.context("Ошибка сохранения"))?;
+    schedule_alarms(&app, &settings.alarms).await;
+    Ok(true)
+}
+
 // ==================== КОМАНДЫ СОВМЕСТИМОСТИ (для старого фронтенда) ====================
 
 /// Парсинг AMG станций (совместимость)
 #[tauri::command]
-async fn parse_amg_stations(
-    state: tauri::State<'_, AppState>,
-) -> Result<Vec<RadioStation>, String> {
+async fn parse_amg_stations(state: tauri::State<'_, AppState>) -> AppResult<Vec<RadioStation>> {
     fetch_stations("amg".to_string(), state).await
 }
 
 /// Парсинг 101.ru станций (совместимость)
 #[tauri::command]
-async fn parse_ru101_stations(
-    state: tauri::State<'_, AppState>,
-) -> Result<Vec<RadioStation>, String> {
+async fn parse_ru101_stations(state: tauri::State<'_, AppState>) -> AppResult<Vec<RadioStation>> {
     fetch_stations("ru101".to_string(), state).await
 }
 
 /// Получить станции (совместимость)
 #[tauri::command]
-async fn get_stations(state: tauri::State<'_, AppState>) -> Result<Vec<RadioStation>, String> {
-    let settings = state.settings.read().await;
-    let mut all_stations = Vec::new();
-    for stations in settings.cached_stations.values() {
-        all_stations.extend(stations.clone());
-    }
-    Ok(all_stations)
+async fn get_stations(state: tauri::State<'_, AppState>) -> AppResult<Vec<RadioStation>> {
+    Ok(state.station_service.get_all_stations().await)
 }
 
 /// Обновить станции (совместимость)
 #[tauri::command]
-async fn refresh_stations(state: tauri::State<'_, AppState>) -> Result<Vec<RadioStation>, String> {
+async fn refresh_stations(state: tauri::State<'_, AppState>) -> AppResult<Vec<RadioStation>> {
     let _ = fetch_stations("amg".to_string(), state.clone()).await;
     get_stations(state).await
 }
@@ -444,7 +1617,7 @@ async fn refresh_stations(state: tauri::State<'_, AppState>) -> Result<Vec<Radio
 async fn get_current_track(
     station_id: String,
     state: tauri::State<'_, AppState>,
-) -> Result<Option<String>, String> {
+) -> AppResult<Option<String>> {
     if let Some(station) = state.station_service.find_station_by_id(&station_id).await {
         return Ok(station.current_track);
     }
@@ -456,31 +1629,24 @@ async fn get_current_track(
 async fn toggle_favorite_station(
     station_id: String,
     state: tauri::State<'_, AppState>,
-) -> Result<bool, String> {
-    let mut settings = state.settings.write().await;
-    let is_favorite = settings.is_favorite(&station_id);
+) -> AppResult<bool> {
+    let is_favorite = state.store.is_favorite(&station_id)?;
 
     if is_favorite {
-        settings.remove_favorite_station(&station_id);
+        state.store.remove_favorite(&station_id)?;
     } else {
-        let station = settings
-            .cached_stations
-            .values()
-            .flatten()
-            .find(|s| s.id == station_id)
-            .cloned();
+        let station = state.station_service.find_station_by_id(&station_id).await;
 
         if let Some(station) = station {
-            settings.add_favorite_station(station);
+            state.store.add_favorite(&station)?;
         } else {
-            return Err(format!("Станция {} не найдена в кэше", station_id));
+            return Err(AppError::not_found(format!(
+                "Станция {} не найдена в кэше",
+                station_id
+            )));
         }
     }
 
-    settings
-        .save()
-        .map_err(|e| format!("Ошибка сохранения: {}", e))?;
-
     Ok(!is_favorite)
 }
 
@@ -495,7 +1661,7 @@ async fn show_track_notification(
     is_station_notification: Option<bool>,
     state: tauri::State<'_, AppState>,
     app: tauri::AppHandle,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let settings = state.settings.read().await;
 
     let is_station = is_station_notification.unwrap_or(false);
@@ -524,11 +1690,12 @@ async fn show_track_notification(
         }
 
         let tray_icon_state_reset = state.tray_icon.clone();
+        let player = state.player.clone();
         tokio::spawn(async move {
             tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
             let tray_guard = tray_icon_state_reset.lock().await;
             if let Some(ref tray) = *tray_guard {
-                let _ = tray.set_tooltip(Some("Интырнэт Радиво"));
+                let _ = tray.set_tooltip(Some(&now_playing_tooltip(&player.now_playing())));
             }
         });
     }
@@ -598,15 +1765,12 @@ async fn show_track_notification(
 
 /// Установить настройку показа уведомлений о песне
 #[tauri::command]
-async fn set_show_notifications(
-    show: bool,
-    state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+async fn set_show_notifications(show: bool, state: tauri::State<'_, AppState>) -> AppResult<()> {
     let mut settings = state.settings.write().await;
     settings.show_notifications = show;
     settings
         .save()
-        .map_err(|e| format!("Ошибка сохранения: {}", e))?;
+        .map_err(|e| e.context("Ошибка сохранения"))?;
     Ok(())
 }
 
@@ -615,52 +1779,53 @@ async fn set_show_notifications(
 async fn set_show_station_notifications(
     show: bool,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let mut settings = state.settings.write().await;
     settings.show_station_notifications = show;
     settings
         .save()
-        .map_err(|e| format!("Ошибка сохранения: {}", e))?;
+        .map_err(|e| e.context("Ошибка сохранения"))?;
     Ok(())
 }
 
 // ==================== RUN ====================
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    let settings = AppSettings::load();
+/// Консольный режим (`intyrnet-radivo stations --source ru101 --json` и т.п.).
+/// Возвращает код завершения или None, если аргументы не относятся к консольному режиму.
+pub fn run_cli(args: &[String]) -> Option<i32> {
+    cli::run(args)
+}
 
-    let app_state = AppState {
-        station_service: Arc::new(StationService::new()),
+/// Состояние приложения: база, настройки и фоновые службы
+fn init_app_state(
+    store: Arc<Store>,
+    settings: AppSettings,
+    settings_error: Option<AppError>,
+) -> AppState {
+    let station_service = Arc::new(StationService::new(store.clone()));
+    let player = Arc::new(Player::new(settings.volume));
+    AppState {
+        recorder: Arc::new(Recorder::new(station_service.clone())),
+        track_history: Arc::new(TrackHistory::new(store.clone())),
+        scrobbler: Arc::new(Scrobbler::new(
+            settings.scrobbling.clone(),
+            get_scrobble_queue_path(),
+        )),
+        player: player.clone(),
+        sleep_timer: Arc::new(SleepTimer::new(player)),
+        control_api: Arc::new(ControlApi::new()),
+        alarms: Arc::new(AlarmScheduler::new()),
+        station_service,
+        store,
         settings: Arc::new(RwLock::new(settings)),
+        settings_error,
         #[cfg(desktop)]
         tray_icon: Arc::new(tokio::sync::Mutex::new(None)),
-    };
-
-    let station_service = app_state.station_service.clone();
-    let settings_clone = app_state.settings.clone();
-
-    tokio::runtime::Builder::new_multi_thread()
-        .enable_all()
-        .build()
-        .unwrap()
-        .block_on(async {
-            let settings = settings_clone.read().await;
-            for (source_str, stations) in &settings.cached_stations {
-                let source = match source_str.as_str() {
-                    "amg" => RadioSource::Amg,
-                    "ru101" => RadioSource::Ru101,
-                    _ => continue,
-                };
-                station_service.load_cache(source, stations.clone()).await;
-            }
-        });
-
-    #[cfg(desktop)]
-    let tray_icon_state_for_setup = app_state.tray_icon.clone();
-    #[cfg(not(desktop))]
-    let tray_icon_state_for_setup = ();
+    }
+}
 
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
     let builder = {
         let b = tauri::Builder::default();
         #[cfg(desktop)]
@@ -676,9 +1841,88 @@ pub fn run() {
         b
     };
     builder
-        .setup(move |app| {
+        .setup(|app| {
+            // Папку данных даёт path resolver Tauri, поэтому состояние создаётся здесь
+            init_data_dir(app);
+            let store = open_store();
+            let (settings, settings_error) = AppSettings::load_or_default();
+            store.set_profile(&settings.profiles.active);
+            let app_state = init_app_state(store, settings.clone(), settings_error);
+            #[cfg(desktop)]
+            let tray_icon_state_for_setup = app_state.tray_icon.clone();
+            app.manage(app_state);
+
             let window = app.get_webview_window("main").unwrap();
-            let settings = AppSettings::load();
+
+            // Периодически повторяем отправку неотправленных прослушиваний
+            let scrobbler = app.state::<AppState>().scrobbler.clone();
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(std::time::Duration::from_secs(5 * 60));
+                loop {
+                    interval.tick().await;
+                    scrobbler.flush_queue().await;
+                }
+            });
+
+            // Запросы внешних пультов (MPRIS, таймер сна) выполняются в фоне по очереди,
+            // чтобы шаги громкости и остановка не обгоняли друг друга
+            let (request_tx, mut request_rx) = tokio::sync::mpsc::unbounded_channel();
+            app.state::<AppState>()
+                .player
+                .set_handler(Arc::new(move |request| {
+                    let _ = request_tx.send(request);
+                }));
+            let handle_for_player = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                while let Some(request) = request_rx.recv().await {
+                    if let Err(e) = handle_player_request(&handle_for_player, request).await {
+                        eprintln!("⚠️ Команда плееру не выполнена: {}", e);
+                    }
+                }
+            });
+
+            // Текущий трек играющей станции опрашивается здесь, а не во фронтенде
+            let handle_for_tracks = app.handle().clone();
+            let (player, station_service) = {
+                let state = app.state::<AppState>();
+                (state.player.clone(), state.station_service.clone())
+            };
+            tauri::async_runtime::spawn(track_poller::run(
+                player,
+                station_service,
+                Arc::new(move |station, update| {
+                    on_track_changed(&handle_for_tracks, station, update)
+                }),
+            ));
+
+            #[cfg(target_os = "linux")]
+            {
+                let player = app.state::<AppState>().player.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = mpris::serve(player).await {
+                        eprintln!("⚠️ MPRIS недоступен: {}", e);
+                    }
+                });
+            }
+
+            if !settings.alarms.is_empty() {
+                let app_handle = app.handle().clone();
+                let alarms = settings.alarms.clone();
+                tauri::async_runtime::spawn(async move {
+                    schedule_alarms(&app_handle, &alarms).await;
+                });
+            }
+
+            if settings.control_api.enabled {
+                let app_handle = app.handle().clone();
+                let control_api_settings = settings.control_api.clone();
+                tauri::async_runtime::spawn(async move {
+                    let control_api = app_handle.state::<AppState>().control_api.clone();
+                    if let Err(e) = control_api.apply(&control_api_settings, &app_handle).await {
+                        eprintln!("⚠️ HTTP API не запущен: {}", e);
+                    }
+                });
+            }
 
             #[cfg(desktop)]
             if let (Some(width), Some(height)) = (settings.window_width, settings.window_height) {
@@ -776,19 +2020,36 @@ pub fn run() {
 
             Ok(())
         })
-        .manage(app_state)
         .invoke_handler(tauri::generate_handler![
             fetch_stations,
+            list_sources,
             get_cached_stations,
+            search_stations,
+            add_custom_station,
+            remove_custom_station,
             get_stream_url,
+            get_stream_variants,
+            get_stream_candidates,
+            report_stream_failure,
             get_platform,
             proxy_video,
             update_station_metadata,
+            get_track_history,
+            clear_track_history,
             get_favorites,
+            export_backup,
+            import_backup,
             toggle_favorite,
+            import_playlist,
+            export_stations,
             is_favorite,
             get_settings,
+            get_settings_error,
             save_settings,
+            list_profiles,
+            create_profile,
+            switch_profile,
+            delete_profile,
             set_volume,
             set_streaming_mode,
             save_window_size,
@@ -802,7 +2063,25 @@ pub fn run() {
             refresh_stations,
             get_current_track,
             toggle_favorite_station,
+            start_recording,
+            stop_recording,
+            get_recording_status,
+            set_recordings_dir,
+            get_scrobble_status,
+            set_scrobble_settings,
+            flush_scrobble_queue,
+            notify_playback_stopped,
+            set_playback_state,
+            get_now_playing,
+            get_control_api_settings,
+            set_control_api_settings,
+            set_sleep_timer,
+            get_sleep_timer,
+            cancel_sleep_timer,
+            get_alarms,
+            save_alarm,
+            remove_alarm,
         ])
-        .run(tauri::generate_context!())
+        .run(app_context())
         .expect("error while running tauri application");
 }
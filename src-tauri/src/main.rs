@@ -2,5 +2,10 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // Консольный режим: без окна, вывод в терминал
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = intyrnet_radivo_lib::run_cli(&args) {
+        std::process::exit(code);
+    }
     intyrnet_radivo_lib::run();
 }
@@ -0,0 +1,217 @@
+//! Минимальный HTTP-сервер для офлайн-тестов (источники, скробблер).
+//! Отдаёт заранее записанные ответы по пути запроса (query игнорируется) и запоминает запросы.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Записанный ответ
+#[derive(Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+    pub headers: Vec<(&'static str, String)>,
+    /// Закрыть соединение без ответа (сетевая ошибка у клиента)
+    pub disconnect: bool,
+}
+
+impl MockResponse {
+    pub fn json(body: &str) -> Self {
+        Self::new(200, "application/json; charset=utf-8", body)
+    }
+
+    pub fn html(body: &str) -> Self {
+        Self::new(200, "text/html; charset=utf-8", body)
+    }
+
+    pub fn status(status: u16) -> Self {
+        Self::new(status, "text/plain", "")
+    }
+
+    pub fn disconnect() -> Self {
+        Self {
+            disconnect: true,
+            ..Self::status(0)
+        }
+    }
+
+    fn new(status: u16, content_type: &'static str, body: &str) -> Self {
+        Self {
+            status,
+            content_type,
+            body: body.to_string(),
+            headers: Vec::new(),
+            disconnect: false,
+        }
+    }
+
+    pub fn with_body(mut self, body: &str) -> Self {
+        self.body = body.to_string();
+        self
+    }
+
+    pub fn with_content_type(mut self, content_type: &'static str) -> Self {
+        self.content_type = content_type;
+        self
+    }
+
+    pub fn with_header(mut self, name: &'static str, value: &str) -> Self {
+        self.headers.push((name, value.to_string()));
+        self
+    }
+}
+
+/// Полученный запрос
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+/// Локальный сервер на случайном порту. Неизвестные пути — 404.
+pub struct MockServer {
+    base_url: String,
+    routes: Arc<Mutex<HashMap<String, MockResponse>>>,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+impl MockServer {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let routes: Arc<Mutex<HashMap<String, MockResponse>>> = Default::default();
+        let requests: Arc<Mutex<Vec<MockRequest>>> = Default::default();
+
+        let server_routes = routes.clone();
+        let server_requests = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let routes = server_routes.clone();
+                let requests = server_requests.clone();
+                tokio::spawn(async move {
+                    let request = match read_request(&mut socket).await {
+                        Some(request) => request,
+                        None => return,
+                    };
+                    let response = routes
+                        .lock()
+                        .unwrap()
+                        .get(&request.path)
+                        .cloned()
+                        .unwrap_or_else(|| MockResponse::status(404));
+                    requests.lock().unwrap().push(request);
+                    if response.disconnect {
+                        return;
+                    }
+                    let _ = socket.write_all(&encode_response(&response)).await;
+                    let _ = socket.shutdown().await;
+                });
+            }
+        });
+
+        Self {
+            base_url,
+            routes,
+            requests,
+        }
+    }
+
+    /// Адрес сервера без завершающего слэша
+    pub fn url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Задать (или заменить) ответ для пути
+    pub fn route(&self, path: &str, response: MockResponse) {
+        self.routes
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), response);
+    }
+
+    /// Сколько раз запрашивался путь
+    pub fn hits(&self, path: &str) -> usize {
+        self.requests(path).len()
+    }
+
+    /// Запросы к пути в порядке получения
+    pub fn requests(&self, path: &str) -> Vec<MockRequest> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| r.path == path)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Прочитать запрос (заголовки и тело по Content-Length), путь — без query
+async fn read_request(socket: &mut tokio::net::TcpStream) -> Option<MockRequest> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+    let header_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        let n = socket.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let target = request_line.next()?;
+    let path = target.split('?').next().unwrap_or(target).to_string();
+
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let length: usize = headers
+        .get("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    while buf.len() < header_end + length {
+        let n = socket.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let body_end = buf.len().min(header_end + length);
+    let body = String::from_utf8_lossy(&buf[header_end..body_end]).to_string();
+
+    Some(MockRequest {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+fn encode_response(response: &MockResponse) -> Vec<u8> {
+    let mut head = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        response.content_type,
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+
+    let mut bytes = head.into_bytes();
+    bytes.extend_from_slice(response.body.as_bytes());
+    bytes
+}
@@ -1,21 +1,5 @@
 use serde::{Deserialize, Serialize};
 
-/// Источник радиостанции
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
-#[serde(rename_all = "lowercase")]
-pub enum RadioSource {
-    /// AMG Radio (volna.top)
-    Amg,
-    /// 101.ru
-    Ru101,
-}
-
-impl Default for RadioSource {
-    fn default() -> Self {
-        RadioSource::Amg
-    }
-}
-
 /// Универсальная структура радиостанции
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RadioStation {
@@ -23,8 +7,8 @@ pub struct RadioStation {
     pub id: String,
     /// Название станции
     pub name: String,
-    /// Источник станции
-    pub source: RadioSource,
+    /// ID источника в реестре (`RadioSourceTrait::id`: "amg", "ru101", ...)
+    pub source: String,
     /// URL потока (MP3/AAC)
     pub stream_url: String,
     /// HLS поток (m3u8) - опционально
@@ -58,6 +42,19 @@ pub struct RadioStation {
     /// Категория/жанр на 101.ru
     pub category: Option<String>,
 
+    // === Поля каталогов (Radio-Browser) ===
+    /// Страна вещания
+    pub country: Option<String>,
+    /// Теги/жанры
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Кодек потока (MP3, AAC, ...)
+    pub codec: Option<String>,
+    /// Битрейт потока, кбит/с
+    pub bitrate: Option<u32>,
+    /// Сайт станции
+    pub homepage: Option<String>,
+
     // === Общие поля ===
     /// Количество слушателей
     pub listeners: Option<u32>,
@@ -67,56 +64,25 @@ pub struct RadioStation {
 
 impl RadioStation {
     /// Создать ID станции на основе источника и внутреннего ID
-    pub fn make_id(source: &RadioSource, internal_id: &str) -> String {
-        match source {
-            RadioSource::Amg => format!("amg_{}", internal_id),
-            RadioSource::Ru101 => format!("ru101_{}", internal_id),
-        }
+    pub fn make_id(source: &str, internal_id: &str) -> String {
+        format!("{}_{}", source, internal_id)
     }
 
     /// Получить внутренний ID без префикса источника
     #[allow(dead_code)]
     pub fn internal_id(&self) -> &str {
-        if self.id.starts_with("amg_") {
-            &self.id[4..]
-        } else if self.id.starts_with("ru101_") {
-            &self.id[6..]
-        } else {
-            &self.id
-        }
+        self.id
+            .strip_prefix(self.source.as_str())
+            .and_then(|rest| rest.strip_prefix('_'))
+            .unwrap_or(&self.id)
     }
 
-    /// Создать новую AMG станцию
-    pub fn new_amg(slug: &str, name: &str, stream_url: &str) -> Self {
+    /// Станция источника `source` без источник-специфичных полей
+    pub fn new(source: &str, internal_id: &str, name: &str, stream_url: &str) -> Self {
         Self {
-            id: Self::make_id(&RadioSource::Amg, slug),
+            id: Self::make_id(source, internal_id),
             name: name.to_string(),
-            source: RadioSource::Amg,
-            stream_url: stream_url.to_string(),
-            stream_hls: None,
-            logo: None,
-            current_track: None,
-            current_artist: None,
-            station_slug: Some(slug.to_string()),
-            artwork_url: None,
-            artwork_url_p: None,
-            artwork_url_w: None,
-            artwork_url_w_p: None,
-            meta_server: Some("https://info.volna.top/radio.json".to_string()),
-            meta_key: Some(slug.to_string()),
-            channel_id: None,
-            category: None,
-            listeners: None,
-            stop_at_ms: None,
-        }
-    }
-
-    /// Создать новую 101.ru станцию
-    pub fn new_ru101(channel_id: u32, name: &str, stream_url: &str) -> Self {
-        Self {
-            id: Self::make_id(&RadioSource::Ru101, &channel_id.to_string()),
-            name: name.to_string(),
-            source: RadioSource::Ru101,
+            source: source.to_string(),
             stream_url: stream_url.to_string(),
             stream_hls: None,
             logo: None,
@@ -129,8 +95,13 @@ impl RadioStation {
             artwork_url_w_p: None,
             meta_server: None,
             meta_key: None,
-            channel_id: Some(channel_id),
+            channel_id: None,
             category: None,
+            country: None,
+            tags: Vec::new(),
+            codec: None,
+            bitrate: None,
+            homepage: None,
             listeners: None,
             stop_at_ms: None,
         }
@@ -0,0 +1,306 @@
+//! MPRIS2 (D-Bus): управление плеером из виджетов GNOME/KDE и `playerctl`.
+//!
+//! Публикует `org.mpris.MediaPlayer2` и `org.mpris.MediaPlayer2.Player` для `Player`:
+//! Play/Pause/Stop уходят во фронтенд, Next/Previous листают избранное.
+
+#[cfg(test)]
+mod tests;
+
+use crate::player::{NowPlaying, Player, PlayerRequest};
+use std::collections::HashMap;
+use std::sync::Arc;
+use zbus::connection::{self, Connection};
+use zbus::zvariant::{ObjectPath, OwnedValue, Value};
+
+/// Имя плеера на шине
+pub const BUS_NAME: &str = "org.mpris.MediaPlayer2.intyrnet_radivo";
+
+/// Путь объекта по спецификации MPRIS
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+
+/// trackid, когда ничего не играет
+const NO_TRACK: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+
+type Metadata = HashMap<String, OwnedValue>;
+
+/// Опубликовать плеер на сессионной шине
+pub async fn serve(player: Arc<Player>) -> zbus::Result<Connection> {
+    serve_on(connection::Builder::session()?, player).await
+}
+
+/// Опубликовать плеер на указанной шине (например, отдельной шине в тестах).
+/// Изменения состояния рассылаются через `PropertiesChanged`; соединение остаётся открытым,
+/// пока жив `Player`, даже если вызывающий не хранит возвращённый `Connection`.
+pub async fn serve_on(
+    builder: connection::Builder<'_>,
+    player: Arc<Player>,
+) -> zbus::Result<Connection> {
+    let mut changes = player.subscribe();
+    let connection = builder
+        .name(BUS_NAME)?
+        .serve_at(OBJECT_PATH, MediaPlayer2)?
+        .serve_at(OBJECT_PATH, MediaPlayer2Player { player })?
+        .build()
+        .await?;
+
+    let iface = connection
+        .object_server()
+        .interface::<_, MediaPlayer2Player>(OBJECT_PATH)
+        .await?;
+    tokio::spawn(async move {
+        let mut last = changes.borrow_and_update().clone();
+        while changes.changed().await.is_ok() {
+            let current = changes.borrow_and_update().clone();
+            if let Err(e) = emit_changes(&iface, &last, &current).await {
+                eprintln!("⚠️ MPRIS: не удалось отправить изменения: {}", e);
+            }
+            last = current;
+        }
+    });
+
+    Ok(connection)
+}
+
+async fn emit_changes(
+    iface: &zbus::object_server::InterfaceRef<MediaPlayer2Player>,
+    last: &NowPlaying,
+    current: &NowPlaying,
+) -> zbus::Result<()> {
+    let emitter = iface.signal_emitter();
+    let player = iface.get().await;
+    if playback_status(last) != playback_status(current) {
+        player.playback_status_changed(emitter).await?;
+    }
+    if metadata(last) != metadata(current) {
+        player.metadata_changed(emitter).await?;
+    }
+    if last.volume != current.volume {
+        player.volume_changed(emitter).await?;
+    }
+    Ok(())
+}
+
+fn playback_status(state: &NowPlaying) -> &'static str {
+    match (&state.station, state.playing) {
+        (None, _) => "Stopped",
+        (Some(_), true) => "Playing",
+        (Some(_), false) => "Paused",
+    }
+}
+
+/// Метаданные MPRIS: трек (или название станции), исполнитель, станция как альбом, обложка
+fn metadata(state: &NowPlaying) -> Metadata {
+    let mut metadata = Metadata::new();
+    let Some(station) = &state.station else {
+        insert(
+            &mut metadata,
+            "mpris:trackid",
+            ObjectPath::from_static_str_unchecked(NO_TRACK),
+        );
+        return metadata;
+    };
+
+    let track_id: String = station
+        .id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let track_path = format!("/ru/intyrnet_radivo/station/{}", track_id);
+    if let Ok(path) = ObjectPath::try_from(track_path.as_str()) {
+        insert(&mut metadata, "mpris:trackid", path);
+    }
+
+    let title = station
+        .current_track
+        .as_deref()
+        .filter(|t| !t.is_empty())
+        .unwrap_or(&station.name);
+    insert(&mut metadata, "xesam:title", title);
+    insert(&mut metadata, "xesam:album", station.name.as_str());
+    if let Some(artist) = station.current_artist.as_deref().filter(|a| !a.is_empty()) {
+        insert(&mut metadata, "xesam:artist", vec![artist]);
+    }
+    if let Some(art) = station
+        .artwork_url
+        .as_deref()
+        .or(station.logo.as_deref())
+        .filter(|u| !u.is_empty())
+    {
+        insert(&mut metadata, "mpris:artUrl", art);
+    }
+    metadata
+}
+
+fn insert<'a>(metadata: &mut Metadata, key: &str, value: impl Into<Value<'a>>) {
+    if let Ok(value) = OwnedValue::try_from(value.into()) {
+        metadata.insert(key.to_string(), value);
+    }
+}
+
+/// `org.mpris.MediaPlayer2`
+struct MediaPlayer2;
+
+#[zbus::interface(name = "org.mpris.MediaPlayer2")]
+impl MediaPlayer2 {
+    fn raise(&self) {}
+
+    fn quit(&self) {}
+
+    #[zbus(property)]
+    fn can_quit(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn can_raise(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn has_track_list(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn identity(&self) -> &str {
+        "Интырнэт Радиво"
+    }
+
+    #[zbus(property)]
+    fn desktop_entry(&self) -> &str {
+        "intyrnet-radivo"
+    }
+
+    #[zbus(property)]
+    fn supported_uri_schemes(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    #[zbus(property)]
+    fn supported_mime_types(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// `org.mpris.MediaPlayer2.Player`
+struct MediaPlayer2Player {
+    player: Arc<Player>,
+}
+
+#[zbus::interface(name = "org.mpris.MediaPlayer2.Player")]
+impl MediaPlayer2Player {
+    fn next(&self) {
+        self.player.request(PlayerRequest::Next);
+    }
+
+    fn previous(&self) {
+        self.player.request(PlayerRequest::Previous);
+    }
+
+    fn pause(&self) {
+        self.player.request(PlayerRequest::Pause);
+    }
+
+    fn play_pause(&self) {
+        if self.player.now_playing().playing {
+            self.player.request(PlayerRequest::Pause);
+        } else {
+            self.player
+                .request(PlayerRequest::Play { station_id: None });
+        }
+    }
+
+    fn stop(&self) {
+        self.player.request(PlayerRequest::Stop);
+    }
+
+    fn play(&self) {
+        self.player
+            .request(PlayerRequest::Play { station_id: None });
+    }
+
+    /// Перемотка в прямом эфире невозможна
+    fn seek(&self, _offset: i64) {}
+
+    fn set_position(&self, _track_id: ObjectPath<'_>, _position: i64) {}
+
+    fn open_uri(&self, _uri: &str) -> zbus::fdo::Result<()> {
+        Err(zbus::fdo::Error::NotSupported(
+            "Открытие URI не поддерживается".to_string(),
+        ))
+    }
+
+    #[zbus(property)]
+    fn playback_status(&self) -> &'static str {
+        playback_status(&self.player.now_playing())
+    }
+
+    #[zbus(property)]
+    fn rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn set_rate(&self, _rate: f64) {}
+
+    #[zbus(property)]
+    fn minimum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn maximum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn metadata(&self) -> Metadata {
+        metadata(&self.player.now_playing())
+    }
+
+    #[zbus(property)]
+    fn volume(&self) -> f64 {
+        f64::from(self.player.now_playing().volume) / 100.0
+    }
+
+    #[zbus(property)]
+    fn set_volume(&self, volume: f64) {
+        let volume = (volume.clamp(0.0, 1.0) * 100.0).round() as u8;
+        self.player.request(PlayerRequest::SetVolume(volume));
+    }
+
+    #[zbus(property)]
+    fn position(&self) -> i64 {
+        0
+    }
+
+    #[zbus(property)]
+    fn can_go_next(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_go_previous(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_play(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_pause(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_seek(&self) -> bool {
+        false
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn can_control(&self) -> bool {
+        true
+    }
+}
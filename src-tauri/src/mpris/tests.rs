@@ -0,0 +1,206 @@
+//! Тесты MPRIS на отдельной сессионной шине (`dbus-daemon --session`).
+//! Если `dbus-daemon` не установлен, тесты пропускаются.
+
+use super::*;
+use crate::models::RadioStation;
+use crate::sources::AmgSource;
+use std::io::{BufRead, BufReader};
+use std::pin::Pin;
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::Duration;
+use zbus::export::futures_core::Stream;
+use zbus::Proxy;
+
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+/// Отдельный dbus-daemon на время теста
+struct PrivateBus {
+    daemon: Child,
+    address: String,
+}
+
+impl PrivateBus {
+    fn start() -> Option<Self> {
+        let mut daemon = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address=1"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let mut address = String::new();
+        BufReader::new(daemon.stdout.take()?)
+            .read_line(&mut address)
+            .ok()?;
+        Some(Self {
+            daemon,
+            address: address.trim().to_string(),
+        })
+    }
+
+    async fn connect(&self) -> Connection {
+        connection::Builder::address(self.address.as_str())
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+    }
+}
+
+impl Drop for PrivateBus {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+    }
+}
+
+/// Плеер, который запоминает полученные запросы
+fn recording_player() -> (Arc<Player>, Arc<Mutex<Vec<PlayerRequest>>>) {
+    let player = Arc::new(Player::new(50));
+    let requests: Arc<Mutex<Vec<PlayerRequest>>> = Default::default();
+    let sink = requests.clone();
+    player.set_handler(Arc::new(move |request| {
+        sink.lock().unwrap().push(request);
+    }));
+    (player, requests)
+}
+
+async fn start(player: Arc<Player>) -> Option<(PrivateBus, Connection, Proxy<'static>)> {
+    let Some(bus) = PrivateBus::start() else {
+        eprintln!("dbus-daemon не найден, тест MPRIS пропущен");
+        return None;
+    };
+    let builder = connection::Builder::address(bus.address.as_str()).unwrap();
+    let server = serve_on(builder, player).await.unwrap();
+    let client = bus.connect().await;
+    let proxy = Proxy::new(&client, BUS_NAME, OBJECT_PATH, PLAYER_INTERFACE)
+        .await
+        .unwrap();
+    Some((bus, server, proxy))
+}
+
+/// Следующий элемент потока (без зависимости от futures-util)
+async fn next<S: Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+    std::future::poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+}
+
+fn station() -> RadioStation {
+    let mut station = AmgSource::new_station("ruwave", "Русская Волна", "http://ruwave/stream");
+    station.current_track = Some("Кукушка".to_string());
+    station.current_artist = Some("Кино".to_string());
+    station.logo = Some("http://logo/ruwave.svg".to_string());
+    station
+}
+
+#[tokio::test]
+async fn publishes_identity_status_metadata_and_volume() {
+    let (player, _) = recording_player();
+    player.set_playback(Some(station()), true);
+    let Some((bus, _server, proxy)) = start(player).await else {
+        return;
+    };
+
+    let root = Proxy::new(
+        &bus.connect().await,
+        BUS_NAME,
+        OBJECT_PATH,
+        "org.mpris.MediaPlayer2",
+    )
+    .await
+    .unwrap();
+    let identity: String = root.get_property("Identity").await.unwrap();
+    assert_eq!(identity, "Интырнэт Радиво");
+
+    let status: String = proxy.get_property("PlaybackStatus").await.unwrap();
+    assert_eq!(status, "Playing");
+    let volume: f64 = proxy.get_property("Volume").await.unwrap();
+    assert_eq!(volume, 0.5);
+
+    let metadata: HashMap<String, OwnedValue> = proxy.get_property("Metadata").await.unwrap();
+    let text = |key: &str| String::try_from(metadata[key].try_clone().unwrap()).unwrap();
+    assert_eq!(text("xesam:title"), "Кукушка");
+    assert_eq!(text("xesam:album"), "Русская Волна");
+    // Логотип используется, если у трека нет обложки
+    assert_eq!(text("mpris:artUrl"), "http://logo/ruwave.svg");
+    let artists = Vec::<String>::try_from(metadata["xesam:artist"].try_clone().unwrap()).unwrap();
+    assert_eq!(artists, vec!["Кино".to_string()]);
+    let track_id = ObjectPath::try_from(metadata["mpris:trackid"].try_clone().unwrap()).unwrap();
+    assert_eq!(track_id.as_str(), "/ru/intyrnet_radivo/station/amg_ruwave");
+}
+
+#[tokio::test]
+async fn methods_become_player_requests() {
+    let (player, requests) = recording_player();
+    let Some((_bus, _server, proxy)) = start(player.clone()).await else {
+        return;
+    };
+
+    for method in ["Next", "Previous", "Stop", "Play", "PlayPause"] {
+        let _: () = proxy.call(method, &()).await.unwrap();
+    }
+    player.set_playback(Some(station()), true);
+    let _: () = proxy.call("PlayPause", &()).await.unwrap();
+    proxy.set_property("Volume", 0.3f64).await.unwrap();
+
+    assert_eq!(
+        *requests.lock().unwrap(),
+        vec![
+            PlayerRequest::Next,
+            PlayerRequest::Previous,
+            PlayerRequest::Stop,
+            PlayerRequest::Play { station_id: None },
+            PlayerRequest::Play { station_id: None },
+            PlayerRequest::Pause,
+            PlayerRequest::SetVolume(30),
+        ]
+    );
+
+    let error = proxy
+        .call::<_, _, ()>("OpenUri", &("http://example.com/stream",))
+        .await;
+    assert!(error.is_err());
+}
+
+#[tokio::test]
+async fn state_changes_are_signalled() {
+    let (player, _) = recording_player();
+    let Some((_bus, _server, proxy)) = start(player.clone()).await else {
+        return;
+    };
+
+    let mut status_changes = proxy
+        .receive_property_changed::<String>("PlaybackStatus")
+        .await;
+    // Первое значение — текущее состояние
+    let initial = next(&mut status_changes)
+        .await
+        .unwrap()
+        .get()
+        .await
+        .unwrap();
+    assert_eq!(initial, "Stopped");
+
+    player.set_playback(Some(station()), true);
+    let changed = tokio::time::timeout(Duration::from_secs(5), next(&mut status_changes))
+        .await
+        .expect("нет сигнала PropertiesChanged")
+        .unwrap();
+    assert_eq!(changed.get().await.unwrap(), "Playing");
+
+    let mut metadata_changes = proxy
+        .receive_property_changed::<HashMap<String, OwnedValue>>("Metadata")
+        .await;
+    next(&mut metadata_changes).await.unwrap();
+    let mut updated = station();
+    updated.current_track = Some("Группа крови".to_string());
+    player.update_metadata(&updated);
+    let changed = tokio::time::timeout(Duration::from_secs(5), next(&mut metadata_changes))
+        .await
+        .expect("нет сигнала об изменении метаданных")
+        .unwrap();
+    let metadata = changed.get().await.unwrap();
+    assert_eq!(
+        String::try_from(metadata["xesam:title"].try_clone().unwrap()).unwrap(),
+        "Группа крови"
+    );
+}
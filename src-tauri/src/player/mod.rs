@@ -0,0 +1,161 @@
+//! Состояние воспроизведения на стороне бэкенда.
+//!
+//! Звук играет во фронтенде (HTML5 Audio): он сообщает о себе командой `set_playback_state`,
+//! а бэкенд управляет им через событие `player-command`. Запросы внешних пультов
+//! (`PlayerRequest`) выполняет `handle_player_request` в `lib.rs`; пульты без доступа
+//! к `AppHandle` (MPRIS, таймер сна) передают их через `Player::request`.
+
+mod sleep_timer;
+#[cfg(test)]
+mod tests;
+
+pub use sleep_timer::{SleepAction, SleepTimer, SleepTimerStatus};
+
+use crate::models::RadioStation;
+use serde::Serialize;
+use std::sync::{Arc, RwLock};
+use tokio::sync::watch;
+
+/// Событие фронтенду с командой плееру
+pub const PLAYER_COMMAND_EVENT: &str = "player-command";
+
+/// Команда фронтенду (`{ action: "play", station }`, `{ action: "set_volume", volume }`, ...)
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum PlayerCommand {
+    /// Включить станцию
+    Play { station: Box<RadioStation> },
+    /// Продолжить воспроизведение текущей станции
+    Resume,
+    /// Поставить на паузу
+    Pause,
+    /// Остановить воспроизведение
+    Stop,
+    /// Установить громкость (0–100)
+    SetVolume { volume: u8 },
+}
+
+/// Запрос на управление от внешнего пульта
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlayerRequest {
+    /// Включить станцию по ID (None — продолжить текущую или последнюю)
+    Play {
+        station_id: Option<String>,
+    },
+    Pause,
+    Stop,
+    /// Следующая станция в избранном
+    Next,
+    /// Предыдущая станция в избранном
+    Previous,
+    SetVolume(u8),
+    /// Громкость на время плавного изменения (без сохранения в настройках)
+    RampVolume(u8),
+    /// Свернуть окно в трей
+    HideWindow,
+    /// Закрыть приложение
+    Quit,
+}
+
+/// Что сейчас играет
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NowPlaying {
+    /// Текущая станция с последними метаданными
+    pub station: Option<RadioStation>,
+    pub playing: bool,
+    /// Громкость 0–100
+    pub volume: u8,
+}
+
+/// Обработчик запросов от внешних пультов
+pub type RequestHandler = Arc<dyn Fn(PlayerRequest) + Send + Sync>;
+
+/// Состояние плеера и канал запросов к нему
+pub struct Player {
+    state: watch::Sender<NowPlaying>,
+    handler: RwLock<Option<RequestHandler>>,
+}
+
+impl Player {
+    pub fn new(volume: u8) -> Self {
+        let (state, _) = watch::channel(NowPlaying {
+            station: None,
+            playing: false,
+            volume,
+        });
+        Self {
+            state,
+            handler: RwLock::new(None),
+        }
+    }
+
+    pub fn now_playing(&self) -> NowPlaying {
+        self.state.borrow().clone()
+    }
+
+    /// Подписка на изменения состояния
+    pub fn subscribe(&self) -> watch::Receiver<NowPlaying> {
+        self.state.subscribe()
+    }
+
+    /// Фронтенд сообщил, что играет (или что ничего не играет)
+    pub fn set_playback(&self, station: Option<RadioStation>, playing: bool) {
+        self.state.send_modify(|state| {
+            state.playing = playing && station.is_some();
+            state.station = station;
+        });
+    }
+
+    pub fn set_volume(&self, volume: u8) {
+        self.state.send_if_modified(|state| {
+            let changed = state.volume != volume;
+            state.volume = volume;
+            changed
+        });
+    }
+
+    /// Обновить метаданные, если это текущая станция
+    pub fn update_metadata(&self, station: &RadioStation) {
+        self.state
+            .send_if_modified(|state| match state.station.as_mut() {
+                Some(current) if current.id == station.id => {
+                    *current = station.clone();
+                    true
+                }
+                _ => false,
+            });
+    }
+
+    /// Установить обработчик запросов (после запуска приложения)
+    pub fn set_handler(&self, handler: RequestHandler) {
+        *self.handler.write().unwrap() = Some(handler);
+    }
+
+    /// Передать запрос обработчику
+    pub fn request(&self, request: PlayerRequest) {
+        let handler = self.handler.read().unwrap().clone();
+        match handler {
+            Some(handler) => handler(request),
+            None => eprintln!("⚠️ Плеер ещё не готов, запрос пропущен: {:?}", request),
+        }
+    }
+}
+
+/// Соседняя станция в избранном (по кругу).
+/// Если текущей станции нет в избранном — первая (или последняя при `offset < 0`).
+pub fn adjacent_favorite(
+    favorites: &[RadioStation],
+    current_id: Option<&str>,
+    offset: isize,
+) -> Option<RadioStation> {
+    if favorites.is_empty() {
+        return None;
+    }
+    let len = favorites.len() as isize;
+    let index = match current_id.and_then(|id| favorites.iter().position(|s| s.id == id)) {
+        Some(index) => (index as isize + offset).rem_euclid(len),
+        None if offset < 0 => len - 1,
+        None => 0,
+    };
+    favorites.get(index as usize).cloned()
+}
@@ -0,0 +1,212 @@
+use super::{Player, PlayerRequest};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{oneshot, Mutex, RwLock};
+use tokio::time::Instant;
+
+/// Что сделать, когда таймер сработал
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SleepAction {
+    /// Только остановить воспроизведение
+    #[default]
+    Stop,
+    /// Остановить и свернуть в трей
+    Hide,
+    /// Остановить и закрыть приложение
+    Quit,
+}
+
+/// Состояние таймера сна (событие `sleep-timer`)
+#[derive(Debug, Clone, Serialize)]
+pub struct SleepTimerStatus {
+    /// Когда сработает (мс с эпохи)
+    pub ends_at_ms: i64,
+    pub remaining_seconds: u64,
+    pub fade_seconds: u64,
+    pub action: SleepAction,
+    /// Идёт плавное снижение громкости
+    pub fading: bool,
+    /// Таймер сработал (последнее событие)
+    pub finished: bool,
+}
+
+/// Обработчик изменения состояния (каждый шаг таймера)
+pub type SleepTimerCallback = Arc<dyn Fn(&SleepTimerStatus) + Send + Sync>;
+
+struct ActiveTimer {
+    stop: oneshot::Sender<()>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+/// Таймер сна: через заданное время плавно убавляет громкость и останавливает плеер.
+/// Работает в бэкенде, поэтому срабатывает и при скрытом в трей окне.
+pub struct SleepTimer {
+    player: Arc<Player>,
+    active: Mutex<Option<ActiveTimer>>,
+    status: Arc<RwLock<Option<SleepTimerStatus>>>,
+}
+
+impl SleepTimer {
+    pub fn new(player: Arc<Player>) -> Self {
+        Self {
+            player,
+            active: Mutex::new(None),
+            status: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Запустить таймер (предыдущий отменяется). Последние `fade` громкость снижается до нуля.
+    pub async fn start(
+        &self,
+        duration: Duration,
+        fade: Duration,
+        action: SleepAction,
+        on_update: SleepTimerCallback,
+    ) -> SleepTimerStatus {
+        self.cancel().await;
+
+        let fade = fade.min(duration);
+        let status = SleepTimerStatus {
+            ends_at_ms: now_ms() + duration.as_millis() as i64,
+            remaining_seconds: duration.as_secs(),
+            fade_seconds: fade.as_secs(),
+            action,
+            fading: false,
+            finished: false,
+        };
+        *self.status.write().await = Some(status.clone());
+
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let task = TimerTask {
+            player: self.player.clone(),
+            status: self.status.clone(),
+            on_update,
+            ends_at: Instant::now() + duration,
+            fade,
+            action,
+        };
+        let handle = tokio::spawn(task.run(stop_rx));
+        *self.active.lock().await = Some(ActiveTimer {
+            stop: stop_tx,
+            task: handle,
+        });
+        status
+    }
+
+    /// Отменить таймер (громкость возвращается, если уже начала снижаться)
+    pub async fn cancel(&self) -> Option<SleepTimerStatus> {
+        let active = self.active.lock().await.take();
+        if let Some(active) = active {
+            let _ = active.stop.send(());
+            let _ = active.task.await;
+        }
+        self.status.write().await.take()
+    }
+
+    pub async fn status(&self) -> Option<SleepTimerStatus> {
+        self.status.read().await.clone()
+    }
+}
+
+struct TimerTask {
+    player: Arc<Player>,
+    status: Arc<RwLock<Option<SleepTimerStatus>>>,
+    on_update: SleepTimerCallback,
+    ends_at: Instant,
+    fade: Duration,
+    action: SleepAction,
+}
+
+impl TimerTask {
+    async fn run(self, mut stop: oneshot::Receiver<()>) {
+        let mut interval = tokio::time::interval(tick_interval(self.fade));
+        // Громкость до начала снижения — её возвращаем после остановки
+        let mut fade_from: Option<u8> = None;
+        let mut last_volume: Option<u8> = None;
+
+        loop {
+            tokio::select! {
+                _ = &mut stop => {
+                    if let Some(volume) = fade_from {
+                        self.player.request(PlayerRequest::SetVolume(volume));
+                    }
+                    return;
+                }
+                _ = interval.tick() => {}
+            }
+
+            let remaining = self.ends_at.saturating_duration_since(Instant::now());
+            if !self.fade.is_zero() && remaining <= self.fade {
+                let from = *fade_from.get_or_insert_with(|| self.player.now_playing().volume);
+                let volume = faded_volume(from, remaining, self.fade);
+                if last_volume != Some(volume) {
+                    // Шаги снижения не сохраняются в настройки — сохраняется только возврат громкости
+                    self.player.request(PlayerRequest::RampVolume(volume));
+                    last_volume = Some(volume);
+                }
+            }
+
+            self.update(|status| {
+                status.remaining_seconds = remaining.as_secs_f64().ceil() as u64;
+                status.fading = fade_from.is_some();
+            })
+            .await;
+
+            if remaining.is_zero() {
+                break;
+            }
+        }
+
+        eprintln!("😴 Таймер сна сработал");
+        self.player.request(PlayerRequest::Stop);
+        if let Some(volume) = fade_from {
+            self.player.request(PlayerRequest::SetVolume(volume));
+        }
+        match self.action {
+            SleepAction::Stop => {}
+            SleepAction::Hide => self.player.request(PlayerRequest::HideWindow),
+            SleepAction::Quit => self.player.request(PlayerRequest::Quit),
+        }
+
+        self.update(|status| status.finished = true).await;
+        *self.status.write().await = None;
+    }
+
+    async fn update(&self, change: impl FnOnce(&mut SleepTimerStatus)) {
+        let snapshot = {
+            let mut status = self.status.write().await;
+            let Some(status) = status.as_mut() else {
+                return;
+            };
+            change(status);
+            status.clone()
+        };
+        (self.on_update)(&snapshot);
+    }
+}
+
+/// Шаг таймера: 20 ступеней громкости за время затухания, но не реже раза в секунду
+fn tick_interval(fade: Duration) -> Duration {
+    if fade.is_zero() {
+        return Duration::from_secs(1);
+    }
+    (fade / 20).clamp(Duration::from_millis(50), Duration::from_secs(1))
+}
+
+/// Громкость при затухании: линейно от `from` до нуля
+pub(super) fn faded_volume(from: u8, remaining: Duration, fade: Duration) -> u8 {
+    if fade.is_zero() {
+        return 0;
+    }
+    let ratio = (remaining.as_secs_f64() / fade.as_secs_f64()).clamp(0.0, 1.0);
+    (f64::from(from) * ratio).round() as u8
+}
+
+fn now_ms() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
@@ -0,0 +1,126 @@
+use super::*;
+use crate::sources::AmgSource;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Плеер, который запоминает полученные запросы и применяет громкость, как `lib.rs`
+fn recording_player(volume: u8) -> (Arc<Player>, Arc<Mutex<Vec<PlayerRequest>>>) {
+    let player = Arc::new(Player::new(volume));
+    let requests: Arc<Mutex<Vec<PlayerRequest>>> = Default::default();
+    let sink = requests.clone();
+    let weak = Arc::downgrade(&player);
+    player.set_handler(Arc::new(move |request| {
+        if let (
+            PlayerRequest::SetVolume(volume) | PlayerRequest::RampVolume(volume),
+            Some(player),
+        ) = (&request, weak.upgrade())
+        {
+            player.set_volume(*volume);
+        }
+        sink.lock().unwrap().push(request);
+    }));
+    (player, requests)
+}
+
+fn volumes(requests: &[PlayerRequest]) -> Vec<u8> {
+    requests
+        .iter()
+        .filter_map(|r| match r {
+            PlayerRequest::SetVolume(volume) | PlayerRequest::RampVolume(volume) => Some(*volume),
+            _ => None,
+        })
+        .collect()
+}
+
+fn station(id: &str) -> RadioStation {
+    AmgSource::new_station(id, id, "http://stream")
+}
+
+#[test]
+fn adjacent_favorite_wraps_around() {
+    let favorites = vec![station("a"), station("b"), station("c")];
+    let id = |offset, current| adjacent_favorite(&favorites, current, offset).map(|s| s.id);
+    assert_eq!(id(1, Some("amg_c")), Some("amg_a".to_string()));
+    assert_eq!(id(-1, Some("amg_a")), Some("amg_c".to_string()));
+    assert_eq!(id(1, None), Some("amg_a".to_string()));
+    assert_eq!(id(-1, Some("unknown")), Some("amg_c".to_string()));
+    assert!(adjacent_favorite(&[], None, 1).is_none());
+}
+
+#[test]
+fn fade_is_linear() {
+    let fade = Duration::from_secs(10);
+    assert_eq!(sleep_timer::faded_volume(80, fade, fade), 80);
+    assert_eq!(
+        sleep_timer::faded_volume(80, Duration::from_secs(5), fade),
+        40
+    );
+    assert_eq!(sleep_timer::faded_volume(80, Duration::ZERO, fade), 0);
+}
+
+#[tokio::test]
+async fn sleep_timer_fades_stops_and_restores_volume() {
+    let (player, requests) = recording_player(60);
+    let timer = SleepTimer::new(player.clone());
+    let updates: Arc<Mutex<Vec<SleepTimerStatus>>> = Default::default();
+    let sink = updates.clone();
+
+    timer
+        .start(
+            Duration::from_millis(400),
+            Duration::from_millis(300),
+            SleepAction::Hide,
+            Arc::new(move |status| sink.lock().unwrap().push(status.clone())),
+        )
+        .await;
+    assert!(timer.status().await.is_some());
+    tokio::time::sleep(Duration::from_millis(800)).await;
+
+    let requests = requests.lock().unwrap().clone();
+    let volumes = volumes(&requests);
+    // Громкость убывает до нуля, после остановки возвращается прежняя
+    assert!(volumes.len() > 2, "{:?}", volumes);
+    assert!(volumes[..volumes.len() - 1].windows(2).all(|w| w[0] > w[1]));
+    assert_eq!(volumes[volumes.len() - 2], 0);
+    assert_eq!(volumes.last(), Some(&60));
+    let stop = requests.iter().position(|r| *r == PlayerRequest::Stop);
+    let restore = requests
+        .iter()
+        .rposition(|r| *r == PlayerRequest::SetVolume(60));
+    assert!(stop < restore);
+    // В настройки сохраняется только возвращённая громкость
+    let saved: Vec<_> = requests
+        .iter()
+        .filter(|r| matches!(r, PlayerRequest::SetVolume(_)))
+        .collect();
+    assert_eq!(saved, [&PlayerRequest::SetVolume(60)]);
+    assert_eq!(requests.last(), Some(&PlayerRequest::HideWindow));
+
+    assert!(timer.status().await.is_none());
+    let updates = updates.lock().unwrap();
+    assert!(updates.iter().any(|s| s.fading));
+    assert!(updates.last().unwrap().finished);
+}
+
+#[tokio::test]
+async fn cancelled_sleep_timer_restores_volume() {
+    let (player, requests) = recording_player(50);
+    let timer = SleepTimer::new(player.clone());
+
+    timer
+        .start(
+            Duration::from_millis(600),
+            Duration::from_millis(500),
+            SleepAction::Stop,
+            Arc::new(|_| {}),
+        )
+        .await;
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(timer.cancel().await.is_some());
+
+    let requests = requests.lock().unwrap().clone();
+    assert!(!requests.contains(&PlayerRequest::Stop));
+    assert_eq!(requests.last(), Some(&PlayerRequest::SetVolume(50)));
+    assert_eq!(player.now_playing().volume, 50);
+    assert!(timer.status().await.is_none());
+}
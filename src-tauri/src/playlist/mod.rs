@@ -0,0 +1,68 @@
+mod parser;
+mod writer;
+
+#[cfg(test)]
+mod tests;
+
+pub use parser::{parse_playlist, PlaylistEntry};
+pub use writer::write_playlist;
+
+use std::path::Path;
+
+/// Формат плейлиста
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaylistFormat {
+    /// M3U / расширенный M3U (M3U8)
+    M3u,
+    /// PLS (Winamp/Shoutcast)
+    Pls,
+    /// XSPF (XML Shareable Playlist Format)
+    Xspf,
+}
+
+impl PlaylistFormat {
+    /// Определить формат по расширению файла
+    pub fn from_path(path: &Path) -> Option<Self> {
+        Self::from_name(path.extension()?.to_str()?)
+    }
+
+    /// Формат по имени ("m3u", "m3u8", "pls", "xspf")
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().trim_start_matches('.').to_lowercase().as_str() {
+            "m3u" | "m3u8" => Some(PlaylistFormat::M3u),
+            "pls" => Some(PlaylistFormat::Pls),
+            "xspf" => Some(PlaylistFormat::Xspf),
+            _ => None,
+        }
+    }
+
+    /// Определить формат по содержимому
+    pub fn sniff(content: &str) -> Option<Self> {
+        let head = content.trim_start_matches('\u{feff}').trim_start();
+        let lower = head.chars().take(256).collect::<String>().to_lowercase();
+        if lower.starts_with("#extm3u") || lower.starts_with("http") {
+            Some(PlaylistFormat::M3u)
+        } else if lower.starts_with("[playlist]") {
+            Some(PlaylistFormat::Pls)
+        } else if lower.starts_with("<?xml") || lower.starts_with("<playlist") {
+            Some(PlaylistFormat::Xspf)
+        } else {
+            None
+        }
+    }
+}
+
+/// Нормализовать URL потока для поиска дубликатов:
+/// схема и хост в нижнем регистре, без порта по умолчанию, фрагмента и завершающего слэша
+pub fn normalize_stream_url(stream_url: &str) -> String {
+    let trimmed = stream_url.trim();
+    match url::Url::parse(trimmed) {
+        Ok(mut parsed) => {
+            parsed.set_fragment(None);
+            // Url уже приводит схему/хост к нижнему регистру и убирает порт по умолчанию
+            let normalized = parsed.to_string();
+            normalized.trim_end_matches('/').to_string()
+        }
+        Err(_) => trimmed.trim_end_matches('/').to_lowercase(),
+    }
+}
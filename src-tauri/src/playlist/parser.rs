@@ -0,0 +1,183 @@
+use super::PlaylistFormat;
+use crate::error::{AppError, AppResult};
+
+/// Запись плейлиста
+#[derive(Debug, Clone, Default)]
+pub struct PlaylistEntry {
+    /// URL потока
+    pub url: String,
+    /// Название (#EXTINF, TitleN=, <title>)
+    pub title: Option<String>,
+    /// Логотип (#EXTIMG, tvg-logo, <image>)
+    pub logo: Option<String>,
+}
+
+/// Разобрать плейлист. Файлы не в UTF-8 декодируются как Windows-1251.
+/// Возвращаются только записи с http(s) URL.
+pub fn parse_playlist(
+    bytes: &[u8],
+    format: Option<PlaylistFormat>,
+) -> AppResult<Vec<PlaylistEntry>> {
+    let content = decode(bytes);
+    let format = format
+        .or_else(|| PlaylistFormat::sniff(&content))
+        .ok_or_else(|| AppError::parse("Неизвестный формат плейлиста"))?;
+
+    let entries = match format {
+        PlaylistFormat::M3u => {
+            if content.contains("#EXT-X-TARGETDURATION") || content.contains("#EXT-X-STREAM-INF") {
+                return Err(AppError::invalid_input(
+                    "Это HLS поток, а не список станций",
+                ));
+            }
+            parse_m3u(&content)
+        }
+        PlaylistFormat::Pls => parse_pls(&content),
+        PlaylistFormat::Xspf => parse_xspf(&content)?,
+    };
+
+    Ok(entries
+        .into_iter()
+        .filter(|e| is_stream_url(&e.url))
+        .collect())
+}
+
+fn decode(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => s.trim_start_matches('\u{feff}').to_string(),
+        Err(_) => {
+            let (decoded, _, _) = encoding_rs::WINDOWS_1251.decode(bytes);
+            decoded.into_owned()
+        }
+    }
+}
+
+fn is_stream_url(url: &str) -> bool {
+    let lower = url.to_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+/// Расширенный M3U: `#EXTINF:-1 tvg-logo="...",Название`, `#EXTIMG:logo`, затем URL
+fn parse_m3u(content: &str) -> Vec<PlaylistEntry> {
+    let mut entries = Vec::new();
+    let mut pending = PlaylistEntry::default();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(info) = line.strip_prefix("#EXTINF:") {
+            let (attributes, title) = split_extinf(info);
+            pending.title = non_empty(title);
+            if let Some(logo) = extinf_attribute(attributes, "tvg-logo") {
+                pending.logo = Some(logo);
+            }
+        } else if let Some(logo) = line.strip_prefix("#EXTIMG:") {
+            pending.logo = non_empty(logo);
+        } else if line.starts_with('#') {
+            continue;
+        } else {
+            let mut entry = std::mem::take(&mut pending);
+            entry.url = line.to_string();
+            entries.push(entry);
+        }
+    }
+
+    entries
+}
+
+/// Разделить `#EXTINF` на атрибуты и название (первая запятая вне кавычек)
+fn split_extinf(info: &str) -> (&str, &str) {
+    let mut in_quotes = false;
+    for (i, c) in info.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => return (&info[..i], &info[i + 1..]),
+            _ => {}
+        }
+    }
+    (info, "")
+}
+
+/// Значение атрибута вида `name="value"` из `#EXTINF`
+fn extinf_attribute(attributes: &str, name: &str) -> Option<String> {
+    let pattern = format!("{}=\"", name);
+    let start = attributes.find(&pattern)? + pattern.len();
+    let end = attributes[start..].find('"')? + start;
+    non_empty(&attributes[start..end])
+}
+
+/// PLS: `FileN=`, `TitleN=`
+fn parse_pls(content: &str) -> Vec<PlaylistEntry> {
+    let mut by_index: std::collections::BTreeMap<u32, PlaylistEntry> =
+        std::collections::BTreeMap::new();
+
+    for line in content.lines() {
+        let (key, value) = match line.trim().split_once('=') {
+            Some(kv) => kv,
+            None => continue,
+        };
+        let key = key.trim().to_lowercase();
+
+        let (field, index) = if let Some(index) = key.strip_prefix("file") {
+            ("file", index)
+        } else if let Some(index) = key.strip_prefix("title") {
+            ("title", index)
+        } else {
+            continue;
+        };
+        let index: u32 = match index.parse() {
+            Ok(i) => i,
+            Err(_) => continue,
+        };
+
+        let entry = by_index.entry(index).or_default();
+        match field {
+            "file" => entry.url = value.trim().to_string(),
+            _ => entry.title = non_empty(value),
+        }
+    }
+
+    by_index
+        .into_values()
+        .filter(|e| !e.url.is_empty())
+        .collect()
+}
+
+/// XSPF: `<track><location/><title/><image/></track>`
+fn parse_xspf(content: &str) -> AppResult<Vec<PlaylistEntry>> {
+    let document = roxmltree::Document::parse(content)?;
+
+    let child_text = |node: roxmltree::Node, name: &str| {
+        node.children()
+            .find(|c| c.is_element() && c.tag_name().name() == name)
+            .and_then(|c| c.text())
+            .and_then(non_empty)
+    };
+
+    let entries = document
+        .descendants()
+        .filter(|n| n.is_element() && n.tag_name().name() == "track")
+        .filter_map(|track| {
+            let url = child_text(track, "location")?;
+            Some(PlaylistEntry {
+                url,
+                title: child_text(track, "title").or_else(|| child_text(track, "creator")),
+                logo: child_text(track, "image"),
+            })
+        })
+        .collect();
+
+    Ok(entries)
+}
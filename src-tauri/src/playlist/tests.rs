@@ -0,0 +1,286 @@
+use super::*;
+
+/// (url, title, logo) записи плейлиста
+type Row = (String, Option<String>, Option<String>);
+
+fn parsed(content: &[u8], format: Option<PlaylistFormat>) -> Vec<Row> {
+    parse_playlist(content, format)
+        .unwrap()
+        .into_iter()
+        .map(|e| (e.url, e.title, e.logo))
+        .collect()
+}
+
+fn entry(url: &str, title: Option<&str>, logo: Option<&str>) -> Row {
+    (
+        url.to_string(),
+        title.map(String::from),
+        logo.map(String::from),
+    )
+}
+
+#[test]
+fn m3u_titles_logos_and_line_endings() {
+    let cases: &[(&str, &[u8], Vec<Row>)] = &[
+        (
+            "запятые в названии и в кавычках атрибутов",
+            b"#EXTM3U\n#EXTINF:-1 tvg-logo=\"http://logo/a,b.png\" group-title=\"Rock, Pop\",Rock, Pop & Roll\nhttp://a/stream\n",
+            vec![entry("http://a/stream", Some("Rock, Pop & Roll"), Some("http://logo/a,b.png"))],
+        ),
+        (
+            "#EXTIMG заменяет tvg-logo, запись без #EXTINF",
+            b"#EXTM3U\n#EXTINF:-1 tvg-logo=\"http://logo/old.png\",A\n#EXTIMG:http://logo/new.png\nhttp://a/stream\nhttp://b/stream\n",
+            vec![
+                entry("http://a/stream", Some("A"), Some("http://logo/new.png")),
+                entry("http://b/stream", None, None),
+            ],
+        ),
+        (
+            "BOM и CRLF",
+            "\u{feff}#EXTM3U\r\n#EXTINF:-1,Радио Один\r\nhttp://a/stream\r\n\r\n".as_bytes(),
+            vec![entry("http://a/stream", Some("Радио Один"), None)],
+        ),
+        (
+            "простой список URL, не-http строки отбрасываются",
+            b"http://a/stream\n  \nfile:///music/track.mp3\nrtsp://b/stream\nHTTPS://c/stream\n",
+            vec![entry("http://a/stream", None, None), entry("HTTPS://c/stream", None, None)],
+        ),
+        (
+            "название не переходит на следующую запись",
+            b"#EXTM3U\n#EXTINF:-1,A\n#EXTVLCOPT:network-caching=1000\nhttp://a/stream\nhttp://b/stream\n",
+            vec![entry("http://a/stream", Some("A"), None), entry("http://b/stream", None, None)],
+        ),
+    ];
+
+    for (name, content, expected) in cases {
+        assert_eq!(&parsed(content, None), expected, "{}", name);
+    }
+}
+
+#[test]
+fn m3u_in_windows_1251_is_decoded() {
+    let (content, _, _) =
+        encoding_rs::WINDOWS_1251.encode("#EXTM3U\n#EXTINF:-1,Русское Радио\nhttp://a/stream\n");
+    assert_eq!(
+        parsed(&content, Some(PlaylistFormat::M3u)),
+        vec![entry("http://a/stream", Some("Русское Радио"), None)]
+    );
+}
+
+#[test]
+fn pls_entries_follow_numbers_with_gaps() {
+    let cases: &[(&str, &str, Vec<Row>)] = &[
+        (
+            "пропуски в номерах, порядок по номеру, а не по строкам",
+            "[playlist]\nFile3=http://c/stream\nTitle3=C\nFile1=http://a/stream\nTitle1=A\nTitle2=Без файла\nFile10=http://j/stream\nNumberOfEntries=3\nVersion=2\n",
+            vec![
+                entry("http://a/stream", Some("A"), None),
+                entry("http://c/stream", Some("C"), None),
+                entry("http://j/stream", None, None),
+            ],
+        ),
+        (
+            "регистр ключей, пробелы, = в URL, CRLF",
+            "\u{feff}[Playlist]\r\nfile1 = http://a/stream?x=1&y=2 \r\nTITLE1= A = B \r\nLength1=-1\r\nFileX=http://bad/stream\r\n",
+            vec![entry("http://a/stream?x=1&y=2", Some("A = B"), None)],
+        ),
+    ];
+
+    for (name, content, expected) in cases {
+        assert_eq!(&parsed(content.as_bytes(), None), expected, "{}", name);
+    }
+}
+
+#[test]
+fn xspf_decodes_entities_and_falls_back_to_creator() {
+    let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<playlist version="1" xmlns="http://xspf.org/ns/0/">
+  <trackList>
+    <track>
+      <location>http://a/stream?x=1&amp;y=2</location>
+      <title>Rock &amp; Roll &quot;FM&quot; &#1056;&#1091;</title>
+      <image>http://logo/a.png?s=1&amp;t=2</image>
+    </track>
+    <track>
+      <location> http://b/stream </location>
+      <creator>Автор</creator>
+    </track>
+    <track>
+      <title>Без location</title>
+    </track>
+  </trackList>
+</playlist>"#;
+
+    assert_eq!(
+        parsed(content.as_bytes(), None),
+        vec![
+            entry(
+                "http://a/stream?x=1&y=2",
+                Some("Rock & Roll \"FM\" Ру"),
+                Some("http://logo/a.png?s=1&t=2")
+            ),
+            entry("http://b/stream", Some("Автор"), None),
+        ]
+    );
+
+    let error = parse_playlist(b"<playlist><trackList>", Some(PlaylistFormat::Xspf)).unwrap_err();
+    assert_eq!(error.kind, crate::error::ErrorKind::Parse);
+}
+
+#[test]
+fn format_is_sniffed_and_hls_is_rejected() {
+    assert_eq!(
+        PlaylistFormat::sniff("\u{feff}  #EXTM3U\n"),
+        Some(PlaylistFormat::M3u)
+    );
+    assert_eq!(
+        PlaylistFormat::sniff("[playlist]"),
+        Some(PlaylistFormat::Pls)
+    );
+    assert_eq!(
+        PlaylistFormat::sniff("<?xml version=\"1.0\"?><playlist/>"),
+        Some(PlaylistFormat::Xspf)
+    );
+    assert_eq!(
+        PlaylistFormat::from_name(".M3U8"),
+        Some(PlaylistFormat::M3u)
+    );
+    assert_eq!(PlaylistFormat::from_name("txt"), None);
+    assert!(parse_playlist(b"just text", None).is_err());
+
+    let hls = b"#EXTM3U\n#EXT-X-STREAM-INF:BANDWIDTH=128000\nhttp://a/128.m3u8\n";
+    let error = parse_playlist(hls, None).unwrap_err();
+    assert_eq!(error.kind, crate::error::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn equivalent_stream_urls_normalize_to_the_same_key() {
+    let groups: &[&[&str]] = &[
+        &[
+            "http://example.com/live",
+            "HTTP://Example.COM:80/live",
+            "http://example.com/live/",
+            " http://example.com/live#player ",
+        ],
+        &[
+            "https://example.com/",
+            "https://EXAMPLE.com:443",
+            "https://example.com",
+        ],
+        &["not a url/", "NOT A URL"],
+    ];
+    for group in groups {
+        let keys: Vec<String> = group.iter().map(|url| normalize_stream_url(url)).collect();
+        assert!(
+            keys.iter().all(|k| k == &keys[0]),
+            "{:?} -> {:?}",
+            group,
+            keys
+        );
+    }
+
+    // Путь, порт и query различают потоки
+    let distinct = [
+        "http://example.com/live",
+        "http://example.com/Live",
+        "http://example.com:8000/live",
+        "http://example.com/live?bitrate=64",
+        "https://example.com/live",
+    ];
+    let keys: std::collections::HashSet<String> = distinct
+        .iter()
+        .map(|url| normalize_stream_url(url))
+        .collect();
+    assert_eq!(keys.len(), distinct.len());
+}
+
+fn stations() -> Vec<PlaylistEntry> {
+    vec![
+        PlaylistEntry {
+            url: "http://a/stream?x=1&y=2".to_string(),
+            title: Some("Rock, Pop & <Roll> \"FM\" 'Радио'".to_string()),
+            logo: Some("http://logo/a.png?s=1&t=<2>".to_string()),
+        },
+        PlaylistEntry {
+            url: "https://b/stream".to_string(),
+            title: Some("Русская Волна".to_string()),
+            logo: None,
+        },
+        PlaylistEntry {
+            url: "http://c/stream".to_string(),
+            title: None,
+            logo: None,
+        },
+    ]
+}
+
+fn rows(entries: Vec<PlaylistEntry>) -> Vec<Row> {
+    entries
+        .into_iter()
+        .map(|e| (e.url, e.title, e.logo))
+        .collect()
+}
+
+#[test]
+fn written_playlists_parse_back() {
+    let expected = rows(stations());
+    for format in [PlaylistFormat::M3u, PlaylistFormat::Xspf] {
+        let written = write_playlist(format, &stations());
+        assert_eq!(PlaylistFormat::sniff(&written), Some(format));
+        assert_eq!(
+            parsed(written.as_bytes(), None),
+            expected,
+            "{:?}:\n{}",
+            format,
+            written
+        );
+    }
+
+    // В PLS логотипов нет
+    let written = write_playlist(PlaylistFormat::Pls, &stations());
+    assert!(written.ends_with("NumberOfEntries=3\nVersion=2\n"));
+    let without_logos: Vec<Row> = expected
+        .into_iter()
+        .map(|(url, title, _)| (url, title, None))
+        .collect();
+    assert_eq!(parsed(written.as_bytes(), None), without_logos);
+}
+
+#[test]
+fn xspf_escapes_names_and_logos() {
+    let written = write_playlist(PlaylistFormat::Xspf, &stations()[..1]);
+    assert!(written
+        .contains("<title>Rock, Pop &amp; &lt;Roll&gt; &quot;FM&quot; &apos;Радио&apos;</title>"));
+    assert!(written.contains("<image>http://logo/a.png?s=1&amp;t=&lt;2&gt;</image>"));
+    assert!(written.contains("<location>http://a/stream?x=1&amp;y=2</location>"));
+    assert!(roxmltree::Document::parse(&written).is_ok());
+}
+
+#[test]
+fn line_breaks_and_quotes_cannot_break_m3u_and_pls() {
+    let entries = vec![PlaylistEntry {
+        url: " http://a/stream\n".to_string(),
+        title: Some("Первая\r\nвторая строка".to_string()),
+        logo: Some("http://logo/\"a\".png".to_string()),
+    }];
+
+    let m3u = write_playlist(PlaylistFormat::M3u, &entries);
+    assert_eq!(
+        parsed(m3u.as_bytes(), None),
+        vec![entry(
+            "http://a/stream",
+            Some("Первая  вторая строка"),
+            Some("http://logo/%22a%22.png")
+        )]
+    );
+
+    let pls = write_playlist(PlaylistFormat::Pls, &entries);
+    assert_eq!(
+        parsed(pls.as_bytes(), None),
+        vec![entry(
+            "http://a/stream",
+            Some("Первая  вторая строка"),
+            None
+        )]
+    );
+}
@@ -0,0 +1,79 @@
+use super::{PlaylistEntry, PlaylistFormat};
+
+/// Сформировать плейлист в заданном формате
+pub fn write_playlist(format: PlaylistFormat, entries: &[PlaylistEntry]) -> String {
+    match format {
+        PlaylistFormat::M3u => write_m3u(entries),
+        PlaylistFormat::Pls => write_pls(entries),
+        PlaylistFormat::Xspf => write_xspf(entries),
+    }
+}
+
+/// Одна строка без переводов строк (для M3U/PLS)
+fn single_line(value: &str) -> String {
+    value.replace(['\r', '\n'], " ").trim().to_string()
+}
+
+fn write_m3u(entries: &[PlaylistEntry]) -> String {
+    let mut out = String::from("#EXTM3U\n");
+    for entry in entries {
+        let title = entry.title.as_deref().map(single_line).unwrap_or_default();
+        match entry.logo.as_deref() {
+            Some(logo) => {
+                let logo = single_line(logo).replace('"', "%22");
+                out.push_str(&format!("#EXTINF:-1 tvg-logo=\"{}\",{}\n", logo, title));
+                out.push_str(&format!("#EXTIMG:{}\n", logo));
+            }
+            None => out.push_str(&format!("#EXTINF:-1,{}\n", title)),
+        }
+        out.push_str(&single_line(&entry.url));
+        out.push('\n');
+    }
+    out
+}
+
+fn write_pls(entries: &[PlaylistEntry]) -> String {
+    let mut out = String::from("[playlist]\n");
+    for (i, entry) in entries.iter().enumerate() {
+        let n = i + 1;
+        out.push_str(&format!("File{}={}\n", n, single_line(&entry.url)));
+        if let Some(title) = entry.title.as_deref() {
+            out.push_str(&format!("Title{}={}\n", n, single_line(title)));
+        }
+        out.push_str(&format!("Length{}=-1\n", n));
+    }
+    out.push_str(&format!("NumberOfEntries={}\nVersion=2\n", entries.len()));
+    out
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn write_xspf(entries: &[PlaylistEntry]) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\">\n  <trackList>\n",
+    );
+    for entry in entries {
+        out.push_str("    <track>\n");
+        out.push_str(&format!(
+            "      <location>{}</location>\n",
+            xml_escape(entry.url.trim())
+        ));
+        if let Some(title) = entry.title.as_deref() {
+            out.push_str(&format!("      <title>{}</title>\n", xml_escape(title)));
+        }
+        if let Some(logo) = entry.logo.as_deref() {
+            out.push_str(&format!("      <image>{}</image>\n", xml_escape(logo)));
+        }
+        out.push_str("    </track>\n");
+    }
+    out.push_str("  </trackList>\n</playlist>\n");
+    out
+}
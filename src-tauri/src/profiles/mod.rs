@@ -0,0 +1,165 @@
+//! Профили слушателей: у каждого своё избранное, история треков, громкость, формат потока
+//! и уведомления. Каталоги станций, свои станции и остальные настройки — общие.
+//!
+//! Настройки текущего профиля лежат в основных полях `settings.json` (их читает весь код),
+//! а при переключении сохраняются в список профилей. Избранное и история разделены в базе.
+
+#[cfg(test)]
+mod tests;
+
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+
+pub use crate::store::DEFAULT_PROFILE;
+
+/// Самое длинное имя профиля (в символах)
+const MAX_NAME_LEN: usize = 40;
+
+/// Настройки, которые у каждого профиля свои
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfilePreferences {
+    pub volume: u8,
+    pub streaming_mode: String,
+    pub show_notifications: bool,
+    pub show_station_notifications: bool,
+}
+
+impl Default for ProfilePreferences {
+    fn default() -> Self {
+        Self {
+            volume: 50,
+            streaming_mode: "mp3".to_string(),
+            show_notifications: true,
+            show_station_notifications: true,
+        }
+    }
+}
+
+/// Профиль слушателя
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    /// У текущего профиля могут быть устаревшими — актуальные в основных настройках
+    #[serde(flatten)]
+    pub preferences: ProfilePreferences,
+}
+
+/// Список профилей и текущий профиль (поле `profiles` в `settings.json`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profiles {
+    pub active: String,
+    pub list: Vec<Profile>,
+}
+
+impl Default for Profiles {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_PROFILE.to_string(),
+            list: vec![Profile {
+                id: DEFAULT_PROFILE.to_string(),
+                name: "Основной".to_string(),
+                preferences: ProfilePreferences::default(),
+            }],
+        }
+    }
+}
+
+impl Profiles {
+    /// Профили с актуальными настройками текущего (`current`)
+    pub fn list(&self, current: &ProfilePreferences) -> Vec<Profile> {
+        self.list
+            .iter()
+            .cloned()
+            .map(|mut profile| {
+                if profile.id == self.active {
+                    profile.preferences = current.clone();
+                }
+                profile
+            })
+            .collect()
+    }
+
+    /// Создать профиль с настройками по умолчанию
+    pub fn create(&mut self, name: &str) -> AppResult<Profile> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::invalid_input("Укажите имя профиля"));
+        }
+        if name.chars().count() > MAX_NAME_LEN {
+            return Err(AppError::invalid_input(format!(
+                "Имя профиля длиннее {} символов",
+                MAX_NAME_LEN
+            )));
+        }
+        if self
+            .list
+            .iter()
+            .any(|p| p.name.to_lowercase() == name.to_lowercase())
+        {
+            return Err(AppError::invalid_input(format!(
+                "Профиль «{}» уже есть",
+                name
+            )));
+        }
+
+        let base = format!("profile_{}", chrono::Utc::now().timestamp_millis());
+        let mut id = base.clone();
+        let mut n = 1;
+        while self.find(&id).is_some() {
+            n += 1;
+            id = format!("{}_{}", base, n);
+        }
+
+        let profile = Profile {
+            id,
+            name: name.to_string(),
+            preferences: ProfilePreferences::default(),
+        };
+        self.list.push(profile.clone());
+        Ok(profile)
+    }
+
+    /// Сделать профиль текущим. Настройки прежнего (`current`) запоминаются;
+    /// возвращаются настройки нового.
+    pub fn switch(
+        &mut self,
+        id: &str,
+        current: ProfilePreferences,
+    ) -> AppResult<ProfilePreferences> {
+        let next = self
+            .find(id)
+            .ok_or_else(|| not_found(id))?
+            .preferences
+            .clone();
+        let active = self.active.clone();
+        if let Some(profile) = self.list.iter_mut().find(|p| p.id == active) {
+            profile.preferences = current;
+        }
+        self.active = id.to_string();
+        Ok(next)
+    }
+
+    /// Удалить профиль из списка (текущий удалить нельзя)
+    pub fn delete(&mut self, id: &str) -> AppResult<Profile> {
+        if id == self.active {
+            return Err(AppError::invalid_input(
+                "Нельзя удалить текущий профиль — сначала переключитесь на другой",
+            ));
+        }
+        let index = self
+            .list
+            .iter()
+            .position(|p| p.id == id)
+            .ok_or_else(|| not_found(id))?;
+        Ok(self.list.remove(index))
+    }
+
+    fn find(&self, id: &str) -> Option<&Profile> {
+        self.list.iter().find(|p| p.id == id)
+    }
+}
+
+fn not_found(id: &str) -> AppError {
+    AppError::not_found(format!("Профиль {} не найден", id))
+}
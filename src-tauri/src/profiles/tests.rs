@@ -0,0 +1,59 @@
+use super::*;
+
+fn preferences(volume: u8) -> ProfilePreferences {
+    ProfilePreferences {
+        volume,
+        ..ProfilePreferences::default()
+    }
+}
+
+#[test]
+fn profiles_are_created_with_unique_names_and_ids() {
+    let mut profiles = Profiles::default();
+    let anna = profiles.create("  Анна ").unwrap();
+    assert_eq!(anna.name, "Анна");
+    assert_eq!(anna.preferences, ProfilePreferences::default());
+
+    let boris = profiles.create("Борис").unwrap();
+    assert_ne!(anna.id, boris.id);
+    assert_ne!(anna.id, DEFAULT_PROFILE);
+
+    assert!(profiles.create("анна").is_err());
+    assert!(profiles.create("   ").is_err());
+    assert!(profiles.create(&"я".repeat(MAX_NAME_LEN + 1)).is_err());
+    assert_eq!(profiles.list.len(), 3);
+}
+
+#[test]
+fn switching_keeps_preferences_of_each_profile() {
+    let mut profiles = Profiles::default();
+    let anna = profiles.create("Анна").unwrap();
+
+    // Громкость основного профиля (80) запоминается, у Анны — по умолчанию
+    let next = profiles.switch(&anna.id, preferences(80)).unwrap();
+    assert_eq!(next, ProfilePreferences::default());
+    assert_eq!(profiles.active, anna.id);
+
+    let listed = profiles.list(&preferences(30));
+    assert_eq!(listed[0].preferences.volume, 80);
+    assert_eq!(listed[1].preferences.volume, 30);
+
+    let next = profiles.switch(DEFAULT_PROFILE, preferences(30)).unwrap();
+    assert_eq!(next.volume, 80);
+    let next = profiles.switch(&anna.id, next).unwrap();
+    assert_eq!(next.volume, 30);
+
+    assert!(profiles.switch("нет такого", preferences(10)).is_err());
+    assert_eq!(profiles.active, anna.id);
+}
+
+#[test]
+fn active_profile_cannot_be_deleted() {
+    let mut profiles = Profiles::default();
+    let anna = profiles.create("Анна").unwrap();
+
+    assert!(profiles.delete(DEFAULT_PROFILE).is_err());
+    assert_eq!(profiles.delete(&anna.id).unwrap().name, "Анна");
+    assert!(profiles.delete(&anna.id).is_err());
+    assert_eq!(profiles.list.len(), 1);
+}
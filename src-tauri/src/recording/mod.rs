@@ -0,0 +1,404 @@
+mod ogg;
+mod tags;
+mod writer;
+
+#[cfg(test)]
+mod tests;
+
+use crate::error::{AppError, AppResult};
+use crate::models::RadioStation;
+use crate::services::StationService;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tags::{sanitize_file_name, TrackTags};
+use tokio::sync::{mpsc, oneshot, Mutex, RwLock};
+use tokio::time::Instant;
+use writer::{StreamFormat, TrackWriter};
+
+/// Интервал опроса метаданных, если источник не сообщает время окончания трека
+const METADATA_POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Сколько ждать данных потока, прежде чем переподключиться
+const STREAM_STALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Сколько раз подряд переподключаться к оборвавшемуся потоку
+const MAX_RECONNECTS: u32 = 3;
+
+/// Состояние записи (для фронтенда)
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingStatus {
+    pub station_id: String,
+    pub station_name: String,
+    /// Папка сессии записи
+    pub directory: String,
+    /// Файл, в который идёт запись
+    pub current_file: Option<String>,
+    /// Все файлы сессии
+    pub files: Vec<String>,
+    pub bytes_written: u64,
+    pub current_artist: Option<String>,
+    pub current_track: Option<String>,
+    pub started_at_ms: i64,
+    pub active: bool,
+    /// Причина остановки, если запись прервалась сама
+    pub error: Option<String>,
+}
+
+/// Обработчик изменения состояния (новый файл, остановка)
+pub type StatusCallback = Arc<dyn Fn(&RecordingStatus) + Send + Sync>;
+
+struct ActiveRecording {
+    stop: oneshot::Sender<()>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+/// Запись потока станции на диск с разбиением по трекам
+pub struct Recorder {
+    station_service: Arc<StationService>,
+    client: reqwest::Client,
+    active: Mutex<Option<ActiveRecording>>,
+    status: Arc<RwLock<Option<RecordingStatus>>>,
+}
+
+impl Recorder {
+    pub fn new(station_service: Arc<StationService>) -> Self {
+        // Без общего таймаута: поток читается часами
+        let client = reqwest::Client::builder()
+            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
+            .connect_timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            station_service,
+            client,
+            active: Mutex::new(None),
+            status: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Начать запись станции в подпапку `directory` (предыдущая запись останавливается)
+    pub async fn start(
+        &self,
+        mut station: RadioStation,
+        directory: &Path,
+        on_update: StatusCallback,
+    ) -> AppResult<RecordingStatus> {
+        self.stop().await;
+
+        let (response, format, stream_url) =
+            connect(&self.client, &self.station_service, &station).await?;
+
+        let session = format!(
+            "{} {}",
+            station.name,
+            chrono::Local::now().format("%Y-%m-%d %H-%M-%S")
+        );
+        let session_dir = directory.join(sanitize_file_name(&session));
+        tokio::fs::create_dir_all(&session_dir)
+            .await
+            .map_err(|e| AppError::from(e).context("Не удалось создать папку записи"))?;
+
+        // Текущий трек нужен для имени первого файла
+        if let Err(e) = self.station_service.update_metadata(&mut station).await {
+            eprintln!("⚠️ Запись: метаданные {} недоступны: {}", station.name, e);
+        }
+
+        let mut writer = TrackWriter::new(&session_dir, format);
+        writer.start_track(track_tags(&station));
+
+        let status = RecordingStatus {
+            station_id: station.id.clone(),
+            station_name: station.name.clone(),
+            directory: session_dir.to_string_lossy().to_string(),
+            current_file: None,
+            files: Vec::new(),
+            bytes_written: 0,
+            current_artist: station.current_artist.clone(),
+            current_track: station.current_track.clone(),
+            started_at_ms: now_ms(),
+            active: true,
+            error: None,
+        };
+        *self.status.write().await = Some(status.clone());
+        eprintln!("🎙️ Запись {} в {}", station.name, session_dir.display());
+
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let task = RecordingTask {
+            station_service: self.station_service.clone(),
+            client: self.client.clone(),
+            station,
+            stream_url,
+            writer: Arc::new(std::sync::Mutex::new(writer)),
+            status: self.status.clone(),
+            on_update,
+        };
+        let handle = tokio::spawn(task.run(response, stop_rx));
+
+        *self.active.lock().await = Some(ActiveRecording {
+            stop: stop_tx,
+            task: handle,
+        });
+
+        Ok(status)
+    }
+
+    /// Остановить запись. Возвращает итоговое состояние последней записи.
+    pub async fn stop(&self) -> Option<RecordingStatus> {
+        let active = self.active.lock().await.take();
+        if let Some(active) = active {
+            let _ = active.stop.send(());
+            let _ = active.task.await;
+        }
+        self.status.read().await.clone()
+    }
+
+    /// Текущее состояние (или итог последней записи)
+    pub async fn status(&self) -> Option<RecordingStatus> {
+        self.status.read().await.clone()
+    }
+}
+
+/// Получить URL потока и подключиться к нему
+async fn connect(
+    client: &reqwest::Client,
+    station_service: &StationService,
+    station: &RadioStation,
+) -> AppResult<(reqwest::Response, StreamFormat, String)> {
+    let url = station_service.get_stream_url(station).await?;
+    let hls_error = || AppError::invalid_input("Запись HLS потоков не поддерживается");
+
+    if url.split('?').next().unwrap_or(&url).ends_with(".m3u8") {
+        return Err(hls_error());
+    }
+
+    let response = AppError::check_status(client.get(&url).send().await?)
+        .map_err(|e| e.context("Поток недоступен"))?;
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from);
+    if content_type
+        .as_deref()
+        .is_some_and(|ct| ct.to_lowercase().contains("mpegurl"))
+    {
+        return Err(hls_error());
+    }
+
+    let format = StreamFormat::detect(content_type.as_deref(), &url);
+    Ok((response, format, url))
+}
+
+fn track_tags(station: &RadioStation) -> TrackTags {
+    TrackTags {
+        title: station.current_track.clone(),
+        artist: station.current_artist.clone(),
+        station: station.name.clone(),
+    }
+}
+
+fn now_ms() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+/// Когда снова запрашивать метаданные: к окончанию трека (101.ru) или через интервал
+fn next_metadata_poll(station: &RadioStation) -> Instant {
+    let delay = match station.stop_at_ms {
+        Some(stop_at) if stop_at > now_ms() => {
+            // Небольшой запас: сервер обновляет трек не мгновенно
+            Duration::from_millis((stop_at - now_ms()) as u64 + 2000)
+                .min(Duration::from_secs(5 * 60))
+        }
+        _ => METADATA_POLL_INTERVAL,
+    };
+    Instant::now() + delay
+}
+
+/// Опрос метаданных отдельно от чтения потока: у ICY-источников запрос открывает второе
+/// соединение и может идти до таймаута. Новый трек передаётся в `tracks`.
+async fn watch_metadata(
+    station_service: Arc<StationService>,
+    mut station: RadioStation,
+    tracks: mpsc::Sender<RadioStation>,
+) {
+    loop {
+        tokio::time::sleep_until(next_metadata_poll(&station)).await;
+        let previous = (
+            station.current_artist.clone(),
+            station.current_track.clone(),
+        );
+        if let Err(e) = station_service.update_metadata(&mut station).await {
+            eprintln!("⚠️ Запись: ошибка метаданных: {}", e);
+            continue;
+        }
+
+        let current = (
+            station.current_artist.clone(),
+            station.current_track.clone(),
+        );
+        if current != previous && current.1.is_some() && tracks.send(station.clone()).await.is_err()
+        {
+            return;
+        }
+    }
+}
+
+/// Фоновая задача записи
+struct RecordingTask {
+    station_service: Arc<StationService>,
+    client: reqwest::Client,
+    station: RadioStation,
+    /// URL потока, к которому подключена запись
+    stream_url: String,
+    /// Файлы пишутся в потоках для блокирующих операций (`spawn_blocking`)
+    writer: Arc<std::sync::Mutex<TrackWriter>>,
+    status: Arc<RwLock<Option<RecordingStatus>>>,
+    on_update: StatusCallback,
+}
+
+impl RecordingTask {
+    async fn run(mut self, mut response: reqwest::Response, mut stop: oneshot::Receiver<()>) {
+        let (track_tx, mut tracks) = mpsc::channel(4);
+        let metadata = tokio::spawn(watch_metadata(
+            self.station_service.clone(),
+            self.station.clone(),
+            track_tx,
+        ));
+        let mut reconnects = 0;
+        let mut file_count = 0;
+
+        let error = loop {
+            tokio::select! {
+                _ = &mut stop => break None,
+                Some(station) = tracks.recv() => {
+                    // Новый файл начнётся со следующего куска потока
+                    let tags = track_tags(&station);
+                    self.station = station;
+                    let started = self.with_writer(move |writer| {
+                        writer.start_track(tags);
+                        Ok(())
+                    });
+                    if let Err(e) = started.await {
+                        break Some(e.context("Ошибка записи файла"));
+                    }
+                }
+                chunk = tokio::time::timeout(STREAM_STALL_TIMEOUT, response.chunk()) => {
+                    match chunk {
+                        Ok(Ok(Some(bytes))) => {
+                            reconnects = 0;
+                            let files = self.with_writer(move |writer| {
+                                writer.write(&bytes)?;
+                                Ok(writer.files().len())
+                            });
+                            match files.await {
+                                Ok(files) => {
+                                    let new_file = files != file_count;
+                                    file_count = files;
+                                    self.update_status(new_file).await;
+                                }
+                                Err(e) => break Some(e.context("Ошибка записи файла")),
+                            }
+                        }
+                        _ => {
+                            if reconnects >= MAX_RECONNECTS {
+                                break Some(AppError::network("Поток прервался"));
+                            }
+                            reconnects += 1;
+                            match self.reconnect().await {
+                                Ok(new_response) => response = new_response,
+                                Err(e) => eprintln!("⚠️ Запись: переподключение не удалось: {}", e),
+                            }
+                        }
+                    }
+                }
+            }
+        };
+        metadata.abort();
+
+        if let Err(e) = self.with_writer(|writer| writer.finish()).await {
+            eprintln!("⚠️ Запись: ошибка закрытия файла: {}", e);
+        }
+
+        let bytes_written = self.writer().bytes_written();
+        let mut guard = self.status.write().await;
+        if let Some(status) = guard.as_mut() {
+            status.active = false;
+            status.current_file = None;
+            status.bytes_written = bytes_written;
+            status.error = error.map(|e| e.to_string());
+            eprintln!(
+                "🎙️ Запись {} остановлена, файлов: {}",
+                status.station_name,
+                status.files.len()
+            );
+            (self.on_update)(status);
+        }
+    }
+
+    fn writer(&self) -> std::sync::MutexGuard<'_, TrackWriter> {
+        self.writer.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Операция с файлами записи вне рабочих потоков tokio
+    async fn with_writer<T: Send + 'static>(
+        &self,
+        operation: impl FnOnce(&mut TrackWriter) -> AppResult<T> + Send + 'static,
+    ) -> AppResult<T> {
+        let writer = self.writer.clone();
+        tokio::task::spawn_blocking(move || {
+            operation(&mut writer.lock().unwrap_or_else(|e| e.into_inner()))
+        })
+        .await
+        .map_err(|e| AppError::io(e.to_string()))?
+    }
+
+    /// Отметить сервер нерабочим и подключиться заново (источник может выдать другой сервер)
+    async fn reconnect(&mut self) -> AppResult<reqwest::Response> {
+        self.station_service
+            .report_stream_failure(&self.station, &self.stream_url)
+            .await;
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        let (response, _, url) =
+            connect(&self.client, &self.station_service, &self.station).await?;
+        self.stream_url = url;
+        eprintln!("🎙️ Запись: переподключение к {}", self.station.name);
+        Ok(response)
+    }
+
+    async fn update_status(&self, notify: bool) {
+        let (bytes_written, files) = {
+            let writer = self.writer();
+            let files = notify.then(|| {
+                let paths = writer
+                    .files()
+                    .iter()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .collect();
+                let current = writer
+                    .current_file()
+                    .map(|p| p.to_string_lossy().to_string());
+                (paths, current)
+            });
+            (writer.bytes_written(), files)
+        };
+
+        let mut guard = self.status.write().await;
+        if let Some(status) = guard.as_mut() {
+            status.bytes_written = bytes_written;
+            if let Some((files, current_file)) = files {
+                status.files = files;
+                status.current_file = current_file;
+                status.current_artist = self.station.current_artist.clone();
+                status.current_track = self.station.current_track.clone();
+                (self.on_update)(status);
+            }
+        }
+    }
+}
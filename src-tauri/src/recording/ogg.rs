@@ -0,0 +1,322 @@
+//! Разбор Ogg-потока на страницы для записи по трекам: заголовки кодека (Vorbis/Opus)
+//! запоминаются и повторяются в начале каждого файла с новыми комментариями.
+
+use super::tags::{rewrite_comment_packet, TrackTags};
+
+const FLAG_CONTINUED: u8 = 0x01;
+const FLAG_BOS: u8 = 0x02;
+
+/// Таблица CRC-32 Ogg (полином 0x04C11DB7, без отражения)
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u32) << 24;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04C1_1DB7
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+pub(super) fn crc32(data: &[u8]) -> u32 {
+    data.iter().fold(0u32, |crc, byte| {
+        (crc << 8) ^ CRC_TABLE[(((crc >> 24) as u8) ^ byte) as usize]
+    })
+}
+
+/// Страница Ogg
+#[derive(Debug, Clone)]
+pub struct OggPage {
+    pub header_type: u8,
+    pub granule: i64,
+    pub serial: u32,
+    pub sequence: u32,
+    /// Таблица сегментов (lacing)
+    pub segments: Vec<u8>,
+    pub body: Vec<u8>,
+}
+
+impl OggPage {
+    /// Разобрать страницу в начале буфера. `None` — данных пока недостаточно.
+    pub(super) fn parse(buf: &[u8]) -> Option<(OggPage, usize)> {
+        if buf.len() < 27 {
+            return None;
+        }
+        let count = buf[26] as usize;
+        let header_len = 27 + count;
+        let segments = buf.get(27..header_len)?.to_vec();
+        let body_len: usize = segments.iter().map(|s| *s as usize).sum();
+        let body = buf.get(header_len..header_len + body_len)?.to_vec();
+
+        let page = OggPage {
+            header_type: buf[5],
+            granule: i64::from_le_bytes(buf[6..14].try_into().ok()?),
+            serial: u32::from_le_bytes(buf[14..18].try_into().ok()?),
+            sequence: u32::from_le_bytes(buf[18..22].try_into().ok()?),
+            segments,
+            body,
+        };
+        Some((page, header_len + body_len))
+    }
+
+    /// Страница начинается с продолжения пакета с предыдущей страницы
+    pub fn is_continued(&self) -> bool {
+        self.header_type & FLAG_CONTINUED != 0
+    }
+
+    fn is_bos(&self) -> bool {
+        self.header_type & FLAG_BOS != 0
+    }
+
+    /// Сериализовать страницу с пересчётом CRC
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(27 + self.segments.len() + self.body.len());
+        out.extend_from_slice(b"OggS");
+        out.push(0);
+        out.push(self.header_type);
+        out.extend_from_slice(&self.granule.to_le_bytes());
+        out.extend_from_slice(&self.serial.to_le_bytes());
+        out.extend_from_slice(&self.sequence.to_le_bytes());
+        out.extend_from_slice(&[0; 4]);
+        out.push(self.segments.len() as u8);
+        out.extend_from_slice(&self.segments);
+        out.extend_from_slice(&self.body);
+
+        let crc = crc32(&out);
+        out[22..26].copy_from_slice(&crc.to_le_bytes());
+        out
+    }
+}
+
+/// Кодек в Ogg-контейнере
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OggCodec {
+    /// Три заголовка: идентификация, комментарии, setup
+    Vorbis,
+    /// Два заголовка: OpusHead, OpusTags
+    Opus,
+}
+
+impl OggCodec {
+    fn detect(first_packet: &[u8]) -> Option<Self> {
+        if first_packet.starts_with(b"\x01vorbis") {
+            Some(OggCodec::Vorbis)
+        } else if first_packet.starts_with(b"OpusHead") {
+            Some(OggCodec::Opus)
+        } else {
+            None
+        }
+    }
+
+    fn header_count(self) -> usize {
+        match self {
+            OggCodec::Vorbis => 3,
+            OggCodec::Opus => 2,
+        }
+    }
+}
+
+/// Событие разбора потока
+pub enum OggEvent {
+    /// Получены (новые) заголовки логического потока — их нужно записать перед следующей страницей
+    Headers,
+    /// Страница с аудио
+    Page(OggPage),
+}
+
+/// Разбор Ogg-потока, поданного кусками произвольного размера
+#[derive(Default)]
+pub struct OggStream {
+    buffer: Vec<u8>,
+    codec: Option<OggCodec>,
+    serial: u32,
+    /// Полученные пакеты заголовков
+    headers: Vec<Vec<u8>>,
+    /// Незавершённый пакет заголовка
+    partial: Vec<u8>,
+    /// Заголовки ещё собираются
+    capturing: bool,
+}
+
+impl OggStream {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Поток можно делить на файлы: кодек известен и его заголовки можно повторить
+    pub fn splittable(&self) -> bool {
+        self.codec.is_some()
+    }
+
+    /// Кодек распознан и все его заголовки получены
+    pub fn headers_ready(&self) -> bool {
+        match self.codec {
+            Some(codec) => !self.capturing && self.headers.len() == codec.header_count(),
+            None => false,
+        }
+    }
+
+    /// Подать кусок потока
+    pub fn push(&mut self, data: &[u8]) -> Vec<OggEvent> {
+        self.buffer.extend_from_slice(data);
+        let mut events = Vec::new();
+
+        loop {
+            // Синхронизация по "OggS" (мусор до неё отбрасывается)
+            match self.buffer.windows(4).position(|w| w == b"OggS") {
+                Some(0) => {}
+                Some(start) => {
+                    self.buffer.drain(..start);
+                }
+                None => {
+                    let keep = self.buffer.len().min(3);
+                    self.buffer.drain(..self.buffer.len() - keep);
+                    break;
+                }
+            }
+
+            let (page, consumed) = match OggPage::parse(&self.buffer) {
+                Some(parsed) => parsed,
+                None => break,
+            };
+            self.buffer.drain(..consumed);
+
+            if page.is_bos() {
+                // Новый логический поток (в т.ч. смена трека в цепочке Icecast)
+                self.codec = None;
+                self.serial = page.serial;
+                self.headers.clear();
+                self.partial.clear();
+                self.capturing = true;
+            }
+
+            if page.serial != self.serial {
+                continue;
+            }
+
+            if self.capturing {
+                if self.capture_headers(&page) {
+                    events.push(OggEvent::Headers);
+                } else if !self.capturing {
+                    // Неизвестный кодек — страницы передаются как есть, начиная с первой
+                    events.push(OggEvent::Page(page));
+                }
+                continue;
+            }
+
+            if self.headers_ready() || self.codec.is_none() {
+                events.push(OggEvent::Page(page));
+            }
+        }
+
+        events
+    }
+
+    /// Собрать пакеты заголовков со страницы. `true` — заголовки собраны полностью.
+    fn capture_headers(&mut self, page: &OggPage) -> bool {
+        let mut offset = 0;
+        for &lacing in &page.segments {
+            let end = offset + lacing as usize;
+            self.partial.extend_from_slice(&page.body[offset..end]);
+            offset = end;
+            if lacing < 255 {
+                let packet = std::mem::take(&mut self.partial);
+                if self.headers.is_empty() {
+                    self.codec = OggCodec::detect(&packet);
+                }
+                self.headers.push(packet);
+            }
+        }
+
+        let complete = match self.codec {
+            Some(codec) => self.headers.len() >= codec.header_count(),
+            // Неизвестный кодек: заголовки не повторяем, поток не делится
+            None => {
+                self.capturing = false;
+                return false;
+            }
+        };
+        if complete {
+            self.capturing = false;
+        }
+        complete
+    }
+
+    /// Страницы заголовков для начала файла с комментариями из `tags`.
+    /// `sequence` — номер следующей страницы в файле.
+    pub fn header_pages(&self, tags: &TrackTags, sequence: &mut u32) -> Vec<u8> {
+        let mut packets = self.headers.clone();
+        if let Some(comment) = packets.get(1).and_then(|p| rewrite_comment_packet(p, tags)) {
+            packets[1] = comment;
+        }
+
+        let mut out = Vec::new();
+        // Первый заголовок — отдельной страницей с флагом начала потока
+        if let Some((first, rest)) = packets.split_first() {
+            for page in self.pages_for_packets(std::slice::from_ref(first), FLAG_BOS, sequence) {
+                out.extend_from_slice(&page.encode());
+            }
+            for page in self.pages_for_packets(rest, 0, sequence) {
+                out.extend_from_slice(&page.encode());
+            }
+        }
+        out
+    }
+
+    /// Разложить пакеты по страницам (не больше 255 сегментов на страницу)
+    fn pages_for_packets(
+        &self,
+        packets: &[Vec<u8>],
+        first_flags: u8,
+        sequence: &mut u32,
+    ) -> Vec<OggPage> {
+        let mut pages = Vec::new();
+        let mut page = self.empty_page(first_flags, *sequence);
+
+        for packet in packets {
+            let mut remaining = packet.as_slice();
+            loop {
+                if page.segments.len() == 255 {
+                    // Пакет продолжается на следующей странице, если последний сегмент полный
+                    let continued = page.segments.last() == Some(&255);
+                    *sequence += 1;
+                    let next =
+                        self.empty_page(if continued { FLAG_CONTINUED } else { 0 }, *sequence);
+                    pages.push(std::mem::replace(&mut page, next));
+                }
+                // Сегмент короче 255 завершает пакет (для длины, кратной 255, — нулевой)
+                let take = remaining.len().min(255);
+                page.segments.push(take as u8);
+                page.body.extend_from_slice(&remaining[..take]);
+                remaining = &remaining[take..];
+                if take < 255 {
+                    break;
+                }
+            }
+        }
+
+        *sequence += 1;
+        pages.push(page);
+        pages
+    }
+
+    fn empty_page(&self, header_type: u8, sequence: u32) -> OggPage {
+        OggPage {
+            header_type,
+            granule: 0,
+            serial: self.serial,
+            sequence,
+            segments: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+/// Теги записанного трека
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrackTags {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    /// Название станции (записывается как альбом)
+    pub station: String,
+}
+
+impl TrackTags {
+    /// Имя файла без расширения: "Исполнитель - Трек" или название станции
+    pub fn file_stem(&self) -> String {
+        let stem = match (&self.artist, &self.title) {
+            (Some(artist), Some(title)) => format!("{} - {}", artist, title),
+            (None, Some(title)) => title.clone(),
+            (Some(artist), None) => artist.clone(),
+            (None, None) => self.station.clone(),
+        };
+        sanitize_file_name(&stem)
+    }
+}
+
+/// Убрать из имени файла символы, недопустимые в Windows/macOS/Linux
+pub fn sanitize_file_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let cleaned = cleaned.trim().trim_end_matches('.').trim();
+    // Ограничиваем длину, чтобы не упереться в лимит пути
+    let cleaned: String = cleaned.chars().take(120).collect();
+    if cleaned.is_empty() {
+        "track".to_string()
+    } else {
+        cleaned
+    }
+}
+
+/// Тег ID3v2.3 (MP3/AAC): TIT2, TPE1, TALB и TRSN, текст в UTF-16
+pub fn id3v2(tags: &TrackTags) -> Vec<u8> {
+    let mut frames = Vec::new();
+    let fields = [
+        (b"TIT2", tags.title.as_deref()),
+        (b"TPE1", tags.artist.as_deref()),
+        (b"TALB", Some(tags.station.as_str())),
+        (b"TRSN", Some(tags.station.as_str())),
+    ];
+
+    for (id, value) in fields {
+        let value = match value.filter(|v| !v.is_empty()) {
+            Some(v) => v,
+            None => continue,
+        };
+        // Кодировка 1 = UTF-16 с BOM
+        let mut body = vec![0x01, 0xFF, 0xFE];
+        for unit in value.encode_utf16() {
+            body.extend_from_slice(&unit.to_le_bytes());
+        }
+        frames.extend_from_slice(id);
+        frames.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        frames.extend_from_slice(&[0, 0]);
+        frames.extend_from_slice(&body);
+    }
+
+    let mut tag = b"ID3\x03\x00\x00".to_vec();
+    tag.extend_from_slice(&syncsafe(frames.len() as u32));
+    tag.extend_from_slice(&frames);
+    tag
+}
+
+/// Размер ID3v2: 4 байта по 7 бит
+fn syncsafe(value: u32) -> [u8; 4] {
+    [
+        ((value >> 21) & 0x7F) as u8,
+        ((value >> 14) & 0x7F) as u8,
+        ((value >> 7) & 0x7F) as u8,
+        (value & 0x7F) as u8,
+    ]
+}
+
+/// Заменить комментарии в пакете Vorbis (`\x03vorbis`) или Opus (`OpusTags`),
+/// сохранив строку vendor исходного потока. `None` — пакет не похож на комментарии.
+pub fn rewrite_comment_packet(packet: &[u8], tags: &TrackTags) -> Option<Vec<u8>> {
+    let (magic, framing): (&[u8], bool) = if packet.starts_with(b"\x03vorbis") {
+        (b"\x03vorbis", true)
+    } else if packet.starts_with(b"OpusTags") {
+        (b"OpusTags", false)
+    } else {
+        return None;
+    };
+
+    let rest = &packet[magic.len()..];
+    let vendor_len = u32::from_le_bytes(rest.get(..4)?.try_into().ok()?) as usize;
+    let vendor = rest.get(4..4 + vendor_len)?;
+
+    let mut comments = Vec::new();
+    if let Some(title) = tags.title.as_deref().filter(|v| !v.is_empty()) {
+        comments.push(format!("TITLE={}", title));
+    }
+    if let Some(artist) = tags.artist.as_deref().filter(|v| !v.is_empty()) {
+        comments.push(format!("ARTIST={}", artist));
+    }
+    if !tags.station.is_empty() {
+        comments.push(format!("ALBUM={}", tags.station));
+        comments.push(format!("ORGANIZATION={}", tags.station));
+    }
+
+    let mut out = magic.to_vec();
+    out.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    out.extend_from_slice(vendor);
+    out.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+    for comment in &comments {
+        out.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+        out.extend_from_slice(comment.as_bytes());
+    }
+    if framing {
+        out.push(0x01);
+    }
+    Some(out)
+}
@@ -0,0 +1,343 @@
+use super::ogg::{crc32, OggEvent, OggPage, OggStream};
+use super::tags::{id3v2, rewrite_comment_packet, TrackTags};
+use super::writer::{StreamFormat, TrackWriter};
+use std::fs;
+use std::path::PathBuf;
+
+/// Страница OpusHead, записанная библиотекой `ogg` (serial 0x5EED0001, CRC 0x658D8E6A)
+const KNOWN_OPUS_HEAD_PAGE: [u8; 47] = [
+    0x4f, 0x67, 0x67, 0x53, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00,
+    0xed, 0x5e, 0x00, 0x00, 0x00, 0x00, 0x6a, 0x8e, 0x8d, 0x65, 0x01, 0x13, 0x4f, 0x70, 0x75, 0x73,
+    0x48, 0x65, 0x61, 0x64, 0x01, 0x02, 0x38, 0x01, 0x80, 0xbb, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+/// Заголовок следующей страницы той же записи: пакет из 300 байт 0xAA, granule 960
+const KNOWN_AUDIO_PAGE_HEADER: [u8; 29] = [
+    0x4f, 0x67, 0x67, 0x53, 0x00, 0x00, 0xc0, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00,
+    0xed, 0x5e, 0x01, 0x00, 0x00, 0x00, 0x65, 0x9b, 0xad, 0x35, 0x02, 0xff, 0x2d,
+];
+
+fn known_audio_page() -> Vec<u8> {
+    let mut page = KNOWN_AUDIO_PAGE_HEADER.to_vec();
+    page.extend_from_slice(&[0xAA; 300]);
+    page
+}
+
+fn tags(artist: &str, title: &str) -> TrackTags {
+    TrackTags {
+        title: Some(title.to_string()),
+        artist: Some(artist.to_string()),
+        station: "Русская Волна".to_string(),
+    }
+}
+
+/// Временная папка, удаляется после теста
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("radivo-rec-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn ogg_pages_round_trip_with_known_crc() {
+    // CRC-32 Ogg: полином 0x04C11DB7, начальное значение 0, без отражения и финального XOR
+    assert_eq!(crc32(b"123456789"), 0x89A1_897F);
+
+    let (head, consumed) = OggPage::parse(&KNOWN_OPUS_HEAD_PAGE).unwrap();
+    assert_eq!(consumed, KNOWN_OPUS_HEAD_PAGE.len());
+    assert_eq!(head.header_type, 0x02);
+    assert_eq!(head.serial, 0x5EED_0001);
+    assert_eq!(head.sequence, 0);
+    assert_eq!(head.granule, 0);
+    assert_eq!(head.segments, [19]);
+    assert!(head.body.starts_with(b"OpusHead"));
+    assert_eq!(head.encode(), KNOWN_OPUS_HEAD_PAGE);
+
+    let audio_bytes = known_audio_page();
+    let (audio, consumed) = OggPage::parse(&audio_bytes).unwrap();
+    assert_eq!(consumed, audio_bytes.len());
+    assert_eq!(audio.granule, 960);
+    assert_eq!(audio.sequence, 1);
+    assert_eq!(audio.segments, [255, 45]);
+    assert!(!audio.is_continued());
+    assert_eq!(audio.encode(), audio_bytes);
+
+    // Изменённая страница получает новую CRC
+    let mut renumbered = audio.clone();
+    renumbered.sequence = 7;
+    let encoded = renumbered.encode();
+    let mut zeroed = encoded.clone();
+    zeroed[22..26].fill(0);
+    assert_eq!(encoded[22..26], crc32(&zeroed).to_le_bytes());
+    assert_ne!(encoded[22..26], audio_bytes[22..26]);
+
+    // Неполная страница — ждём данных
+    assert!(OggPage::parse(&audio_bytes[..audio_bytes.len() - 1]).is_none());
+}
+
+/// Пакет OpusTags с одним комментарием
+fn opus_tags_packet(vendor: &str) -> Vec<u8> {
+    let mut packet = b"OpusTags".to_vec();
+    packet.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    packet.extend_from_slice(vendor.as_bytes());
+    packet.extend_from_slice(&1u32.to_le_bytes());
+    packet.extend_from_slice(&9u32.to_le_bytes());
+    packet.extend_from_slice(b"TITLE=Old");
+    packet
+}
+
+/// Заголовки Opus и аудиостраница одного логического потока
+fn opus_stream(vendor: &str) -> Vec<u8> {
+    let tags_packet = opus_tags_packet(vendor);
+    let tags_page = OggPage {
+        header_type: 0,
+        granule: 0,
+        serial: 0x5EED_0001,
+        sequence: 1,
+        segments: vec![tags_packet.len() as u8],
+        body: tags_packet,
+    };
+    let mut audio = OggPage::parse(&known_audio_page()).unwrap().0;
+    audio.sequence = 2;
+
+    let mut stream = b"junk before sync".to_vec();
+    stream.extend_from_slice(&KNOWN_OPUS_HEAD_PAGE);
+    stream.extend(tags_page.encode());
+    stream.extend(audio.encode());
+    stream
+}
+
+/// Разобрать пакет комментариев: (vendor, комментарии, байт framing)
+fn parse_comments(packet: &[u8], magic: &[u8]) -> (String, Vec<String>, Option<u8>) {
+    let mut rest = packet.strip_prefix(magic).unwrap();
+    let read_string = |rest: &mut &[u8]| {
+        let len = u32::from_le_bytes(rest[..4].try_into().unwrap()) as usize;
+        let value = String::from_utf8(rest[4..4 + len].to_vec()).unwrap();
+        *rest = &rest[4 + len..];
+        value
+    };
+    let vendor = read_string(&mut rest);
+    let count = u32::from_le_bytes(rest[..4].try_into().unwrap());
+    rest = &rest[4..];
+    let comments = (0..count).map(|_| read_string(&mut rest)).collect();
+    (vendor, comments, rest.first().copied())
+}
+
+#[test]
+fn comment_packets_are_rewritten_with_track_tags() {
+    let tags = tags("Кино", "Группа крови");
+    let expected = vec![
+        "TITLE=Группа крови".to_string(),
+        "ARTIST=Кино".to_string(),
+        "ALBUM=Русская Волна".to_string(),
+        "ORGANIZATION=Русская Волна".to_string(),
+    ];
+
+    let mut vorbis = b"\x03vorbis".to_vec();
+    let vendor = "Xiph.Org libVorbis I 20200704";
+    vorbis.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    vorbis.extend_from_slice(vendor.as_bytes());
+    vorbis.extend_from_slice(&0u32.to_le_bytes());
+    vorbis.push(1);
+
+    let rewritten = rewrite_comment_packet(&vorbis, &tags).unwrap();
+    let (parsed_vendor, comments, framing) = parse_comments(&rewritten, b"\x03vorbis");
+    assert_eq!(parsed_vendor, vendor);
+    assert_eq!(comments, expected);
+    assert_eq!(framing, Some(1));
+
+    // В Opus нет бита framing; пустые поля не пишутся
+    let opus = opus_tags_packet("libopus 1.4");
+    let station_only = TrackTags {
+        station: "Радио".to_string(),
+        title: Some(String::new()),
+        artist: None,
+    };
+    let rewritten = rewrite_comment_packet(&opus, &station_only).unwrap();
+    let (parsed_vendor, comments, framing) = parse_comments(&rewritten, b"OpusTags");
+    assert_eq!(parsed_vendor, "libopus 1.4");
+    assert_eq!(comments, ["ALBUM=Радио", "ORGANIZATION=Радио"]);
+    assert_eq!(framing, None);
+
+    assert!(rewrite_comment_packet(b"OpusHead\x01", &tags).is_none());
+    // Длина vendor больше пакета
+    assert!(rewrite_comment_packet(b"OpusTags\xff\x00\x00\x00abc", &tags).is_none());
+}
+
+#[test]
+fn ogg_stream_repeats_headers_with_new_comments() {
+    let stream = opus_stream("libopus 1.4");
+
+    // Кусками по 7 байт: страницы собираются из частей
+    let mut ogg = OggStream::new();
+    let mut events = Vec::new();
+    for chunk in stream.chunks(7) {
+        events.extend(ogg.push(chunk));
+    }
+    assert!(ogg.splittable());
+    assert!(ogg.headers_ready());
+    assert_eq!(events.len(), 2);
+    assert!(matches!(events[0], OggEvent::Headers));
+    match &events[1] {
+        OggEvent::Page(page) => assert_eq!(page.granule, 960),
+        OggEvent::Headers => panic!("ожидалась страница с аудио"),
+    }
+
+    let mut sequence = 0;
+    let headers = ogg.header_pages(&tags("Кино", "Группа крови"), &mut sequence);
+    assert_eq!(sequence, 2);
+
+    let (head, used) = OggPage::parse(&headers).unwrap();
+    assert_eq!(&headers[..used], KNOWN_OPUS_HEAD_PAGE);
+    assert_eq!(head.header_type, 0x02);
+
+    let (comments, rest) = OggPage::parse(&headers[used..]).unwrap();
+    assert_eq!(used + rest, headers.len());
+    assert_eq!(comments.sequence, 1);
+    assert_eq!(comments.header_type, 0);
+    // CRC записанной страницы верна
+    assert_eq!(comments.encode(), headers[used..]);
+    let (_, parsed, _) = parse_comments(&comments.body, b"OpusTags");
+    assert_eq!(parsed[0], "TITLE=Группа крови");
+}
+
+#[test]
+fn id3_tag_has_syncsafe_size_and_utf16_frames() {
+    let title = "Группа крови ".repeat(6);
+    let tag = id3v2(&TrackTags {
+        title: Some(title.clone()),
+        artist: None,
+        station: "Радио".to_string(),
+    });
+
+    assert_eq!(&tag[..6], b"ID3\x03\x00\x00");
+    let size = tag[6..10].iter().fold(0usize, |size, byte| {
+        assert!(byte & 0x80 == 0, "байт размера не syncsafe");
+        (size << 7) | *byte as usize
+    });
+    assert_eq!(size, tag.len() - 10);
+    // Размер больше 127 — проверяется перенос между байтами
+    assert!(size > 127);
+
+    let mut frames = Vec::new();
+    let mut rest = &tag[10..];
+    while !rest.is_empty() {
+        let id = std::str::from_utf8(&rest[..4]).unwrap().to_string();
+        let len = u32::from_be_bytes(rest[4..8].try_into().unwrap()) as usize;
+        assert_eq!(&rest[8..10], [0, 0]);
+        let body = &rest[10..10 + len];
+        assert_eq!(&body[..3], [0x01, 0xFF, 0xFE]);
+        let units: Vec<u16> = body[3..]
+            .chunks(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        frames.push((id, String::from_utf16(&units).unwrap()));
+        rest = &rest[10 + len..];
+    }
+
+    // Пустой исполнитель не записывается
+    assert_eq!(
+        frames,
+        [
+            ("TIT2".to_string(), title),
+            ("TALB".to_string(), "Радио".to_string()),
+            ("TRSN".to_string(), "Радио".to_string()),
+        ]
+    );
+}
+
+/// Кадр MPEG-1 Layer III 128 кбит/с 44.1 кГц (заголовок и заполнитель)
+fn mp3_frame(fill: u8) -> Vec<u8> {
+    let mut frame = vec![0xFF, 0xFB, 0x90, 0x64];
+    frame.extend(std::iter::repeat_n(fill, 32));
+    frame
+}
+
+/// Содержимое файла без тега ID3
+fn audio_of(path: &PathBuf) -> Vec<u8> {
+    let data = fs::read(path).unwrap();
+    assert_eq!(&data[..3], b"ID3");
+    let size = data[6..10]
+        .iter()
+        .fold(0usize, |size, byte| (size << 7) | *byte as usize);
+    data[10 + size..].to_vec()
+}
+
+#[test]
+fn track_writer_starts_new_file_at_frame_boundary() {
+    let dir = TempDir::new("mp3");
+    let mut writer = TrackWriter::new(&dir.0, StreamFormat::Mp3);
+    writer.start_track(tags("Кино", "Группа крови"));
+
+    // До первого кадра писать некуда: мусор (с ложным 0xFF) отбрасывается
+    writer.write(&[0x00, 0xFF, 0x00, 0x12]).unwrap();
+    assert!(writer.files().is_empty());
+
+    let first = [mp3_frame(0x11), mp3_frame(0x22)].concat();
+    writer
+        .write(&[&[0x01, 0x02][..], &first[..20]].concat())
+        .unwrap();
+    writer.write(&first[20..]).unwrap();
+    assert_eq!(writer.files().len(), 1);
+
+    // Смена трека посреди куска: хвост кадра остаётся в старом файле
+    writer.start_track(tags("Ария", "Беспечный ангел"));
+    let tail = [0x33; 10];
+    let second = mp3_frame(0x44);
+    writer.write(&[&tail[..], &second[..]].concat()).unwrap();
+    writer.finish().unwrap();
+
+    let files = writer.files().to_vec();
+    let names: Vec<_> = files
+        .iter()
+        .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "001 Кино - Группа крови.mp3",
+            "002 Ария - Беспечный ангел.mp3"
+        ]
+    );
+    assert_eq!(audio_of(&files[0]), [&first[..], &tail[..]].concat());
+    assert_eq!(audio_of(&files[1]), second);
+
+    let on_disk: u64 = files.iter().map(|p| fs::metadata(p).unwrap().len()).sum();
+    assert_eq!(writer.bytes_written(), on_disk);
+}
+
+#[test]
+fn adts_frames_are_recognised_for_aac() {
+    let dir = TempDir::new("aac");
+    let mut writer = TrackWriter::new(&dir.0, StreamFormat::Aac);
+    writer.start_track(tags("Кино", "Кукушка"));
+
+    // 0xFFFB — кадр MP3, но не ADTS
+    let adts = [0xFF, 0xF1, 0x50, 0x80, 0x01, 0x7F, 0xFC, 0xAB];
+    writer
+        .write(&[&[0xFF, 0xFB, 0x90][..], &adts[..]].concat())
+        .unwrap();
+    writer.finish().unwrap();
+
+    assert_eq!(writer.files().len(), 1);
+    assert_eq!(audio_of(&writer.files()[0]), adts);
+    assert_eq!(
+        StreamFormat::detect(Some("audio/aacp"), "http://a/live.mp3"),
+        StreamFormat::Aac
+    );
+    assert_eq!(
+        StreamFormat::detect(None, "http://a/live.opus?x=1"),
+        StreamFormat::Opus
+    );
+}
@@ -0,0 +1,255 @@
+use super::ogg::{OggEvent, OggStream};
+use super::tags::{id3v2, TrackTags};
+use crate::error::AppResult;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Формат записываемого потока
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamFormat {
+    Mp3,
+    Aac,
+    Ogg,
+    Opus,
+}
+
+impl StreamFormat {
+    /// Определить формат по Content-Type, затем по расширению URL (по умолчанию MP3)
+    pub fn detect(content_type: Option<&str>, url: &str) -> Self {
+        let content_type = content_type.unwrap_or_default().to_lowercase();
+        if content_type.contains("aac") || content_type.contains("mp4") {
+            return StreamFormat::Aac;
+        }
+        if content_type.contains("opus") {
+            return StreamFormat::Opus;
+        }
+        if content_type.contains("ogg") {
+            return StreamFormat::Ogg;
+        }
+        if content_type.contains("mpeg") || content_type.contains("mp3") {
+            return StreamFormat::Mp3;
+        }
+
+        let path = url.split(['?', '#']).next().unwrap_or(url).to_lowercase();
+        if path.ends_with(".aac") || path.ends_with(".aacp") {
+            StreamFormat::Aac
+        } else if path.ends_with(".opus") {
+            StreamFormat::Opus
+        } else if path.ends_with(".ogg") || path.ends_with(".oga") {
+            StreamFormat::Ogg
+        } else {
+            StreamFormat::Mp3
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            StreamFormat::Mp3 => "mp3",
+            StreamFormat::Aac => "aac",
+            StreamFormat::Ogg => "ogg",
+            StreamFormat::Opus => "opus",
+        }
+    }
+
+    fn is_ogg(self) -> bool {
+        matches!(self, StreamFormat::Ogg | StreamFormat::Opus)
+    }
+
+    /// Позиция ближайшего заголовка кадра MP3/ADTS (для Ogg не используется)
+    fn frame_sync(self, data: &[u8]) -> Option<usize> {
+        data.windows(3).position(|w| {
+            if w[0] != 0xFF {
+                return false;
+            }
+            match self {
+                // ADTS: 12 бит синхронизации, layer = 0
+                StreamFormat::Aac => w[1] & 0xF6 == 0xF0,
+                // MPEG: 11 бит синхронизации, layer, битрейт и частота не зарезервированы
+                _ => {
+                    w[1] & 0xE0 == 0xE0
+                        && (w[1] >> 1) & 0x03 != 0
+                        && w[2] >> 4 != 0x0F
+                        && (w[2] >> 2) & 0x03 != 0x03
+                }
+            }
+        })
+    }
+}
+
+/// Запись потока в файлы по трекам. Новый файл начинается на ближайшей границе
+/// кадра (MP3/AAC) или страницы (Ogg), чтобы каждый файл воспроизводился с начала.
+pub struct TrackWriter {
+    directory: PathBuf,
+    format: StreamFormat,
+    file: Option<BufWriter<File>>,
+    /// Теги текущего файла
+    tags: TrackTags,
+    /// Трек, с которого начнётся следующий файл
+    pending: Option<TrackTags>,
+    ogg: Option<OggStream>,
+    /// Номер следующей страницы Ogg в текущем файле
+    ogg_sequence: u32,
+    /// Перед следующей страницей нужно записать заголовки кодека
+    ogg_needs_headers: bool,
+    files: Vec<PathBuf>,
+    bytes_written: u64,
+}
+
+impl TrackWriter {
+    pub fn new(directory: &Path, format: StreamFormat) -> Self {
+        Self {
+            directory: directory.to_path_buf(),
+            format,
+            file: None,
+            tags: TrackTags::default(),
+            pending: None,
+            ogg: format.is_ogg().then(OggStream::new),
+            ogg_sequence: 0,
+            ogg_needs_headers: false,
+            files: Vec::new(),
+            bytes_written: 0,
+        }
+    }
+
+    /// Начать новый трек (файл откроется на ближайшей границе кадра)
+    pub fn start_track(&mut self, tags: TrackTags) {
+        self.pending = Some(tags);
+    }
+
+    /// Записать кусок потока
+    pub fn write(&mut self, data: &[u8]) -> AppResult<()> {
+        if self.format.is_ogg() {
+            self.write_ogg(data)
+        } else {
+            self.write_raw(data)
+        }
+    }
+
+    /// Закрыть текущий файл
+    pub fn finish(&mut self) -> AppResult<()> {
+        if let Some(mut file) = self.file.take() {
+            file.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Все созданные файлы
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Файл, в который идёт запись
+    pub fn current_file(&self) -> Option<&PathBuf> {
+        self.file.as_ref().and(self.files.last())
+    }
+
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    fn write_raw(&mut self, mut data: &[u8]) -> AppResult<()> {
+        if self.pending.is_some() {
+            match self.format.frame_sync(data) {
+                Some(pos) => {
+                    self.write_to_file(&data[..pos])?;
+                    self.rotate()?;
+                    data = &data[pos..];
+                }
+                // До первого кадра писать некуда
+                None if self.file.is_none() => return Ok(()),
+                None => {}
+            }
+        }
+        self.write_to_file(data)
+    }
+
+    fn write_ogg(&mut self, data: &[u8]) -> AppResult<()> {
+        let events = match self.ogg.as_mut() {
+            Some(ogg) => ogg.push(data),
+            None => return Ok(()),
+        };
+
+        for event in events {
+            let mut page = match event {
+                OggEvent::Headers => {
+                    self.ogg_needs_headers = true;
+                    continue;
+                }
+                OggEvent::Page(page) => page,
+            };
+
+            let splittable = self.ogg.as_ref().is_some_and(|o| o.splittable());
+            if self.pending.is_some() && !page.is_continued() && (splittable || self.file.is_none())
+            {
+                self.rotate()?;
+            }
+            if self.file.is_none() {
+                continue;
+            }
+
+            if !splittable {
+                self.write_to_file(&page.encode())?;
+                continue;
+            }
+
+            if self.ogg_needs_headers {
+                let headers = match self.ogg.as_ref() {
+                    Some(ogg) => ogg.header_pages(&self.tags, &mut self.ogg_sequence),
+                    None => Vec::new(),
+                };
+                self.write_to_file(&headers)?;
+                self.ogg_needs_headers = false;
+            }
+
+            page.sequence = self.ogg_sequence;
+            self.ogg_sequence += 1;
+            self.write_to_file(&page.encode())?;
+        }
+
+        Ok(())
+    }
+
+    /// Закрыть текущий файл и открыть файл для ожидающего трека
+    fn rotate(&mut self) -> AppResult<()> {
+        let tags = match self.pending.take() {
+            Some(tags) => tags,
+            None => return Ok(()),
+        };
+        self.finish()?;
+
+        let name = format!(
+            "{:03} {}.{}",
+            self.files.len() + 1,
+            tags.file_stem(),
+            self.format.extension()
+        );
+        let path = self.directory.join(name);
+        let mut file = BufWriter::new(File::create(&path)?);
+
+        if !self.format.is_ogg() {
+            let tag = id3v2(&tags);
+            file.write_all(&tag)?;
+            self.bytes_written += tag.len() as u64;
+        }
+
+        eprintln!("🎙️ Запись: {}", path.display());
+        self.file = Some(file);
+        self.files.push(path);
+        self.tags = tags;
+        self.ogg_sequence = 0;
+        self.ogg_needs_headers = self.ogg.as_ref().is_some_and(|o| o.headers_ready());
+        Ok(())
+    }
+
+    fn write_to_file(&mut self, data: &[u8]) -> AppResult<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        if let Some(file) = self.file.as_mut() {
+            file.write_all(data)?;
+            self.bytes_written += data.len() as u64;
+        }
+        Ok(())
+    }
+}
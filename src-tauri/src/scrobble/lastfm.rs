@@ -0,0 +1,93 @@
+use super::{LastFmConfig, Listen};
+use crate::error::{AppError, AppResult};
+
+/// API Last.fm
+pub const DEFAULT_BASE_URL: &str = "https://ws.audioscrobbler.com/2.0/";
+
+/// Отправить "сейчас играет"
+pub async fn update_now_playing(
+    client: &reqwest::Client,
+    config: &LastFmConfig,
+    listen: &Listen,
+) -> AppResult<()> {
+    call(
+        client,
+        config,
+        "track.updateNowPlaying",
+        track_params(listen),
+    )
+    .await
+}
+
+/// Отправить прослушивание
+pub async fn scrobble(
+    client: &reqwest::Client,
+    config: &LastFmConfig,
+    listen: &Listen,
+) -> AppResult<()> {
+    let mut params = track_params(listen);
+    params.push(("timestamp", listen.listened_at.to_string()));
+    call(client, config, "track.scrobble", params).await
+}
+
+fn track_params(listen: &Listen) -> Vec<(&'static str, String)> {
+    let mut params = vec![
+        ("artist", listen.artist.clone()),
+        ("track", listen.track.clone()),
+    ];
+    if let Some(album) = &listen.album {
+        params.push(("album", album.clone()));
+    }
+    params
+}
+
+/// Подпись запроса: md5 от отсортированных пар "имяЗначение" и секрета
+pub fn sign(params: &[(&str, String)], secret: &str) -> String {
+    let mut sorted: Vec<_> = params.iter().collect();
+    sorted.sort_by_key(|(name, _)| *name);
+
+    let mut raw = String::new();
+    for (name, value) in sorted {
+        raw.push_str(name);
+        raw.push_str(value);
+    }
+    raw.push_str(secret);
+    format!("{:x}", md5::compute(raw.as_bytes()))
+}
+
+async fn call(
+    client: &reqwest::Client,
+    config: &LastFmConfig,
+    method: &str,
+    mut params: Vec<(&str, String)>,
+) -> AppResult<()> {
+    params.push(("method", method.to_string()));
+    params.push(("api_key", config.api_key.clone()));
+    params.push(("sk", config.session_key.clone()));
+    let signature = sign(&params, &config.api_secret);
+    params.push(("api_sig", signature));
+    params.push(("format", "json".to_string()));
+
+    let response = client.post(&config.base_url).form(&params).send().await?;
+    let status = response.status();
+    // Ошибки Last.fm приходят в теле: {"error": код, "message": "..."}
+    let json: serde_json::Value = response.json().await.unwrap_or_default();
+
+    if let Some(code) = json.get("error").and_then(|c| c.as_u64()) {
+        let message = json
+            .get("message")
+            .and_then(|m| m.as_str())
+            .unwrap_or("неизвестная ошибка");
+        return Err(match code {
+            // Сервис временно недоступен
+            11 | 16 => AppError::network(format!("Last.fm недоступен: {}", message)),
+            29 => AppError::rate_limited(None),
+            _ => AppError::invalid_input(format!("Last.fm: {} ({})", message, code)),
+        });
+    }
+    if !status.is_success() {
+        return Err(AppError::http(status.as_u16()));
+    }
+
+    Ok(())
+}
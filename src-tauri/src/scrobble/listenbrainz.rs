@@ -0,0 +1,64 @@
+use super::{Listen, ListenBrainzConfig};
+use crate::error::{AppError, AppResult};
+use serde_json::json;
+
+/// API ListenBrainz
+pub const DEFAULT_BASE_URL: &str = "https://api.listenbrainz.org";
+
+/// Отправить "сейчас играет"
+pub async fn update_now_playing(
+    client: &reqwest::Client,
+    config: &ListenBrainzConfig,
+    listen: &Listen,
+) -> AppResult<()> {
+    let payload = json!({ "track_metadata": track_metadata(listen) });
+    submit(client, config, "playing_now", payload).await
+}
+
+/// Отправить прослушивание
+pub async fn scrobble(
+    client: &reqwest::Client,
+    config: &ListenBrainzConfig,
+    listen: &Listen,
+) -> AppResult<()> {
+    let payload = json!({
+        "listened_at": listen.listened_at,
+        "track_metadata": track_metadata(listen),
+    });
+    submit(client, config, "single", payload).await
+}
+
+fn track_metadata(listen: &Listen) -> serde_json::Value {
+    let mut metadata = json!({
+        "artist_name": listen.artist,
+        "track_name": listen.track,
+        "additional_info": {
+            "submission_client": "Интырнэт Радиво",
+            "submission_client_version": env!("CARGO_PKG_VERSION"),
+        },
+    });
+    if let Some(album) = &listen.album {
+        metadata["release_name"] = json!(album);
+    }
+    metadata
+}
+
+async fn submit(
+    client: &reqwest::Client,
+    config: &ListenBrainzConfig,
+    listen_type: &str,
+    payload: serde_json::Value,
+) -> AppResult<()> {
+    let url = format!("{}/1/submit-listens", config.base_url.trim_end_matches('/'));
+    let body = json!({ "listen_type": listen_type, "payload": [payload] });
+
+    let response = client
+        .post(&url)
+        .header("Authorization", format!("Token {}", config.token))
+        .json(&body)
+        .send()
+        .await?;
+    AppError::check_status(response).map_err(|e| e.context("ListenBrainz"))?;
+
+    Ok(())
+}
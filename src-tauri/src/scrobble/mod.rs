@@ -0,0 +1,352 @@
+mod lastfm;
+mod listenbrainz;
+mod queue;
+
+#[cfg(test)]
+mod tests;
+
+use crate::error::{AppError, AppResult};
+use crate::models::RadioStation;
+use queue::QueuedListen;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tokio::sync::{Mutex, RwLock};
+
+/// Сколько раз пытаться отправить прослушивание из очереди
+const MAX_ATTEMPTS: u32 = 20;
+
+/// Прослушивания старше двух недель сервисы не принимают
+const MAX_LISTEN_AGE_SECS: i64 = 14 * 24 * 60 * 60;
+
+/// Если метаданные давно не обновлялись, трек считается доигранным к `last_seen + SEEN_GRACE`
+const SEEN_GRACE_MS: i64 = 60_000;
+
+fn default_min_listen_seconds() -> u64 {
+    30
+}
+
+fn default_lastfm_base_url() -> String {
+    lastfm::DEFAULT_BASE_URL.to_string()
+}
+
+fn default_listenbrainz_base_url() -> String {
+    listenbrainz::DEFAULT_BASE_URL.to_string()
+}
+
+/// Настройки скробблинга
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrobbleSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Минимальное время прослушивания трека, чтобы он засчитался
+    #[serde(default = "default_min_listen_seconds")]
+    pub min_listen_seconds: u64,
+    #[serde(default)]
+    pub lastfm: Option<LastFmConfig>,
+    #[serde(default)]
+    pub listenbrainz: Option<ListenBrainzConfig>,
+}
+
+impl Default for ScrobbleSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_listen_seconds: default_min_listen_seconds(),
+            lastfm: None,
+            listenbrainz: None,
+        }
+    }
+}
+
+/// Доступ к Last.fm (ключ сессии получается заранее через auth.getMobileSession)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LastFmConfig {
+    pub api_key: String,
+    pub api_secret: String,
+    pub session_key: String,
+    #[serde(default = "default_lastfm_base_url")]
+    pub base_url: String,
+}
+
+/// Доступ к ListenBrainz (токен пользователя)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListenBrainzConfig {
+    pub token: String,
+    #[serde(default = "default_listenbrainz_base_url")]
+    pub base_url: String,
+}
+
+/// Сервис, куда отправляются прослушивания
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScrobbleTarget {
+    LastFm,
+    ListenBrainz,
+}
+
+/// Прослушанный трек
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Listen {
+    pub artist: String,
+    pub track: String,
+    /// Название станции
+    pub album: Option<String>,
+    /// Начало прослушивания (Unix, секунды)
+    pub listened_at: i64,
+}
+
+/// Состояние скробблера (для фронтенда)
+#[derive(Debug, Clone, Serialize)]
+pub struct ScrobbleStatus {
+    pub enabled: bool,
+    pub now_playing: Option<Listen>,
+    /// Прослушиваний в очереди на повторную отправку
+    pub queued: usize,
+}
+
+/// Трек, который сейчас играет
+struct PlayingTrack {
+    station_id: String,
+    listen: Listen,
+    started_at_ms: i64,
+    stop_at_ms: Option<i64>,
+    last_seen_ms: i64,
+}
+
+/// Скробблер: "сейчас играет" при смене трека, прослушивание — когда трек доигран
+pub struct Scrobbler {
+    client: reqwest::Client,
+    settings: RwLock<ScrobbleSettings>,
+    current: Mutex<Option<PlayingTrack>>,
+    queue: Mutex<Vec<QueuedListen>>,
+    /// Файл очереди (рядом с настройками)
+    queue_path: Option<PathBuf>,
+}
+
+impl Scrobbler {
+    pub fn new(settings: ScrobbleSettings, queue_path: Option<PathBuf>) -> Self {
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("intyrnet-radivo/", env!("CARGO_PKG_VERSION")))
+            .timeout(std::time::Duration::from_secs(15))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            client,
+            settings: RwLock::new(settings),
+            current: Mutex::new(None),
+            queue: Mutex::new(queue::load(queue_path.as_deref())),
+            queue_path,
+        }
+    }
+
+    pub async fn set_settings(&self, settings: ScrobbleSettings) {
+        *self.settings.write().await = settings;
+    }
+
+    pub async fn status(&self) -> ScrobbleStatus {
+        ScrobbleStatus {
+            enabled: self.settings.read().await.enabled,
+            now_playing: self.current.lock().await.as_ref().map(|t| t.listen.clone()),
+            queued: self.queue.lock().await.len(),
+        }
+    }
+
+    /// Метаданные станции получены. Тот же трек продлевает прослушивание до этого момента.
+    pub async fn on_metadata(&self, station: &RadioStation) {
+        self.handle_metadata(station, now_ms()).await;
+    }
+
+    /// Воспроизведение остановлено: текущий трек засчитывается до этого момента
+    pub async fn playback_stopped(&self) {
+        let finished = self.current.lock().await.take();
+        if let Some(track) = finished {
+            self.finish_track(track, now_ms()).await;
+        }
+    }
+
+    async fn handle_metadata(&self, station: &RadioStation, now_ms: i64) {
+        if !self.settings.read().await.enabled {
+            return;
+        }
+
+        // Без исполнителя это обычно джингл или реклама — не засчитываем
+        let listen = match (&station.current_artist, &station.current_track) {
+            (Some(artist), Some(track))
+                if !artist.trim().is_empty() && !track.trim().is_empty() =>
+            {
+                Some(Listen {
+                    artist: artist.trim().to_string(),
+                    track: track.trim().to_string(),
+                    album: Some(station.name.clone()),
+                    listened_at: now_ms / 1000,
+                })
+            }
+            _ => None,
+        };
+
+        let finished = {
+            let mut current = self.current.lock().await;
+            if let (Some(track), Some(listen)) = (current.as_mut(), &listen) {
+                if track.station_id == station.id
+                    && track.listen.artist == listen.artist
+                    && track.listen.track == listen.track
+                {
+                    track.last_seen_ms = now_ms;
+                    if station.stop_at_ms.is_some() {
+                        track.stop_at_ms = station.stop_at_ms;
+                    }
+                    return;
+                }
+            }
+
+            let next = listen.as_ref().map(|listen| PlayingTrack {
+                station_id: station.id.clone(),
+                listen: listen.clone(),
+                started_at_ms: now_ms,
+                stop_at_ms: station.stop_at_ms,
+                last_seen_ms: now_ms,
+            });
+            std::mem::replace(&mut *current, next)
+        };
+
+        if let Some(track) = finished {
+            self.finish_track(track, now_ms).await;
+        }
+        if let Some(listen) = &listen {
+            self.send_now_playing(listen).await;
+        }
+    }
+
+    /// Засчитать трек, если он звучал не меньше порога
+    async fn finish_track(&self, track: PlayingTrack, now_ms: i64) {
+        let mut ended_at = now_ms.min(track.last_seen_ms + SEEN_GRACE_MS);
+        if let Some(stop_at) = track.stop_at_ms {
+            ended_at = ended_at.min(stop_at);
+        }
+
+        let min_listen_ms = self.settings.read().await.min_listen_seconds as i64 * 1000;
+        if ended_at - track.started_at_ms < min_listen_ms {
+            return;
+        }
+
+        self.submit_listen(track.listen).await;
+    }
+
+    async fn send_now_playing(&self, listen: &Listen) {
+        let settings = self.settings.read().await.clone();
+        if let Some(config) = &settings.lastfm {
+            if let Err(e) = lastfm::update_now_playing(&self.client, config, listen).await {
+                eprintln!("⚠️ Last.fm now playing: {}", e);
+            }
+        }
+        if let Some(config) = &settings.listenbrainz {
+            if let Err(e) = listenbrainz::update_now_playing(&self.client, config, listen).await {
+                eprintln!("⚠️ ListenBrainz now playing: {}", e);
+            }
+        }
+    }
+
+    /// Поставить прослушивание в очередь каждого настроенного сервиса и отправить очередь
+    async fn submit_listen(&self, listen: Listen) {
+        let targets = {
+            let settings = self.settings.read().await;
+            let mut targets = Vec::new();
+            if settings.lastfm.is_some() {
+                targets.push(ScrobbleTarget::LastFm);
+            }
+            if settings.listenbrainz.is_some() {
+                targets.push(ScrobbleTarget::ListenBrainz);
+            }
+            targets
+        };
+        if targets.is_empty() {
+            return;
+        }
+
+        {
+            let mut queue = self.queue.lock().await;
+            for target in targets {
+                queue.push(QueuedListen {
+                    target,
+                    listen: listen.clone(),
+                    attempts: 0,
+                });
+            }
+        }
+        self.flush_queue().await;
+    }
+
+    /// Отправить очередь по порядку. Возвращает число оставшихся прослушиваний.
+    /// Запросы идут без блокировки очереди: прослушивания, добавленные за это время,
+    /// встают после неотправленных.
+    pub async fn flush_queue(&self) -> usize {
+        let settings = self.settings.read().await.clone();
+        let pending = std::mem::take(&mut *self.queue.lock().await);
+        let now_secs = now_ms() / 1000;
+
+        let mut remaining = Vec::new();
+        // Сервис, на котором уже была ошибка: остальное для него — в следующий раз
+        let mut failed_targets = Vec::new();
+
+        for mut item in pending {
+            if now_secs - item.listen.listened_at > MAX_LISTEN_AGE_SECS {
+                continue;
+            }
+            if failed_targets.contains(&item.target) {
+                remaining.push(item);
+                continue;
+            }
+
+            let result = match item.target {
+                ScrobbleTarget::LastFm => match &settings.lastfm {
+                    Some(config) => lastfm::scrobble(&self.client, config, &item.listen).await,
+                    None => Err(AppError::invalid_input("Last.fm не настроен")),
+                },
+                ScrobbleTarget::ListenBrainz => match &settings.listenbrainz {
+                    Some(config) => {
+                        listenbrainz::scrobble(&self.client, config, &item.listen).await
+                    }
+                    None => Err(AppError::invalid_input("ListenBrainz не настроен")),
+                },
+            };
+
+            if let Err(e) = result {
+                eprintln!(
+                    "⚠️ Скробблинг {:?} не удался ({}), повторим позже",
+                    item.target, e
+                );
+                failed_targets.push(item.target);
+                item.attempts += 1;
+                if item.attempts < MAX_ATTEMPTS {
+                    remaining.push(item);
+                }
+            }
+        }
+
+        let mut queue = self.queue.lock().await;
+        remaining.append(&mut queue);
+        *queue = remaining;
+        if let Err(e) = queue::save(self.queue_path.as_deref(), &queue) {
+            eprintln!("⚠️ Не удалось сохранить очередь скробблинга: {}", e);
+        }
+        queue.len()
+    }
+}
+
+fn now_ms() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+/// Проверить, что хотя бы один сервис настроен
+pub fn validate(settings: &ScrobbleSettings) -> AppResult<()> {
+    if settings.enabled && settings.lastfm.is_none() && settings.listenbrainz.is_none() {
+        return Err(AppError::invalid_input(
+            "Для скробблинга настройте Last.fm или ListenBrainz",
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use super::{Listen, ScrobbleTarget};
+use crate::error::AppResult;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Прослушивание, ожидающее отправки
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedListen {
+    pub target: ScrobbleTarget,
+    pub listen: Listen,
+    /// Неудачных попыток отправки
+    #[serde(default)]
+    pub attempts: u32,
+}
+
+/// Загрузить очередь из файла (повреждённый или отсутствующий файл — пустая очередь)
+pub fn load(path: Option<&Path>) -> Vec<QueuedListen> {
+    path.and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(path: Option<&Path>, queue: &[QueuedListen]) -> AppResult<()> {
+    if let Some(path) = path {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(queue)?)?;
+    }
+    Ok(())
+}
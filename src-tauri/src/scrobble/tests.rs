@@ -0,0 +1,355 @@
+use super::*;
+use crate::mock_server::{MockResponse, MockServer};
+use crate::sources::Ru101Source;
+
+const SUBMIT_PATH: &str = "/1/submit-listens";
+const LASTFM_PATH: &str = "/2.0/";
+
+fn station(artist: &str, track: &str, stop_at_ms: Option<i64>) -> RadioStation {
+    let mut station = Ru101Source::new_station(200, "Relax FM", "");
+    station.current_artist = Some(artist.to_string());
+    station.current_track = Some(track.to_string());
+    station.stop_at_ms = stop_at_ms;
+    station
+}
+
+fn listenbrainz_settings(server: &MockServer) -> ScrobbleSettings {
+    ScrobbleSettings {
+        enabled: true,
+        listenbrainz: Some(ListenBrainzConfig {
+            token: "lb-token".to_string(),
+            base_url: server.url().to_string(),
+        }),
+        ..Default::default()
+    }
+}
+
+fn listen_types(server: &MockServer) -> Vec<(String, String)> {
+    server
+        .requests(SUBMIT_PATH)
+        .iter()
+        .map(|r| {
+            let body: serde_json::Value = serde_json::from_str(&r.body).unwrap();
+            (
+                body["listen_type"].as_str().unwrap().to_string(),
+                body["payload"][0]["track_metadata"]["track_name"]
+                    .as_str()
+                    .unwrap()
+                    .to_string(),
+            )
+        })
+        .collect()
+}
+
+fn temp_queue_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "intyrnet-radivo-test-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir.join("scrobble_queue.json")
+}
+
+#[tokio::test]
+async fn listen_is_submitted_when_next_track_starts() {
+    let server = MockServer::start().await;
+    server.route(SUBMIT_PATH, MockResponse::json(r#"{"status":"ok"}"#));
+    let scrobbler = Scrobbler::new(listenbrainz_settings(&server), None);
+    let t0 = now_ms();
+
+    scrobbler
+        .handle_metadata(&station("Кино", "Кукушка", None), t0)
+        .await;
+    scrobbler
+        .handle_metadata(&station("Кино", "Кукушка", None), t0 + 20_000)
+        .await;
+    scrobbler
+        .handle_metadata(&station("Ария", "Беспечный ангел", None), t0 + 40_000)
+        .await;
+
+    assert_eq!(
+        listen_types(&server),
+        [
+            ("playing_now".to_string(), "Кукушка".to_string()),
+            ("single".to_string(), "Кукушка".to_string()),
+            ("playing_now".to_string(), "Беспечный ангел".to_string()),
+        ]
+    );
+
+    let request = &server.requests(SUBMIT_PATH)[1];
+    assert_eq!(request.method, "POST");
+    assert_eq!(
+        request.headers.get("authorization").map(String::as_str),
+        Some("Token lb-token")
+    );
+    let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+    assert_eq!(body["payload"][0]["listened_at"], t0 / 1000);
+    assert_eq!(
+        body["payload"][0]["track_metadata"]["release_name"],
+        "Relax FM"
+    );
+}
+
+#[tokio::test]
+async fn short_listen_is_not_submitted() {
+    let server = MockServer::start().await;
+    server.route(SUBMIT_PATH, MockResponse::json(r#"{"status":"ok"}"#));
+    let scrobbler = Scrobbler::new(listenbrainz_settings(&server), None);
+    let t0 = now_ms();
+
+    scrobbler
+        .handle_metadata(&station("Кино", "Кукушка", None), t0)
+        .await;
+    scrobbler
+        .handle_metadata(&station("Ария", "Беспечный ангел", None), t0 + 10_000)
+        .await;
+
+    let types: Vec<_> = listen_types(&server).into_iter().map(|(t, _)| t).collect();
+    assert_eq!(types, ["playing_now", "playing_now"]);
+}
+
+#[tokio::test]
+async fn stop_at_limits_listen_time() {
+    let server = MockServer::start().await;
+    server.route(SUBMIT_PATH, MockResponse::json(r#"{"status":"ok"}"#));
+    let scrobbler = Scrobbler::new(listenbrainz_settings(&server), None);
+    let t0 = now_ms();
+
+    // Трек закончился через 20 с после начала прослушивания — меньше порога в 30 с
+    scrobbler
+        .handle_metadata(&station("Кино", "Кукушка", Some(t0 + 20_000)), t0)
+        .await;
+    scrobbler
+        .handle_metadata(&station("Ария", "Беспечный ангел", None), t0 + 120_000)
+        .await;
+    // А этот доигран до конца (45 с), хотя следующий трек пришёл позже
+    scrobbler
+        .handle_metadata(
+            &station("Ария", "Беспечный ангел", Some(t0 + 165_000)),
+            t0 + 130_000,
+        )
+        .await;
+    scrobbler
+        .handle_metadata(&station("Сплин", "Выхода нет", None), t0 + 200_000)
+        .await;
+
+    let singles: Vec<_> = listen_types(&server)
+        .into_iter()
+        .filter(|(t, _)| t == "single")
+        .map(|(_, track)| track)
+        .collect();
+    assert_eq!(singles, ["Беспечный ангел"]);
+}
+
+#[tokio::test]
+async fn repeated_metadata_extends_listen_without_stop_time() {
+    let server = MockServer::start().await;
+    server.route(SUBMIT_PATH, MockResponse::json(r#"{"status":"ok"}"#));
+    let settings = ScrobbleSettings {
+        min_listen_seconds: 120,
+        ..listenbrainz_settings(&server)
+    };
+    let scrobbler = Scrobbler::new(settings, None);
+    let t0 = now_ms();
+
+    // AMG не сообщает время окончания: трек звучит, пока опрос его повторяет
+    for offset in [0, 50_000, 100_000, 150_000] {
+        scrobbler
+            .handle_metadata(&station("Кино", "Кукушка", None), t0 + offset)
+            .await;
+    }
+    scrobbler
+        .handle_metadata(&station("Ария", "Беспечный ангел", None), t0 + 200_000)
+        .await;
+    // Без повторов засчитывается не больше минуты после последнего ответа
+    scrobbler
+        .handle_metadata(&station("Сплин", "Выхода нет", None), t0 + 400_000)
+        .await;
+
+    let singles: Vec<_> = listen_types(&server)
+        .into_iter()
+        .filter(|(t, _)| t == "single")
+        .map(|(_, track)| track)
+        .collect();
+    assert_eq!(singles, ["Кукушка"]);
+}
+
+#[tokio::test]
+async fn disabled_scrobbler_sends_nothing() {
+    let server = MockServer::start().await;
+    let mut settings = listenbrainz_settings(&server);
+    settings.enabled = false;
+    let scrobbler = Scrobbler::new(settings, None);
+    let t0 = now_ms();
+
+    scrobbler
+        .handle_metadata(&station("Кино", "Кукушка", None), t0)
+        .await;
+    scrobbler
+        .handle_metadata(&station("Ария", "Беспечный ангел", None), t0 + 60_000)
+        .await;
+
+    assert_eq!(server.hits(SUBMIT_PATH), 0);
+}
+
+#[tokio::test]
+async fn lastfm_requests_are_signed() {
+    let server = MockServer::start().await;
+    server.route(LASTFM_PATH, MockResponse::json(r#"{"scrobbles":{}}"#));
+    let settings = ScrobbleSettings {
+        enabled: true,
+        lastfm: Some(LastFmConfig {
+            api_key: "key".to_string(),
+            api_secret: "secret".to_string(),
+            session_key: "session".to_string(),
+            base_url: format!("{}{}", server.url(), LASTFM_PATH),
+        }),
+        ..Default::default()
+    };
+    let scrobbler = Scrobbler::new(settings, None);
+    let t0 = now_ms();
+
+    scrobbler
+        .handle_metadata(&station("Кино", "Кукушка", None), t0)
+        .await;
+    scrobbler
+        .handle_metadata(&station("Ария", "Беспечный ангел", None), t0 + 60_000)
+        .await;
+
+    let requests = server.requests(LASTFM_PATH);
+    let methods: Vec<_> = requests
+        .iter()
+        .map(|r| {
+            let params: Vec<(String, String)> = url::form_urlencoded::parse(r.body.as_bytes())
+                .into_owned()
+                .collect();
+            let get = |name: &str| {
+                params
+                    .iter()
+                    .find(|(k, _)| k == name)
+                    .map(|(_, v)| v.clone())
+                    .unwrap_or_default()
+            };
+
+            let signed: Vec<(&str, String)> = params
+                .iter()
+                .filter(|(k, _)| k != "api_sig" && k != "format")
+                .map(|(k, v)| (k.as_str(), v.clone()))
+                .collect();
+            assert_eq!(get("api_sig"), lastfm::sign(&signed, "secret"));
+            assert_eq!(get("sk"), "session");
+            assert_eq!(get("format"), "json");
+            if get("method") == "track.scrobble" {
+                assert_eq!(get("timestamp"), (t0 / 1000).to_string());
+                assert_eq!(get("album"), "Relax FM");
+            }
+            get("method")
+        })
+        .collect();
+
+    assert_eq!(
+        methods,
+        [
+            "track.updateNowPlaying",
+            "track.scrobble",
+            "track.updateNowPlaying"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn lastfm_error_body_is_reported() {
+    let server = MockServer::start().await;
+    server.route(
+        LASTFM_PATH,
+        MockResponse::json(r#"{"error":9,"message":"Invalid session key"}"#),
+    );
+    let config = LastFmConfig {
+        api_key: "key".to_string(),
+        api_secret: "secret".to_string(),
+        session_key: "expired".to_string(),
+        base_url: format!("{}{}", server.url(), LASTFM_PATH),
+    };
+    let listen = Listen {
+        artist: "Кино".to_string(),
+        track: "Кукушка".to_string(),
+        album: None,
+        listened_at: now_ms() / 1000,
+    };
+
+    let error = lastfm::scrobble(&reqwest::Client::new(), &config, &listen)
+        .await
+        .unwrap_err();
+
+    assert_eq!(error.code(), "invalid_input");
+    assert!(error.message.contains("Invalid session key"));
+}
+
+#[tokio::test]
+async fn failed_listens_are_queued_and_retried() {
+    let server = MockServer::start().await;
+    server.route(SUBMIT_PATH, MockResponse::status(503));
+    let queue_path = temp_queue_path("queue");
+    let scrobbler = Scrobbler::new(listenbrainz_settings(&server), Some(queue_path.clone()));
+    let t0 = now_ms();
+
+    scrobbler
+        .handle_metadata(&station("Кино", "Кукушка", None), t0)
+        .await;
+    scrobbler
+        .handle_metadata(&station("Ария", "Беспечный ангел", None), t0 + 60_000)
+        .await;
+    assert_eq!(scrobbler.status().await.queued, 1);
+
+    // Очередь переживает перезапуск
+    let restarted = Scrobbler::new(listenbrainz_settings(&server), Some(queue_path.clone()));
+    assert_eq!(restarted.status().await.queued, 1);
+
+    server.route(SUBMIT_PATH, MockResponse::json(r#"{"status":"ok"}"#));
+    assert_eq!(restarted.flush_queue().await, 0);
+
+    let (listen_type, track) = listen_types(&server).pop().unwrap();
+    assert_eq!(listen_type, "single");
+    assert_eq!(track, "Кукушка");
+    assert_eq!(queue::load(Some(&queue_path)).len(), 0);
+
+    let _ = std::fs::remove_dir_all(queue_path.parent().unwrap());
+}
+
+#[tokio::test]
+async fn status_does_not_wait_for_queue_flush() {
+    // Сервер принимает соединение и не отвечает
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let settings = ScrobbleSettings {
+        enabled: true,
+        listenbrainz: Some(ListenBrainzConfig {
+            token: "lb-token".to_string(),
+            base_url: format!("http://{}", listener.local_addr().unwrap()),
+        }),
+        ..Default::default()
+    };
+    let scrobbler = std::sync::Arc::new(Scrobbler::new(settings, None));
+    scrobbler.queue.lock().await.push(QueuedListen {
+        target: ScrobbleTarget::ListenBrainz,
+        listen: Listen {
+            artist: "Кино".to_string(),
+            track: "Кукушка".to_string(),
+            album: None,
+            listened_at: now_ms() / 1000,
+        },
+        attempts: 0,
+    });
+
+    let flushing = tokio::spawn({
+        let scrobbler = scrobbler.clone();
+        async move { scrobbler.flush_queue().await }
+    });
+    let _connection = listener.accept().await.unwrap();
+
+    let status = tokio::time::timeout(std::time::Duration::from_secs(1), scrobbler.status())
+        .await
+        .expect("статус ждёт отправки очереди");
+    assert!(status.enabled);
+    flushing.abort();
+}
@@ -1,3 +1,5 @@
 mod station_service;
+mod track_history;
 
 pub use station_service::StationService;
+pub use track_history::{TrackHistory, TrackHistoryEntry};
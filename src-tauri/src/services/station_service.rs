@@ -1,101 +1,201 @@
-use crate::models::{RadioSource, RadioStation};
-use crate::sources::{AmgSource, RadioSourceTrait, Ru101Source};
-use std::collections::HashMap;
+use crate::error::{AppError, AppResult};
+use crate::hls::{HlsClient, StreamVariants};
+use crate::models::RadioStation;
+use crate::sources::{RadioSourceTrait, SourceInfo, SourceRegistry, StreamCandidate};
+use crate::store::Store;
 use std::sync::Arc;
-use tokio::sync::RwLock;
 
 /// Сервис управления станциями из всех источников
 pub struct StationService {
-    amg_source: AmgSource,
-    ru101_source: Ru101Source,
-    /// Кэш станций по источникам
-    cache: Arc<RwLock<HashMap<RadioSource, Vec<RadioStation>>>>,
+    registry: SourceRegistry,
+    hls: HlsClient,
+    /// Кэш каталогов и пользовательские станции
+    store: Arc<Store>,
 }
 
 impl StationService {
-    pub fn new() -> Self {
+    pub fn new(store: Arc<Store>) -> Self {
+        Self::with_registry(SourceRegistry::with_default_sources(), store)
+    }
+
+    /// Создать сервис с заданным реестром источников
+    pub fn with_registry(registry: SourceRegistry, store: Arc<Store>) -> Self {
         Self {
-            amg_source: AmgSource::new(),
-            ru101_source: Ru101Source::new(),
-            cache: Arc::new(RwLock::new(HashMap::new())),
+            registry,
+            hls: HlsClient::new(),
+            store,
         }
     }
 
+    /// Список зарегистрированных источников
+    pub fn list_sources(&self) -> Vec<SourceInfo> {
+        self.registry.list()
+    }
+
+    /// Зарегистрирован ли источник
+    pub fn has_source(&self, source_id: &str) -> bool {
+        self.registry.contains(source_id)
+    }
+
+    /// Отдаёт ли источник метаданные текущего трека
+    pub fn supports_metadata(&self, source_id: &str) -> bool {
+        self.registry
+            .get(source_id)
+            .is_some_and(|source| source.capabilities().metadata)
+    }
+
+    /// Читает ли источник метаданные из потока (ICY) — каждый запрос открывает соединение
+    pub fn reads_stream_metadata(&self, source_id: &str) -> bool {
+        self.registry
+            .get(source_id)
+            .is_some_and(|source| source.capabilities().stream_metadata)
+    }
+
+    /// Найти источник по ID
+    fn source(&self, source_id: &str) -> AppResult<&dyn RadioSourceTrait> {
+        self.registry
+            .get(source_id)
+            .ok_or_else(|| AppError::not_found(format!("Неизвестный источник: {}", source_id)))
+    }
+
     /// Загрузить станции из указанного источника
-    pub async fn fetch_stations(
-        &self,
-        source: RadioSource,
-    ) -> Result<Vec<RadioStation>, Box<dyn std::error::Error + Send + Sync>> {
-        let stations = match source {
-            RadioSource::Amg => self.amg_source.fetch_stations().await?,
-            RadioSource::Ru101 => self.ru101_source.fetch_stations().await?,
-        };
+    pub async fn fetch_stations(&self, source_id: &str) -> AppResult<Vec<RadioStation>> {
+        let source = self.source(source_id)?;
+
+        // Станции не-каталожных источников (пользовательские) живут только в кэше
+        if !source.capabilities().catalog {
+            return Ok(self
+                .get_cached_stations(source_id)
+                .await
+                .unwrap_or_default());
+        }
+
+        let stations = source
+            .fetch_stations()
+            .await
+            .map_err(|e| e.with_source(source_id))?;
 
         // Кэшируем результат
-        {
-            let mut cache = self.cache.write().await;
-            cache.insert(source, stations.clone());
+        if let Err(e) = self.store.replace_source_stations(source_id, &stations) {
+            eprintln!("⚠️ Не удалось сохранить каталог {}: {}", source_id, e);
         }
 
         Ok(stations)
     }
 
     /// Получить станции из кэша
-    #[allow(dead_code)]
-    pub async fn get_cached_stations(&self, source: &RadioSource) -> Option<Vec<RadioStation>> {
-        let cache = self.cache.read().await;
-        cache.get(source).cloned()
+    pub async fn get_cached_stations(&self, source_id: &str) -> Option<Vec<RadioStation>> {
+        self.store.source_stations(source_id).unwrap_or_else(|e| {
+            eprintln!("⚠️ Не удалось прочитать кэш {}: {}", source_id, e);
+            None
+        })
     }
 
-    /// Загрузить кэш из сохранённых данных
-    pub async fn load_cache(&self, source: RadioSource, stations: Vec<RadioStation>) {
-        let mut cache = self.cache.write().await;
-        cache.insert(source, stations);
+    /// Добавить или заменить станцию в кэше её источника
+    pub async fn upsert_cached_station(&self, station: RadioStation) -> AppResult<()> {
+        self.store.upsert_station(&station)
+    }
+
+    /// Удалить станцию из кэша. Возвращает false, если её не было.
+    pub async fn remove_cached_station(&self, station_id: &str) -> AppResult<bool> {
+        self.store.remove_station(station_id)
     }
 
     /// Получить все станции из всех источников
-    #[allow(dead_code)]
     pub async fn get_all_stations(&self) -> Vec<RadioStation> {
-        let cache = self.cache.read().await;
-        cache.values().flatten().cloned().collect()
+        self.store.all_stations().unwrap_or_else(|e| {
+            eprintln!("⚠️ Не удалось прочитать кэш станций: {}", e);
+            Vec::new()
+        })
+    }
+
+    /// Поиск по началу названия во всех источниках (или в одном)
+    pub async fn search_stations(
+        &self,
+        query: &str,
+        source_id: Option<&str>,
+        limit: usize,
+    ) -> AppResult<Vec<RadioStation>> {
+        self.store.search_stations(query, source_id, limit)
     }
 
     /// Получить URL потока для станции (обновляет токен если нужно)
-    pub async fn get_stream_url(
+    pub async fn get_stream_url(&self, station: &RadioStation) -> AppResult<String> {
+        let source_id = &station.source;
+        self.source(source_id)?
+            .get_stream_url(station)
+            .await
+            .map_err(|e| e.with_source(source_id))
+    }
+
+    /// Получить все URL потока станции, от лучшего к худшему
+    pub async fn get_stream_candidates(
         &self,
         station: &RadioStation,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        match station.source {
-            RadioSource::Amg => self.amg_source.get_stream_url(station).await,
-            RadioSource::Ru101 => self.ru101_source.get_stream_url(station).await,
+    ) -> AppResult<Vec<StreamCandidate>> {
+        let source_id = &station.source;
+        self.source(source_id)?
+            .get_stream_candidates(station)
+            .await
+            .map_err(|e| e.with_source(source_id))
+    }
+
+    /// Сообщить о нерабочем URL потока (следующий get_stream_url его пропустит)
+    pub async fn report_stream_failure(&self, station: &RadioStation, url: &str) {
+        if let Ok(source) = self.source(&station.source) {
+            source.report_stream_failure(station, url).await;
         }
     }
 
-    /// Обновить метаданные станции
-    pub async fn update_metadata(
-        &self,
-        station: &mut RadioStation,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        match station.source {
-            RadioSource::Amg => self.amg_source.update_metadata(station).await,
-            RadioSource::Ru101 => self.ru101_source.update_metadata(station).await,
+    /// Получить варианты HLS потока и обычный поток для fallback
+    pub async fn get_stream_variants(&self, station: &RadioStation) -> StreamVariants {
+        let fallback_url = self
+            .get_stream_url(station)
+            .await
+            .ok()
+            .filter(|url| !url.is_empty() && Some(url) != station.stream_hls.as_ref());
+
+        let hls_url = station.stream_hls.clone().filter(|url| !url.is_empty());
+        let (variants, hls_error) = match hls_url {
+            Some(ref url) => match self.hls.fetch_variants(url).await {
+                Ok(variants) => (variants, None),
+                Err(e) => {
+                    eprintln!("⚠️ HLS {} недоступен: {}", url, e);
+                    (Vec::new(), Some(e.to_string()))
+                }
+            },
+            None => (Vec::new(), None),
+        };
+
+        StreamVariants {
+            hls_url,
+            variants,
+            hls_error,
+            fallback_url,
         }
     }
 
+    /// Обновить метаданные станции
+    pub async fn update_metadata(&self, station: &mut RadioStation) -> AppResult<()> {
+        let source_id = station.source.clone();
+        self.source(&source_id)?
+            .update_metadata(station)
+            .await
+            .map_err(|e| e.with_source(&source_id))
+    }
+
     /// Найти станцию по ID
     pub async fn find_station_by_id(&self, id: &str) -> Option<RadioStation> {
-        let cache = self.cache.read().await;
-        for stations in cache.values() {
-            if let Some(station) = stations.iter().find(|s| s.id == id) {
-                return Some(station.clone());
-            }
-        }
-        None
+        self.store.find_station(id).unwrap_or_else(|e| {
+            eprintln!("⚠️ Не удалось найти станцию {}: {}", id, e);
+            None
+        })
     }
 }
 
 impl Default for StationService {
+    /// Сервис с кэшем только в памяти
     fn default() -> Self {
-        Self::new()
+        Self::new(Arc::new(Store::open_or_memory(None)))
     }
 }
@@ -0,0 +1,146 @@
+use crate::error::AppResult;
+use crate::models::RadioStation;
+use crate::store::Store;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Сколько записей хранить (старые удаляются)
+const MAX_ENTRIES: usize = 5000;
+
+/// Запись истории: трек, прозвучавший на станции
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackHistoryEntry {
+    pub station_id: String,
+    pub station_name: String,
+    pub artist: Option<String>,
+    pub title: Option<String>,
+    pub cover_url: Option<String>,
+    /// Когда трек впервые увиден (Unix, мс)
+    pub played_at_ms: i64,
+    /// Время окончания трека, если источник его сообщает (101.ru)
+    pub stop_at_ms: Option<i64>,
+}
+
+/// История треков по станциям (хранится в базе `Store`)
+pub struct TrackHistory {
+    store: Arc<Store>,
+    /// Запись идёт из нескольких опросов метаданных — сравнение с последней записью и вставка
+    /// должны быть атомарными
+    lock: Mutex<()>,
+}
+
+impl TrackHistory {
+    pub fn new(store: Arc<Store>) -> Self {
+        Self {
+            store,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Записать текущий трек станции, если он отличается от последнего записанного.
+    /// Возвращает новую запись.
+    pub async fn record(&self, station: &RadioStation) -> AppResult<Option<TrackHistoryEntry>> {
+        if station.current_track.is_none() && station.current_artist.is_none() {
+            return Ok(None);
+        }
+
+        let _guard = self.lock.lock().await;
+        if let Some((id, last)) = self.store.last_history_entry(&station.id)? {
+            if last.title == station.current_track && last.artist == station.current_artist {
+                // Тот же трек: уточняем время окончания, если оно появилось
+                if station.stop_at_ms.is_some() && last.stop_at_ms != station.stop_at_ms {
+                    self.store.set_history_stop(id, station.stop_at_ms)?;
+                }
+                return Ok(None);
+            }
+        }
+
+        let entry = TrackHistoryEntry {
+            station_id: station.id.clone(),
+            station_name: station.name.clone(),
+            artist: station.current_artist.clone(),
+            title: station.current_track.clone(),
+            cover_url: station.artwork_url.clone().or_else(|| station.logo.clone()),
+            played_at_ms: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as i64,
+            stop_at_ms: station.stop_at_ms,
+        };
+        self.store
+            .append_history(std::slice::from_ref(&entry), MAX_ENTRIES)?;
+
+        Ok(Some(entry))
+    }
+
+    /// Записи от новых к старым с фильтром по станции и времени
+    pub async fn get(
+        &self,
+        station_id: Option<&str>,
+        since_ms: Option<i64>,
+        limit: usize,
+    ) -> Vec<TrackHistoryEntry> {
+        self.store
+            .history(station_id, since_ms, limit)
+            .unwrap_or_else(|e| {
+                eprintln!("⚠️ Не удалось прочитать историю: {}", e);
+                Vec::new()
+            })
+    }
+
+    /// Очистить историю (всю или одной станции). Возвращает число удалённых записей.
+    pub async fn clear(&self, station_id: Option<&str>) -> AppResult<usize> {
+        let _guard = self.lock.lock().await;
+        self.store.clear_history(station_id)
+    }
+
+    /// Добавить записи из резервной копии (`replace` — вместо текущей истории).
+    /// Записи, которые уже есть (та же станция и время), пропускаются. Итоговую историю
+    /// и лимит записей получает `write` — он пишет её вместе с остальной копией.
+    /// Возвращает число добавленных.
+    pub async fn import(
+        &self,
+        entries: Vec<TrackHistoryEntry>,
+        replace: bool,
+        write: impl FnOnce(&[TrackHistoryEntry], usize) -> AppResult<()>,
+    ) -> AppResult<usize> {
+        let _guard = self.lock.lock().await;
+        let mut merged = if replace {
+            Vec::new()
+        } else {
+            self.store.history(None, None, MAX_ENTRIES)?
+        };
+        let mut known: HashSet<(String, i64)> = merged
+            .iter()
+            .map(|e| (e.station_id.clone(), e.played_at_ms))
+            .collect();
+        let before = merged.len();
+        merged.extend(
+            entries
+                .into_iter()
+                .filter(|e| known.insert((e.station_id.clone(), e.played_at_ms))),
+        );
+        let added = merged.len() - before;
+
+        merged.sort_by_key(|e| e.played_at_ms);
+        write(&merged, MAX_ENTRIES)?;
+        Ok(added)
+    }
+
+    /// Перенести историю из старого файла `track_history.json` (файл переименовывается)
+    pub fn migrate_file(&self, path: &Path) -> AppResult<usize> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        let entries: Vec<TrackHistoryEntry> = serde_json::from_str(&content)?;
+        self.store.append_history(&entries, MAX_ENTRIES)?;
+        fs::rename(path, path.with_extension("json.migrated"))?;
+        Ok(entries.len())
+    }
+}
@@ -0,0 +1,76 @@
+//! Миграции `settings.json` между версиями схемы.
+//!
+//! Каждая миграция получает JSON версии N и приводит его к версии N + 1.
+//! Новую миграцию добавляем в конец `MIGRATIONS` — `SCHEMA_VERSION` вырастет сам.
+
+use crate::error::{AppError, AppResult};
+use serde_json::{Map, Value};
+
+type Migration = fn(&mut Map<String, Value>) -> AppResult<()>;
+
+/// Миграции по порядку: `MIGRATIONS[n]` переводит версию n в n + 1
+const MIGRATIONS: &[Migration] = &[v0_basic_fields, v1_stations_moved_to_store];
+
+/// Текущая версия схемы настроек
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Поле с версией схемы
+pub const VERSION_FIELD: &str = "schema_version";
+
+/// Привести настройки к текущей версии. Возвращает исходную версию.
+pub fn migrate(value: &mut Value) -> AppResult<u32> {
+    let settings = value
+        .as_object_mut()
+        .ok_or_else(|| AppError::parse("Настройки должны быть объектом JSON"))?;
+    let version = match settings.get(VERSION_FIELD) {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| {
+                AppError::parse(format!("Неверная версия схемы настроек: {}", version))
+            })?,
+    };
+    if version > SCHEMA_VERSION {
+        return Err(AppError::unsupported(format!(
+            "Настройки сохранены более новой версией приложения (схема {}, поддерживается до {})",
+            version, SCHEMA_VERSION
+        )));
+    }
+
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(settings).map_err(|e| {
+            e.context(&format!(
+                "Миграция настроек {} → {} не удалась",
+                from,
+                from + 1
+            ))
+        })?;
+    }
+    settings.insert(VERSION_FIELD.to_string(), Value::from(SCHEMA_VERSION));
+    Ok(version)
+}
+
+/// v0 → v1: громкость и режим стриминга обязательны — заполняем и исправляем,
+/// чтобы одно неверное значение не сбрасывало все настройки
+fn v0_basic_fields(settings: &mut Map<String, Value>) -> AppResult<()> {
+    let volume = settings
+        .get("volume")
+        .and_then(Value::as_f64)
+        .map(|v| v.round().clamp(0.0, 100.0) as u8)
+        .unwrap_or(50);
+    settings.insert("volume".to_string(), Value::from(volume));
+
+    if !settings.get("streaming_mode").is_some_and(Value::is_string) {
+        settings.insert("streaming_mode".to_string(), Value::from("mp3"));
+    }
+    Ok(())
+}
+
+/// v1 → v2: избранное, кэш каталогов и пользовательские станции переехали в базу.
+/// Миграция пустая и только поднимает версию схемы: сам перенос делает
+/// `migrate_legacy_settings` в `lib.rs` при открытии базы и удаляет ключи из файла
+/// только после успешного переноса, иначе неудавшийся перенос потерял бы избранное.
+fn v1_stations_moved_to_store(_settings: &mut Map<String, Value>) -> AppResult<()> {
+    Ok(())
+}
@@ -0,0 +1,207 @@
+//! Файл настроек `settings.json`: версия схемы, миграции и резервные копии.
+//!
+//! Запись атомарная: временный файл, fsync и переименование поверх старого. Перед заменой
+//! предыдущая версия уходит в `settings.json.1.bak` (до `BACKUP_COUNT` копий).
+//! Если основной файл повреждён, настройки восстанавливаются из самой свежей рабочей копии;
+//! если рабочих копий нет — приложение стартует с настройками по умолчанию. Повреждённый
+//! файл в обоих случаях сохраняется рядом, и следующее сохранение его не уничтожит.
+//! Файл более новой версии приложения не считается повреждённым: он не загружается
+//! и не перезаписывается до перезапуска.
+
+mod migrations;
+#[cfg(test)]
+mod tests;
+
+pub use migrations::SCHEMA_VERSION;
+
+use crate::error::{AppError, AppResult, ErrorKind};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Сколько предыдущих версий файла хранить (`.1.bak` — самая свежая)
+pub const BACKUP_COUNT: usize = 3;
+
+/// Настройки сохраняются из многих команд одновременно — записи идут по очереди
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Файлы, сохранённые более новой версией приложения: их не перезаписываем
+static NEWER_FILES: Mutex<BTreeSet<PathBuf>> = Mutex::new(BTreeSet::new());
+
+/// Загруженные настройки
+#[derive(Debug)]
+pub struct Loaded<T> {
+    pub settings: T,
+    /// Основной файл был повреждён: что случилось и из какой копии восстановлено
+    pub recovered: Option<String>,
+}
+
+/// Загрузить настройки, при необходимости выполнив миграции.
+/// `Ok(None)` — файла нет. При ошибке исходный файл уже сохранён в резервную копию,
+/// кроме файла более новой версии (`ErrorKind::Unsupported`): он не тронут, а `save` в него
+/// больше не пишет.
+pub fn load<T: DeserializeOwned + Serialize>(path: &Path) -> AppResult<Option<Loaded<T>>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(AppError::from(e).context("Ошибка чтения настроек")),
+    };
+
+    let error = match parse::<T>(&content) {
+        Ok((settings, from_version)) => {
+            if from_version < SCHEMA_VERSION {
+                let backup = copy_to(path, &format!("v{}", from_version))?;
+                // Исходный файл уже в копии `v{N}` — ротация не нужна
+                write_atomic(path, &serde_json::to_string_pretty(&settings)?, false)?;
+                eprintln!(
+                    "📦 Настройки обновлены до схемы {} (копия: {})",
+                    SCHEMA_VERSION,
+                    backup.display()
+                );
+            }
+            return Ok(Some(Loaded {
+                settings,
+                recovered: None,
+            }));
+        }
+        Err(e) if e.kind == ErrorKind::Unsupported => {
+            NEWER_FILES
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(path.to_path_buf());
+            return Err(e.context("Настройки не загружены, изменения не будут сохранены"));
+        }
+        Err(e) => e,
+    };
+
+    let broken = copy_to(
+        path,
+        &format!("broken-{}", chrono::Local::now().format("%Y%m%d-%H%M%S")),
+    )?;
+    for backup in (1..=BACKUP_COUNT).map(|n| backup_path(path, n)) {
+        let Ok(content) = fs::read_to_string(&backup) else {
+            continue;
+        };
+        let Ok((settings, _)) = parse::<T>(&content) else {
+            continue;
+        };
+        // Повреждённый файл не должен вытеснить рабочие копии
+        write_atomic(path, &serde_json::to_string_pretty(&settings)?, false)?;
+        let report = format!(
+            "Файл настроек повреждён ({}). Настройки восстановлены из копии {}, повреждённый файл сохранён в {}",
+            error.message,
+            backup.display(),
+            broken.display()
+        );
+        eprintln!("⚠️ {}", report);
+        return Ok(Some(Loaded {
+            settings,
+            recovered: Some(report),
+        }));
+    }
+
+    Err(error.context(&format!(
+        "Настройки не загружены, исходный файл сохранён в {}",
+        broken.display()
+    )))
+}
+
+/// Разобрать и мигрировать настройки. Возвращает их и исходную версию схемы.
+fn parse<T: DeserializeOwned>(content: &str) -> AppResult<(T, u32)> {
+    let mut value: Value = serde_json::from_str(content)?;
+    let from_version = migrations::migrate(&mut value)?;
+    let settings = serde_json::from_value(value)?;
+    Ok((settings, from_version))
+}
+
+/// Записать настройки (атомарно, с ротацией резервных копий)
+pub fn save<T: Serialize>(path: &Path, settings: &T) -> AppResult<()> {
+    if NEWER_FILES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains(path)
+    {
+        return Err(AppError::unsupported(
+            "Настройки сохранены более новой версией приложения и не перезаписываются",
+        ));
+    }
+    write_atomic(path, &serde_json::to_string_pretty(settings)?, true)
+}
+
+fn write_atomic(path: &Path, content: &str, rotate: bool) -> AppResult<()> {
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let current = fs::read_to_string(path).ok();
+    if current.as_deref() == Some(content) {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let tmp = sibling(path, ".tmp");
+    {
+        let mut file = File::create(&tmp)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+    }
+    if rotate {
+        if let Some(current) = current {
+            rotate_backups(path, &current);
+        }
+    }
+    fs::rename(&tmp, path)?;
+    sync_parent_dir(path);
+    Ok(())
+}
+
+/// Сдвинуть копии (`.1` → `.2` …) и сохранить текущий файл как `.1`, если он рабочий
+fn rotate_backups(path: &Path, current: &str) {
+    if serde_json::from_str::<Value>(current).is_err() {
+        return;
+    }
+    for n in (1..BACKUP_COUNT).rev() {
+        let from = backup_path(path, n);
+        if from.exists() {
+            let _ = fs::rename(&from, backup_path(path, n + 1));
+        }
+    }
+    if let Err(e) = fs::write(backup_path(path, 1), current) {
+        eprintln!("⚠️ Не удалось сохранить копию настроек: {}", e);
+    }
+}
+
+/// Каталог тоже синхронизируем, чтобы переименование пережило сбой питания
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) {
+    if let Some(dir) = path.parent().and_then(|p| File::open(p).ok()) {
+        let _ = dir.sync_all();
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) {}
+
+/// Путь резервной копии номер `n` (1 — самая свежая)
+pub fn backup_path(path: &Path, n: usize) -> PathBuf {
+    sibling(path, &format!(".{}.bak", n))
+}
+
+/// `settings.json` → `settings.json<suffix>`
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Скопировать файл настроек в `settings.json.<suffix>.bak`
+fn copy_to(path: &Path, suffix: &str) -> AppResult<PathBuf> {
+    let backup = sibling(path, &format!(".{}.bak", suffix));
+    fs::copy(path, &backup)
+        .map_err(|e| AppError::from(e).context("Не удалось сохранить копию настроек"))?;
+    Ok(backup)
+}
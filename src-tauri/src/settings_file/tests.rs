@@ -0,0 +1,234 @@
+use super::*;
+use serde::Deserialize;
+use serde_json::json;
+
+/// Упрощённые настройки: обязательные поля как в `AppSettings`
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Settings {
+    #[serde(default)]
+    schema_version: u32,
+    volume: u8,
+    streaming_mode: String,
+    #[serde(default)]
+    show_notifications: Option<bool>,
+}
+
+/// Временная папка, удаляется после теста
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("radivo-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+
+    fn write(&self, content: &str) -> PathBuf {
+        let path = self.0.join("settings.json");
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn backups(&self) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(&self.0)
+            .unwrap()
+            .filter_map(|e| e.ok()?.file_name().into_string().ok())
+            .filter(|name| name.ends_with(".bak"))
+            .collect();
+        names.sort();
+        names
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn unversioned_settings_are_migrated_to_current_schema() {
+    let mut value = json!({
+        "volume": 150,
+        "favorite_stations": [{ "id": "amg_ruwave" }],
+        "cached_stations": {},
+        "show_notifications": false
+    });
+    assert_eq!(migrations::migrate(&mut value).unwrap(), 0);
+    // Станции остаются до переноса в базу
+    assert_eq!(
+        value,
+        json!({
+            "schema_version": SCHEMA_VERSION,
+            "volume": 100,
+            "streaming_mode": "mp3",
+            "favorite_stations": [{ "id": "amg_ruwave" }],
+            "cached_stations": {},
+            "show_notifications": false
+        })
+    );
+
+    // Повторная миграция ничего не меняет
+    let before = value.clone();
+    assert_eq!(migrations::migrate(&mut value).unwrap(), SCHEMA_VERSION);
+    assert_eq!(value, before);
+}
+
+#[test]
+fn unknown_or_invalid_versions_are_errors() {
+    let mut future = json!({ "schema_version": SCHEMA_VERSION + 1, "volume": 50 });
+    let error = migrations::migrate(&mut future).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Unsupported);
+    assert!(error.message.contains("более новой версией"), "{}", error);
+
+    let mut invalid = json!({ "schema_version": "два" });
+    assert!(migrations::migrate(&mut invalid).is_err());
+
+    let mut not_object = json!([1, 2, 3]);
+    assert!(migrations::migrate(&mut not_object).is_err());
+}
+
+#[test]
+fn load_migrates_file_and_keeps_original_copy() {
+    let dir = TempDir::new("settings-migrate");
+    let original = r#"{ "volume": 30, "streaming_mode": "hls", "custom_stations": [] }"#;
+    let path = dir.write(original);
+
+    let settings: Settings = load(&path).unwrap().unwrap().settings;
+    assert_eq!(settings.schema_version, SCHEMA_VERSION);
+    assert_eq!(settings.volume, 30);
+    assert_eq!(settings.streaming_mode, "hls");
+
+    assert_eq!(dir.backups(), vec!["settings.json.v0.bak".to_string()]);
+    assert_eq!(
+        fs::read_to_string(dir.0.join("settings.json.v0.bak")).unwrap(),
+        original
+    );
+    let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(saved["schema_version"], json!(SCHEMA_VERSION));
+    assert!(saved.get("custom_stations").is_none());
+
+    // Файл уже в текущей схеме — копия больше не создаётся
+    let loaded = load::<Settings>(&path).unwrap().unwrap();
+    assert!(loaded.recovered.is_none());
+    assert_eq!(dir.backups().len(), 1);
+}
+
+#[test]
+fn broken_file_is_reported_and_preserved() {
+    let dir = TempDir::new("settings-broken");
+    // Текущая схема без миграций: неверная громкость не исправляется
+    let broken = format!(
+        r#"{{ "schema_version": {}, "volume": "громко" }}"#,
+        SCHEMA_VERSION
+    );
+    let path = dir.write(&broken);
+
+    let error = load::<Settings>(&path).unwrap_err();
+    assert!(
+        error.message.contains("исходный файл сохранён"),
+        "{}",
+        error
+    );
+    // Файл не тронут, рядом — копия
+    assert_eq!(fs::read_to_string(&path).unwrap(), broken);
+    let backups = dir.backups();
+    assert_eq!(backups.len(), 1);
+    assert!(backups[0].starts_with("settings.json.broken-"));
+
+    let dir = TempDir::new("settings-invalid-json");
+    let path = dir.write("{ не json");
+    assert!(load::<Settings>(&path).is_err());
+    assert_eq!(dir.backups().len(), 1);
+}
+
+#[test]
+fn newer_file_is_not_treated_as_broken_or_overwritten() {
+    let dir = TempDir::new("settings-newer");
+    let newer = format!(
+        r#"{{ "schema_version": {}, "volume": 30, "streaming_mode": "mp3" }}"#,
+        SCHEMA_VERSION + 1
+    );
+    let path = dir.write(&newer);
+
+    for _ in 0..2 {
+        let error = load::<Settings>(&path).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Unsupported);
+    }
+    assert!(dir.backups().is_empty());
+
+    let error = save(&path, &settings(70)).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Unsupported);
+    assert_eq!(fs::read_to_string(&path).unwrap(), newer);
+    assert!(dir.backups().is_empty());
+}
+
+#[test]
+fn missing_file_is_not_an_error() {
+    let dir = TempDir::new("settings-missing");
+    assert!(load::<Settings>(&dir.0.join("settings.json"))
+        .unwrap()
+        .is_none());
+    assert!(dir.backups().is_empty());
+}
+
+fn settings(volume: u8) -> Settings {
+    Settings {
+        schema_version: SCHEMA_VERSION,
+        volume,
+        streaming_mode: "mp3".to_string(),
+        show_notifications: None,
+    }
+}
+
+#[test]
+fn save_rotates_previous_versions() {
+    let dir = TempDir::new("settings-rotate");
+    let path = dir.0.join("settings.json");
+
+    for volume in 1..=5 {
+        save(&path, &settings(volume)).unwrap();
+    }
+    // Те же настройки — файл и копии не трогаем
+    save(&path, &settings(5)).unwrap();
+
+    let volume_in = |path: &Path| {
+        serde_json::from_str::<Settings>(&fs::read_to_string(path).unwrap())
+            .unwrap()
+            .volume
+    };
+    assert_eq!(volume_in(&path), 5);
+    assert_eq!(dir.backups().len(), BACKUP_COUNT);
+    for n in 1..=BACKUP_COUNT {
+        assert_eq!(volume_in(&backup_path(&path, n)), 5 - n as u8);
+    }
+    assert!(!dir.0.join("settings.json.tmp").exists());
+}
+
+#[test]
+fn broken_file_is_recovered_from_newest_valid_backup() {
+    let dir = TempDir::new("settings-recover");
+    let path = dir.0.join("settings.json");
+    for volume in [10, 20, 30] {
+        save(&path, &settings(volume)).unwrap();
+    }
+    // Самая свежая копия тоже повреждена — берём следующую
+    fs::write(backup_path(&path, 1), "{ \"volume\": ").unwrap();
+    fs::write(&path, "").unwrap();
+
+    let loaded = load::<Settings>(&path).unwrap().unwrap();
+    assert_eq!(loaded.settings.volume, 10);
+    let report = loaded.recovered.unwrap();
+    assert!(report.contains("settings.json.2.bak"), "{}", report);
+    assert!(report.contains("settings.json.broken-"), "{}", report);
+
+    // Основной файл восстановлен, копии не сдвинулись
+    let restored = load::<Settings>(&path).unwrap().unwrap();
+    assert!(restored.recovered.is_none());
+    assert_eq!(restored.settings.volume, 10);
+    assert_eq!(
+        fs::read_to_string(backup_path(&path, 1)).unwrap(),
+        "{ \"volume\": "
+    );
+}
@@ -1,21 +1,53 @@
-use super::RadioSourceTrait;
+use super::{RadioSourceTrait, SourceCapabilities};
+use crate::error::{AppError, AppResult};
 use crate::models::RadioStation;
 use async_trait::async_trait;
 
+/// Сайт AMG (WordPress REST API)
+const DEFAULT_SITE_URL: &str = "https://ru.volna.top";
+
+/// Сервер метаданных AMG
+const DEFAULT_INFO_URL: &str = "https://info.volna.top";
+
 /// Источник AMG Radio (volna.top)
 pub struct AmgSource {
     client: reqwest::Client,
+    /// Базовый URL сайта (без завершающего слэша)
+    site_url: String,
+    /// Базовый URL сервера метаданных (без завершающего слэша)
+    info_url: String,
 }
 
 impl AmgSource {
+    /// ID источника (префикс ID станций)
+    pub const ID: &'static str = "amg";
+
     pub fn new() -> Self {
+        Self::with_base_urls(DEFAULT_SITE_URL, DEFAULT_INFO_URL)
+    }
+
+    /// Создать источник с другими адресами сайта и метаданных (например, локальный тестовый сервер)
+    pub fn with_base_urls(site_url: &str, info_url: &str) -> Self {
         let client = reqwest::Client::builder()
             .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
             .timeout(std::time::Duration::from_secs(10))
             .build()
             .unwrap_or_default();
 
-        Self { client }
+        Self {
+            client,
+            site_url: site_url.trim_end_matches('/').to_string(),
+            info_url: info_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Станция AMG по slug
+    pub fn new_station(slug: &str, name: &str, stream_url: &str) -> RadioStation {
+        let mut station = RadioStation::new(Self::ID, slug, name, stream_url);
+        station.station_slug = Some(slug.to_string());
+        station.meta_server = Some("https://info.volna.top/radio.json".to_string());
+        station.meta_key = Some(slug.to_string());
+        station
     }
 
     /// Нормализация ключа метаданных
@@ -115,7 +147,7 @@ impl AmgSource {
         known_stations_data
             .into_iter()
             .map(|(slug, name, stream_url, logo)| {
-                let mut station = RadioStation::new_amg(slug, name, stream_url);
+                let mut station = Self::new_station(slug, name, stream_url);
                 station.logo = Some(logo.to_string());
                 station.artwork_url = Some(logo.to_string());
                 station
@@ -132,13 +164,30 @@ impl Default for AmgSource {
 
 #[async_trait]
 impl RadioSourceTrait for AmgSource {
-    async fn fetch_stations(
-        &self,
-    ) -> Result<Vec<RadioStation>, Box<dyn std::error::Error + Send + Sync>> {
+    fn id(&self) -> &'static str {
+        Self::ID
+    }
+
+    fn display_name(&self) -> &'static str {
+        "AMG Radio"
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            catalog: true,
+            metadata: true,
+            resolves_stream_url: false,
+            hls: true,
+            track_end_time: false,
+            stream_metadata: false,
+        }
+    }
+
+    async fn fetch_stations(&self) -> AppResult<Vec<RadioStation>> {
         // Пробуем WordPress REST API
-        let api_url = "https://ru.volna.top/wp-json/wp/v2/station?per_page=100";
+        let api_url = format!("{}/wp-json/wp/v2/station?per_page=100", self.site_url);
 
-        let response = match self.client.get(api_url).send().await {
+        let response = match self.client.get(&api_url).send().await {
             Ok(resp) if resp.status().is_success() => resp.json::<serde_json::Value>().await?,
             _ => {
                 eprintln!("⚠️ WordPress API недоступен, используем известные станции");
@@ -196,7 +245,7 @@ impl RadioSourceTrait for AmgSource {
                         station_json.get("featured_media").and_then(|m| m.as_u64())
                     {
                         let media_url =
-                            format!("https://ru.volna.top/wp-json/wp/v2/media/{}", media_id);
+                            format!("{}/wp-json/wp/v2/media/{}", self.site_url, media_id);
                         if let Ok(media_resp) = self.client.get(&media_url).send().await {
                             if let Ok(media_json) = media_resp.json::<serde_json::Value>().await {
                                 media_json
@@ -213,7 +262,7 @@ impl RadioSourceTrait for AmgSource {
                         None
                     };
 
-                    let mut station = RadioStation::new_amg(&station_slug, &name, &stream_url);
+                    let mut station = Self::new_station(&station_slug, &name, &stream_url);
                     station.stream_hls = stream_hls;
                     station.logo = logo.clone();
                     station.artwork_url = logo;
@@ -235,34 +284,28 @@ impl RadioSourceTrait for AmgSource {
         Ok(stations)
     }
 
-    async fn get_stream_url(
-        &self,
-        station: &RadioStation,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    async fn get_stream_url(&self, station: &RadioStation) -> AppResult<String> {
         // AMG потоки не требуют токенов, возвращаем как есть
         Ok(station.stream_url.clone())
     }
 
-    async fn update_metadata(
-        &self,
-        station: &mut RadioStation,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    async fn update_metadata(&self, station: &mut RadioStation) -> AppResult<()> {
         let slug = station
             .meta_key
             .as_ref()
             .or(station.station_slug.as_ref())
-            .ok_or("Нет slug для метаданных")?;
+            .ok_or_else(|| AppError::missing_field("station_slug", "Нет slug для метаданных"))?;
 
         let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)?
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
             .as_millis();
 
-        let url = format!("https://info.volna.top/tag/{}.json?l={}", slug, timestamp);
+        let url = format!("{}/tag/{}.json?l={}", self.info_url, slug, timestamp);
 
         let response = self.client.get(&url).send().await?;
-        if !response.status().is_success() {
-            return Err(format!("Ошибка получения метаданных: {}", response.status()).into());
-        }
+        let response = AppError::check_status(response)
+            .map_err(|e| e.context("Ошибка получения метаданных"))?;
 
         let json: serde_json::Value = response.json().await?;
 
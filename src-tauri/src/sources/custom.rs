@@ -0,0 +1,236 @@
+use super::{RadioSourceTrait, SourceCapabilities};
+use crate::error::{AppError, AppResult};
+use crate::icy::{self, IcyClient};
+use crate::models::RadioStation;
+use async_trait::async_trait;
+use reqwest::header::HeaderMap;
+
+/// Результат проверки URL потока
+#[derive(Debug, Clone, Default)]
+pub struct StreamProbe {
+    /// Content-Type ответа
+    pub content_type: Option<String>,
+    /// icy-name
+    pub name: Option<String>,
+    /// icy-genre
+    pub genre: Option<String>,
+    /// icy-br, кбит/с
+    pub bitrate: Option<u32>,
+    /// icy-url
+    pub homepage: Option<String>,
+}
+
+impl StreamProbe {
+    /// Поток в формате HLS (m3u8)
+    pub fn is_hls(&self) -> bool {
+        self.content_type
+            .as_deref()
+            .map(|ct| ct.contains("mpegurl"))
+            .unwrap_or(false)
+    }
+
+    /// Кодек по Content-Type
+    pub fn codec(&self) -> Option<String> {
+        let ct = self.content_type.as_deref()?;
+        let codec = match ct {
+            "audio/mpeg" | "audio/mp3" => "MP3",
+            "audio/aac" | "audio/aacp" | "audio/x-aac" => "AAC",
+            "audio/ogg" | "application/ogg" => "OGG",
+            "audio/opus" => "OPUS",
+            "audio/flac" | "audio/x-flac" => "FLAC",
+            _ => return None,
+        };
+        Some(codec.to_string())
+    }
+
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                // Названия станций часто приходят в UTF-8 или Windows-1251, а не в ASCII
+                .map(|v| icy::decode_text(v.as_bytes()).trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+
+        Self {
+            content_type: header("content-type").map(|ct| {
+                ct.split(';')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_lowercase()
+            }),
+            name: header("icy-name"),
+            genre: header("icy-genre"),
+            // icy-br бывает вида "128" или "128,128"
+            bitrate: header("icy-br")
+                .and_then(|br| br.split(',').next().and_then(|b| b.trim().parse().ok())),
+            homepage: header("icy-url"),
+        }
+    }
+
+    /// Похоже ли содержимое на аудиопоток или HLS
+    fn looks_playable(&self) -> bool {
+        match self.content_type.as_deref() {
+            None => true,
+            Some(ct) => {
+                ct.starts_with("audio/")
+                    || ct == "application/ogg"
+                    || ct == "application/octet-stream"
+                    || ct.contains("mpegurl")
+            }
+        }
+    }
+}
+
+/// Источник пользовательских станций (добавленных по URL потока)
+pub struct CustomSource {
+    client: reqwest::Client,
+    icy: IcyClient,
+}
+
+impl CustomSource {
+    /// ID источника (префикс ID станций)
+    pub const ID: &'static str = "custom";
+
+    pub fn new() -> Self {
+        let client = reqwest::Client::builder()
+            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            client,
+            icy: IcyClient::new(),
+        }
+    }
+
+    /// Пользовательская станция (ID строится по URL потока)
+    pub fn new_station(stream_url: &str, name: &str) -> RadioStation {
+        let internal_id = format!("{:016x}", fnv1a_hash(stream_url.trim()));
+        RadioStation::new(Self::ID, &internal_id, name, stream_url)
+    }
+
+    /// Проверить URL потока: HEAD, при неудаче GET (только заголовки, тело не читается)
+    pub async fn probe(&self, stream_url: &str) -> AppResult<StreamProbe> {
+        let parsed = url::Url::parse(stream_url)
+            .map_err(|e| AppError::invalid_input(format!("Неверный URL: {}", e)))?;
+        if parsed.scheme() != "http" && parsed.scheme() != "https" {
+            return Err(AppError::invalid_input(format!(
+                "Неподдерживаемая схема URL: {}",
+                parsed.scheme()
+            )));
+        }
+
+        // Многие Icecast/Shoutcast серверы не поддерживают HEAD
+        let headers = match self.client.head(stream_url).send().await {
+            Ok(resp) if resp.status().is_success() => resp.headers().clone(),
+            _ => {
+                let resp = self
+                    .client
+                    .get(stream_url)
+                    .header("Icy-MetaData", "1")
+                    .send()
+                    .await?;
+                AppError::check_status(resp)?.headers().clone()
+            }
+        };
+
+        let probe = StreamProbe::from_headers(&headers);
+        if !probe.looks_playable() {
+            return Err(AppError::invalid_input(format!(
+                "URL не похож на аудиопоток ({})",
+                probe.content_type.as_deref().unwrap_or_default()
+            )));
+        }
+
+        Ok(probe)
+    }
+
+    /// Создать пользовательскую станцию по URL и результату проверки
+    pub fn build_station(
+        stream_url: &str,
+        name: Option<String>,
+        logo: Option<String>,
+        probe: &StreamProbe,
+    ) -> RadioStation {
+        let name = name
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty())
+            .or_else(|| probe.name.clone())
+            .or_else(|| {
+                url::Url::parse(stream_url)
+                    .ok()
+                    .and_then(|u| u.host_str().map(String::from))
+            })
+            .unwrap_or_else(|| stream_url.to_string());
+
+        let mut station = Self::new_station(stream_url, &name);
+        if probe.is_hls() {
+            station.stream_hls = Some(stream_url.to_string());
+        }
+        station.logo = logo.filter(|l| !l.trim().is_empty());
+        station.homepage = probe.homepage.clone();
+        station.codec = probe.codec();
+        station.bitrate = probe.bitrate;
+        if let Some(ref genre) = probe.genre {
+            station.tags = genre
+                .split([',', ';', '/'])
+                .map(|t| t.trim())
+                .filter(|t| !t.is_empty())
+                .map(String::from)
+                .collect();
+            station.category = station.tags.first().cloned();
+        }
+        station
+    }
+}
+
+impl Default for CustomSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl RadioSourceTrait for CustomSource {
+    fn id(&self) -> &'static str {
+        Self::ID
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Мои станции"
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            catalog: false,
+            metadata: true,
+            resolves_stream_url: false,
+            hls: true,
+            track_end_time: false,
+            stream_metadata: true,
+        }
+    }
+
+    async fn fetch_stations(&self) -> AppResult<Vec<RadioStation>> {
+        // Станции хранятся в настройках, загружать нечего
+        Ok(Vec::new())
+    }
+
+    async fn get_stream_url(&self, station: &RadioStation) -> AppResult<String> {
+        Ok(station.stream_url.clone())
+    }
+
+    async fn update_metadata(&self, station: &mut RadioStation) -> AppResult<()> {
+        self.icy.update_station(station).await
+    }
+}
+
+/// FNV-1a хэш (стабилен между запусками и версиями компилятора)
+fn fnv1a_hash(value: &str) -> u64 {
+    value.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
@@ -1,29 +1,98 @@
 mod amg;
+mod custom;
+mod radio_browser;
+mod registry;
 mod ru101;
 
+#[cfg(test)]
+mod tests;
+
 pub use amg::AmgSource;
+pub use custom::{CustomSource, StreamProbe};
+pub use radio_browser::RadioBrowserSource;
+pub use registry::SourceRegistry;
 pub use ru101::Ru101Source;
 
+use crate::error::AppResult;
 use crate::models::RadioStation;
 use async_trait::async_trait;
+use serde::Serialize;
+
+/// Возможности источника (для фронтенда)
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SourceCapabilities {
+    /// Станции загружаются из внешнего каталога (иначе их добавляет пользователь)
+    pub catalog: bool,
+    /// Источник отдаёт метаданные текущего трека
+    pub metadata: bool,
+    /// URL потока нужно получать заново перед воспроизведением (токены, серверы)
+    pub resolves_stream_url: bool,
+    /// Станции могут иметь HLS поток
+    pub hls: bool,
+    /// Метаданные содержат время окончания трека (stop_at_ms)
+    pub track_end_time: bool,
+    /// Метаданные читаются из самого потока (ICY): каждый запрос открывает соединение с ним
+    pub stream_metadata: bool,
+}
+
+/// Описание зарегистрированного источника
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceInfo {
+    pub id: String,
+    pub name: String,
+    pub capabilities: SourceCapabilities,
+}
+
+/// Кандидат URL потока (сервер вещания)
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamCandidate {
+    pub url: String,
+    /// Качество (битрейт), если источник его сообщает
+    pub quality: Option<u32>,
+    /// Формат потока (mp3, aac, ...)
+    pub format: Option<String>,
+    /// Протокол (http/https)
+    pub protocol: Option<String>,
+    /// URL недавно отмечен как нерабочий и пропускается до окончания паузы
+    pub cooling_down: bool,
+}
 
 /// Trait для источников радиостанций
 #[async_trait]
 pub trait RadioSourceTrait: Send + Sync {
+    /// ID источника (ключ в реестре, значение `RadioStation::source` и префикс ID станций)
+    fn id(&self) -> &'static str;
+
+    /// Отображаемое название источника
+    fn display_name(&self) -> &'static str;
+
+    /// Возможности источника
+    fn capabilities(&self) -> SourceCapabilities;
+
     /// Получить список всех станций
-    async fn fetch_stations(
-        &self,
-    ) -> Result<Vec<RadioStation>, Box<dyn std::error::Error + Send + Sync>>;
+    async fn fetch_stations(&self) -> AppResult<Vec<RadioStation>>;
 
     /// Получить URL потока для станции (с обновлением токена если нужно)
-    async fn get_stream_url(
+    async fn get_stream_url(&self, station: &RadioStation) -> AppResult<String>;
+
+    /// Все известные URL потока станции, от лучшего к худшему
+    async fn get_stream_candidates(
         &self,
         station: &RadioStation,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>>;
+    ) -> AppResult<Vec<StreamCandidate>> {
+        let url = self.get_stream_url(station).await?;
+        Ok(vec![StreamCandidate {
+            url,
+            quality: station.bitrate,
+            format: station.codec.clone(),
+            protocol: None,
+            cooling_down: false,
+        }])
+    }
+
+    /// Сообщить, что URL потока не воспроизводится (источник может временно его пропускать)
+    async fn report_stream_failure(&self, _station: &RadioStation, _url: &str) {}
 
     /// Обновить метаданные станции (текущий трек и т.д.)
-    async fn update_metadata(
-        &self,
-        station: &mut RadioStation,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    async fn update_metadata(&self, station: &mut RadioStation) -> AppResult<()>;
 }
@@ -0,0 +1,225 @@
+use super::{RadioSourceTrait, SourceCapabilities};
+use crate::error::{AppError, AppResult};
+use crate::icy::IcyClient;
+use crate::models::RadioStation;
+use async_trait::async_trait;
+
+/// Публичный сервер Radio-Browser по умолчанию
+const DEFAULT_BASE_URL: &str = "https://de1.api.radio-browser.info";
+
+/// Сколько станций загружать из каталога
+const DEFAULT_LIMIT: u32 = 500;
+
+/// Станция из ответа `/json/stations/search`
+#[derive(Debug, serde::Deserialize)]
+struct RadioBrowserStation {
+    stationuuid: String,
+    name: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    url_resolved: String,
+    #[serde(default)]
+    homepage: String,
+    #[serde(default)]
+    favicon: String,
+    /// Теги через запятую
+    #[serde(default)]
+    tags: String,
+    #[serde(default)]
+    country: String,
+    #[serde(default)]
+    codec: String,
+    #[serde(default)]
+    bitrate: u32,
+    /// 1 если поток в формате HLS
+    #[serde(default)]
+    hls: u8,
+}
+
+/// Ответ `/json/url/{uuid}` (учёт прослушивания)
+#[derive(Debug, serde::Deserialize)]
+struct RadioBrowserClickResponse {
+    ok: bool,
+    #[serde(default)]
+    url: String,
+}
+
+/// Источник Radio-Browser (radio-browser.info)
+pub struct RadioBrowserSource {
+    client: reqwest::Client,
+    icy: IcyClient,
+    /// Базовый URL API (без завершающего слэша)
+    base_url: String,
+    /// Максимальное количество станций при загрузке каталога
+    limit: u32,
+}
+
+impl RadioBrowserSource {
+    /// ID источника (префикс ID станций)
+    pub const ID: &'static str = "radiobrowser";
+
+    pub fn new() -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL)
+    }
+
+    /// Создать источник с другим сервером API (зеркало или локальный тестовый сервер)
+    pub fn with_base_url(base_url: &str) -> Self {
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("intyrnet-radivo/", env!("CARGO_PKG_VERSION")))
+            .timeout(std::time::Duration::from_secs(15))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            client,
+            icy: IcyClient::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            limit: DEFAULT_LIMIT,
+        }
+    }
+
+    /// Задать максимальное количество станций
+    #[allow(dead_code)]
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Преобразовать станцию API в RadioStation
+    fn map_station(raw: RadioBrowserStation) -> Option<RadioStation> {
+        let stream_url = if raw.url_resolved.is_empty() {
+            raw.url
+        } else {
+            raw.url_resolved
+        };
+        let name = raw.name.trim();
+        if stream_url.is_empty() || name.is_empty() {
+            return None;
+        }
+
+        let non_empty = |s: String| {
+            let s = s.trim().to_string();
+            if s.is_empty() {
+                None
+            } else {
+                Some(s)
+            }
+        };
+
+        let mut station = RadioStation::new(Self::ID, &raw.stationuuid, name, &stream_url);
+        if raw.hls == 1 {
+            station.stream_hls = Some(stream_url);
+        }
+        station.logo = non_empty(raw.favicon);
+        station.homepage = non_empty(raw.homepage);
+        station.country = non_empty(raw.country);
+        station.codec = non_empty(raw.codec);
+        station.bitrate = if raw.bitrate > 0 {
+            Some(raw.bitrate)
+        } else {
+            None
+        };
+        station.tags = raw
+            .tags
+            .split(',')
+            .map(|t| t.trim())
+            .filter(|t| !t.is_empty())
+            .map(String::from)
+            .collect();
+        station.category = station.tags.first().cloned();
+
+        Some(station)
+    }
+}
+
+impl Default for RadioBrowserSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl RadioSourceTrait for RadioBrowserSource {
+    fn id(&self) -> &'static str {
+        Self::ID
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Radio-Browser"
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            catalog: true,
+            metadata: true,
+            resolves_stream_url: true,
+            hls: true,
+            track_end_time: false,
+            stream_metadata: true,
+        }
+    }
+
+    async fn fetch_stations(&self) -> AppResult<Vec<RadioStation>> {
+        let url = format!("{}/json/stations/search", self.base_url);
+
+        let response = self
+            .client
+            .get(&url)
+            .query(&[
+                ("order", "clickcount".to_string()),
+                ("reverse", "true".to_string()),
+                ("hidebroken", "true".to_string()),
+                ("limit", self.limit.to_string()),
+            ])
+            .send()
+            .await?;
+
+        let response =
+            AppError::check_status(response).map_err(|e| e.context("Ошибка API Radio-Browser"))?;
+
+        let raw_stations: Vec<RadioBrowserStation> = response.json().await?;
+
+        let mut seen_ids = std::collections::HashSet::new();
+        let mut stations: Vec<RadioStation> = raw_stations
+            .into_iter()
+            .filter_map(Self::map_station)
+            .filter(|s| seen_ids.insert(s.id.clone()))
+            .collect();
+
+        stations.sort_by(|a, b| a.name.cmp(&b.name));
+        eprintln!("📻 Radio-Browser: загружено {} станций", stations.len());
+
+        Ok(stations)
+    }
+
+    async fn get_stream_url(&self, station: &RadioStation) -> AppResult<String> {
+        let uuid = station.internal_id();
+
+        // Запрос засчитывает прослушивание в каталоге и возвращает актуальный URL
+        let url = format!("{}/json/url/{}", self.base_url, uuid);
+        let click = match self.client.get(&url).send().await {
+            Ok(resp) if resp.status().is_success() => {
+                resp.json::<RadioBrowserClickResponse>().await.ok()
+            }
+            _ => None,
+        };
+
+        match click {
+            Some(click) if click.ok && !click.url.is_empty() => Ok(click.url),
+            _ if !station.stream_url.is_empty() => {
+                eprintln!("⚠️ Radio-Browser: не удалось учесть прослушивание {}", uuid);
+                Ok(station.stream_url.clone())
+            }
+            _ => Err(AppError::missing_field(
+                "stream_url",
+                "Нет URL потока для станции Radio-Browser",
+            )),
+        }
+    }
+
+    async fn update_metadata(&self, station: &mut RadioStation) -> AppResult<()> {
+        // Radio-Browser не отдаёт текущий трек — читаем его из самого потока
+        self.icy.update_station(station).await
+    }
+}
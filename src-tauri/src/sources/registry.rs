@@ -0,0 +1,73 @@
+use super::{
+    AmgSource, CustomSource, RadioBrowserSource, RadioSourceTrait, Ru101Source, SourceInfo,
+};
+use std::collections::HashMap;
+
+/// Реестр источников радиостанций (ключ: ID источника)
+pub struct SourceRegistry {
+    sources: HashMap<&'static str, Box<dyn RadioSourceTrait>>,
+    /// Порядок регистрации (для стабильного списка источников)
+    order: Vec<&'static str>,
+}
+
+impl SourceRegistry {
+    /// Пустой реестр
+    pub fn new() -> Self {
+        Self {
+            sources: HashMap::new(),
+            order: Vec::new(),
+        }
+    }
+
+    /// Реестр со всеми встроенными источниками.
+    /// Новый источник достаточно зарегистрировать здесь.
+    pub fn with_default_sources() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(AmgSource::new()));
+        registry.register(Box::new(Ru101Source::new()));
+        registry.register(Box::new(RadioBrowserSource::new()));
+        registry.register(Box::new(CustomSource::new()));
+        registry
+    }
+
+    /// Зарегистрировать источник (заменяет источник с тем же ID)
+    pub fn register(&mut self, source: Box<dyn RadioSourceTrait>) {
+        let id = source.id();
+        if self.sources.insert(id, source).is_none() {
+            self.order.push(id);
+        }
+    }
+
+    /// Получить источник по ID
+    pub fn get(&self, id: &str) -> Option<&dyn RadioSourceTrait> {
+        self.sources.get(id).map(|s| s.as_ref())
+    }
+
+    /// Зарегистрирован ли источник
+    pub fn contains(&self, id: &str) -> bool {
+        self.sources.contains_key(id)
+    }
+
+    fn describe(source: &dyn RadioSourceTrait) -> SourceInfo {
+        SourceInfo {
+            id: source.id().to_string(),
+            name: source.display_name().to_string(),
+            capabilities: source.capabilities(),
+        }
+    }
+
+    /// Список источников в порядке регистрации
+    pub fn list(&self) -> Vec<SourceInfo> {
+        self.order
+            .iter()
+            .filter_map(|id| self.sources.get(id))
+            .map(|source| Self::describe(source.as_ref()))
+            .collect()
+    }
+}
+
+impl Default for SourceRegistry {
+    fn default() -> Self {
+        Self::with_default_sources()
+    }
+}
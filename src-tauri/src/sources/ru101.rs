@@ -1,10 +1,19 @@
-use super::RadioSourceTrait;
+use super::{RadioSourceTrait, SourceCapabilities, StreamCandidate};
+use crate::error::{AppError, AppResult};
 use crate::models::RadioStation;
 use async_trait::async_trait;
 use scraper::{Html, Selector};
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
+/// Сколько пропускать сервер после сообщения о сбое
+const STREAM_FAILURE_COOLDOWN: Duration = Duration::from_secs(5 * 60);
+
+/// Сайт 101.ru
+const DEFAULT_BASE_URL: &str = "https://101.ru";
+
 /// Ответ API 101.ru для потоков
 #[derive(Debug, serde::Deserialize)]
 struct Ru101StreamResponse {
@@ -18,7 +27,6 @@ struct Ru101StreamServer {
     #[allow(dead_code)]
     title_channel: String,
     url_stream: String,
-    #[allow(dead_code)]
     format: String,
     quality: u32,
     protocols: String,
@@ -27,12 +35,26 @@ struct Ru101StreamServer {
 /// Источник 101.ru
 pub struct Ru101Source {
     client: reqwest::Client,
+    /// Базовый URL сайта и API (без завершающего слэша)
+    base_url: String,
     /// Cookie для авторизации потоков
     cookie: Arc<RwLock<Option<String>>>,
+    /// Последний полученный список серверов по каналам
+    servers: Arc<RwLock<HashMap<u32, Vec<StreamCandidate>>>>,
+    /// Серверы с недавним сбоем (ключ: URL без query) -> время сбоя
+    failed_servers: Arc<RwLock<HashMap<String, Instant>>>,
 }
 
 impl Ru101Source {
+    /// ID источника (префикс ID станций)
+    pub const ID: &'static str = "ru101";
+
     pub fn new() -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL)
+    }
+
+    /// Создать источник с другим адресом сайта (например, локальный тестовый сервер)
+    pub fn with_base_url(base_url: &str) -> Self {
         let client = reqwest::Client::builder()
             .user_agent("Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/144.0.0.0 Safari/537.36")
             .timeout(std::time::Duration::from_secs(15))
@@ -42,20 +64,35 @@ impl Ru101Source {
 
         Self {
             client,
+            base_url: base_url.trim_end_matches('/').to_string(),
             cookie: Arc::new(RwLock::new(None)),
+            servers: Arc::new(RwLock::new(HashMap::new())),
+            failed_servers: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
+    /// Станция 101.ru по ID канала
+    pub fn new_station(channel_id: u32, name: &str, stream_url: &str) -> RadioStation {
+        let mut station = RadioStation::new(Self::ID, &channel_id.to_string(), name, stream_url);
+        station.channel_id = Some(channel_id);
+        station
+    }
+
+    /// Referer для запросов к сайту
+    fn referer(&self) -> String {
+        format!("{}/", self.base_url)
+    }
+
     /// Инициализировать сессию и получить cookie
-    async fn init_session(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    async fn init_session(&self) -> AppResult<()> {
         let response = self
             .client
-            .get("https://101.ru/")
+            .get(self.referer())
             .header(
                 "Accept",
                 "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
             )
-            .header("Referer", "https://101.ru/")
+            .header("Referer", self.referer())
             .send()
             .await?;
 
@@ -166,7 +203,7 @@ impl Ru101Source {
                         .map(|s| s.to_string())
                 });
 
-            let mut station = RadioStation::new_ru101(channel_id, &name, "");
+            let mut station = Self::new_station(channel_id, &name, "");
             station.logo = logo;
 
             stations.push(station);
@@ -201,7 +238,7 @@ impl Ru101Source {
                     continue;
                 }
 
-                let station = RadioStation::new_ru101(channel_id, &name, "");
+                let station = Self::new_station(channel_id, &name, "");
                 stations.push(station);
             }
         }
@@ -209,45 +246,89 @@ impl Ru101Source {
         stations
     }
 
-    /// Получить URL потока для конкретного канала
-    async fn fetch_stream_for_channel(
-        &self,
-        channel_id: u32,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    /// Ключ сервера для учёта сбоев (токены в query меняются от запроса к запросу)
+    fn server_key(url: &str) -> String {
+        url.split('?').next().unwrap_or(url).to_string()
+    }
+
+    /// Получить все серверы вещания канала, от лучшего к худшему:
+    /// рабочие раньше недавно упавших, HTTPS раньше HTTP, затем по качеству
+    async fn fetch_servers_for_channel(&self, channel_id: u32) -> AppResult<Vec<StreamCandidate>> {
         let url = format!(
-            "https://101.ru/api/channel/getListServersChannel/{}",
-            channel_id
+            "{}/api/channel/getListServersChannel/{}",
+            self.base_url, channel_id
         );
 
         let response = self
             .client
             .get(&url)
-            .header("Referer", "https://101.ru/")
+            .header("Referer", self.referer())
             .send()
             .await?;
 
-        if !response.status().is_success() {
-            return Err(format!("Ошибка API 101.ru: {}", response.status()).into());
-        }
+        let response =
+            AppError::check_status(response).map_err(|e| e.context("Ошибка API 101.ru"))?;
 
         let data: Ru101StreamResponse = response.json().await?;
 
         if data.status != 1 || data.result.is_empty() {
-            return Err("Нет доступных потоков".into());
+            return Err(AppError::not_found("Нет доступных потоков"));
         }
 
-        // Выбираем HTTPS поток с наилучшим качеством
-        let best_stream = data
+        let candidates = data
             .result
-            .iter()
-            .filter(|s| s.protocols == "https")
-            .max_by_key(|s| s.quality)
-            .or_else(|| data.result.first());
-
-        match best_stream {
-            Some(stream) => Ok(stream.url_stream.clone()),
-            None => Err("Нет подходящего потока".into()),
+            .into_iter()
+            .filter(|s| !s.url_stream.is_empty())
+            .map(|s| StreamCandidate {
+                url: s.url_stream,
+                quality: Some(s.quality),
+                format: Some(s.format),
+                protocol: Some(s.protocols),
+                cooling_down: false,
+            })
+            .collect();
+        let candidates = self.rank_candidates(candidates).await;
+
+        if candidates.is_empty() {
+            return Err(AppError::not_found("Нет подходящего потока"));
+        }
+
+        self.servers
+            .write()
+            .await
+            .insert(channel_id, candidates.clone());
+
+        Ok(candidates)
+    }
+
+    /// Отметить недавно упавшие серверы и упорядочить: рабочие раньше упавших,
+    /// HTTPS раньше HTTP, затем по качеству
+    async fn rank_candidates(&self, mut candidates: Vec<StreamCandidate>) -> Vec<StreamCandidate> {
+        {
+            let mut failed = self.failed_servers.write().await;
+            failed.retain(|_, at| at.elapsed() < STREAM_FAILURE_COOLDOWN);
+            for candidate in &mut candidates {
+                candidate.cooling_down = failed.contains_key(&Self::server_key(&candidate.url));
+            }
+        }
+
+        candidates.sort_by_key(|c| {
+            (
+                c.cooling_down,
+                c.protocol.as_deref() != Some("https"),
+                std::cmp::Reverse(c.quality),
+            )
+        });
+        candidates
+    }
+
+    /// Инициализировать сессию, если cookie ещё нет
+    async fn ensure_session(&self) -> AppResult<()> {
+        let has_cookie = self.cookie.read().await.is_some();
+        if !has_cookie {
+            self.init_session().await?;
         }
+        Ok(())
     }
 }
 
@@ -259,9 +340,26 @@ impl Default for Ru101Source {
 
 #[async_trait]
 impl RadioSourceTrait for Ru101Source {
-    async fn fetch_stations(
-        &self,
-    ) -> Result<Vec<RadioStation>, Box<dyn std::error::Error + Send + Sync>> {
+    fn id(&self) -> &'static str {
+        Self::ID
+    }
+
+    fn display_name(&self) -> &'static str {
+        "101.ru"
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            catalog: true,
+            metadata: true,
+            resolves_stream_url: true,
+            hls: false,
+            track_end_time: true,
+            stream_metadata: false,
+        }
+    }
+
+    async fn fetch_stations(&self) -> AppResult<Vec<RadioStation>> {
         // Инициализируем сессию для получения cookie
         self.init_session().await?;
 
@@ -279,15 +377,17 @@ impl RadioSourceTrait for Ru101Source {
 
             for &group_id in chunk {
                 let client = self.client.clone();
+                let base_url = self.base_url.clone();
+                let referer = self.referer();
                 handles.push(tokio::spawn(async move {
-                    let url = format!("https://101.ru/radio-top/group/{}", group_id);
+                    let url = format!("{}/radio-top/group/{}", base_url, group_id);
                     let response = client
                         .get(&url)
                         .header(
                             "Accept",
                             "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
                         )
-                        .header("Referer", "https://101.ru/")
+                        .header("Referer", referer)
                         .send()
                         .await;
 
@@ -309,7 +409,7 @@ impl RadioSourceTrait for Ru101Source {
                                 // Добавляем полный URL для логотипа если он относительный
                                 if let Some(ref logo) = station.logo {
                                     if logo.starts_with('/') {
-                                        station.logo = Some(format!("https://101.ru{}", logo));
+                                        station.logo = Some(format!("{}{}", self.base_url, logo));
                                     }
                                 }
                                 all_stations.push(station);
@@ -331,51 +431,81 @@ impl RadioSourceTrait for Ru101Source {
         Ok(all_stations)
     }
 
-    async fn get_stream_url(
+    async fn get_stream_url(&self, station: &RadioStation) -> AppResult<String> {
+        // Первый кандидат — лучший сервер без недавнего сбоя
+        // (если сбоили все, пробуем лучший из них)
+        let candidates = self.get_stream_candidates(station).await?;
+        candidates
+            .into_iter()
+            .next()
+            .map(|c| c.url)
+            .ok_or_else(|| AppError::not_found("Нет подходящего потока"))
+    }
+
+    async fn get_stream_candidates(
         &self,
         station: &RadioStation,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        let channel_id = station
-            .channel_id
-            .ok_or("Нет channel_id для станции 101.ru")?;
-
-        // Инициализируем сессию если нужно
-        {
-            let cookie_guard = self.cookie.read().await;
-            if cookie_guard.is_none() {
-                drop(cookie_guard);
-                self.init_session().await?;
+    ) -> AppResult<Vec<StreamCandidate>> {
+        let channel_id = station.channel_id.ok_or_else(|| {
+            AppError::missing_field("channel_id", "Нет channel_id для станции 101.ru")
+        })?;
+
+        let fetched = match self.ensure_session().await {
+            Ok(()) => self.fetch_servers_for_channel(channel_id).await,
+            Err(e) => Err(e),
+        };
+        match fetched {
+            Ok(candidates) => Ok(candidates),
+            Err(e) => {
+                // API недоступно — используем последний известный список серверов,
+                // с учётом сбоев, о которых сообщили после его получения
+                let known = self.servers.read().await.get(&channel_id).cloned();
+                match known {
+                    Some(candidates) if !candidates.is_empty() => {
+                        eprintln!(
+                            "⚠️ 101.ru: список серверов канала {} недоступен ({}), используем сохранённый",
+                            channel_id, e
+                        );
+                        Ok(self.rank_candidates(candidates).await)
+                    }
+                    _ => Err(e),
+                }
             }
         }
+    }
 
-        self.fetch_stream_for_channel(channel_id).await
+    async fn report_stream_failure(&self, station: &RadioStation, url: &str) {
+        eprintln!(
+            "⚠️ 101.ru: сервер {} станции {} отмечен как нерабочий",
+            url, station.name
+        );
+        self.failed_servers
+            .write()
+            .await
+            .insert(Self::server_key(url), Instant::now());
     }
 
-    async fn update_metadata(
-        &self,
-        station: &mut RadioStation,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let channel_id = station
-            .channel_id
-            .ok_or("Нет channel_id для станции 101.ru")?;
+    async fn update_metadata(&self, station: &mut RadioStation) -> AppResult<()> {
+        let channel_id = station.channel_id.ok_or_else(|| {
+            AppError::missing_field("channel_id", "Нет channel_id для станции 101.ru")
+        })?;
 
-        let url = format!("https://101.ru/api/channel/getTrackOnAir/{}", channel_id);
+        let url = format!("{}/api/channel/getTrackOnAir/{}", self.base_url, channel_id);
 
         let response = self
             .client
             .get(&url)
-            .header("Referer", "https://101.ru/")
+            .header("Referer", self.referer())
             .send()
             .await?;
 
-        if !response.status().is_success() {
-            return Err(format!("Ошибка API 101.ru: {}", response.status()).into());
-        }
+        let response =
+            AppError::check_status(response).map_err(|e| e.context("Ошибка API 101.ru"))?;
 
         let json: serde_json::Value = response.json().await?;
 
         if json.get("status").and_then(|s| s.as_i64()) != Some(1) {
-            return Err("Ошибка получения метаданных".into());
+            return Err(AppError::parse("Ошибка получения метаданных"));
         }
 
         if let Some(result) = json.get("result") {
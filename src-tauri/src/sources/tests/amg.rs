@@ -0,0 +1,173 @@
+use super::fixture;
+use crate::mock_server::{MockResponse, MockServer};
+use crate::sources::{AmgSource, RadioSourceTrait};
+
+const STATIONS_PATH: &str = "/wp-json/wp/v2/station";
+
+async fn source_with_catalog() -> (MockServer, AmgSource) {
+    let server = MockServer::start().await;
+    server.route(
+        STATIONS_PATH,
+        MockResponse::json(fixture!("amg_stations.json")),
+    );
+    server.route(
+        "/wp-json/wp/v2/media/501",
+        MockResponse::json(fixture!("amg_media_501.json")),
+    );
+    let source = AmgSource::with_base_urls(server.url(), server.url());
+    (server, source)
+}
+
+fn known_station_ids() -> Vec<String> {
+    AmgSource::get_known_stations()
+        .into_iter()
+        .map(|s| s.id)
+        .collect()
+}
+
+#[tokio::test]
+async fn fetch_stations_maps_wordpress_catalog() {
+    let (_server, source) = source_with_catalog().await;
+
+    let stations = source.fetch_stations().await.unwrap();
+
+    // Станции без потока и без meta пропускаются, остальные отсортированы по названию
+    let names: Vec<_> = stations.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["Escape FM", "Jazz FM", "Русская Волна"]);
+
+    // meta_key берётся из имени HLS плейлиста, логотип — из media API
+    let ruwave = &stations[2];
+    assert_eq!(ruwave.id, "amg_ruwave");
+    assert_eq!(ruwave.meta_key.as_deref(), Some("ruwave"));
+    assert_eq!(ruwave.stream_url, "https://ruwave.amgradio.ru/ruwave");
+    assert_eq!(
+        ruwave.stream_hls.as_deref(),
+        Some("https://hls.amgradio.ru/hls/RuWave.m3u8?token=abc")
+    );
+    assert_eq!(
+        ruwave.logo.as_deref(),
+        Some("https://ru.volna.top/wp-content/uploads/2024/03/ruwave.svg")
+    );
+    assert_eq!(ruwave.artwork_url, ruwave.logo);
+
+    // Без HLS meta_key строится из slug; media не найден — логотипа нет
+    let jazz = &stations[1];
+    assert_eq!(jazz.id, "amg_jazzfm");
+    assert_eq!(jazz.meta_key.as_deref(), Some("jazzfm"));
+    assert_eq!(jazz.stream_hls, None);
+    assert_eq!(jazz.logo, None);
+
+    // Только HLS: он же используется как stream_url
+    let escape = &stations[0];
+    assert_eq!(escape.id, "amg_escape");
+    assert_eq!(escape.stream_url, "https://hls.amgradio.ru/hls/Escape.m3u8");
+}
+
+#[tokio::test]
+async fn fetch_stations_falls_back_to_known_stations_on_http_error() {
+    let server = MockServer::start().await;
+    server.route(STATIONS_PATH, MockResponse::status(500));
+    let source = AmgSource::with_base_urls(server.url(), server.url());
+
+    let ids: Vec<_> = source
+        .fetch_stations()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|s| s.id)
+        .collect();
+
+    assert_eq!(ids, known_station_ids());
+    assert_eq!(server.hits(STATIONS_PATH), 1);
+}
+
+#[tokio::test]
+async fn fetch_stations_falls_back_to_known_stations_on_empty_catalog() {
+    let server = MockServer::start().await;
+    server.route(STATIONS_PATH, MockResponse::json("[]"));
+    let source = AmgSource::with_base_urls(server.url(), server.url());
+
+    let ids: Vec<_> = source
+        .fetch_stations()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|s| s.id)
+        .collect();
+
+    assert_eq!(ids, known_station_ids());
+}
+
+#[tokio::test]
+async fn fetch_stations_falls_back_to_known_stations_when_unreachable() {
+    // Порт освобождается сразу после bind — соединение будет отклонено
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+    let source = AmgSource::with_base_urls(&url, &url);
+
+    let stations = source.fetch_stations().await.unwrap();
+
+    assert_eq!(stations.len(), known_station_ids().len());
+}
+
+#[tokio::test]
+async fn update_metadata_maps_tag_json() {
+    let server = MockServer::start().await;
+    server.route(
+        "/tag/ruwave.json",
+        MockResponse::json(fixture!("amg_tag_ruwave.json")),
+    );
+    let source = AmgSource::with_base_urls(server.url(), server.url());
+
+    let mut station = AmgSource::new_station(
+        "ruwave",
+        "Русская Волна",
+        "https://ruwave.amgradio.ru/ruwave",
+    );
+    source.update_metadata(&mut station).await.unwrap();
+
+    assert_eq!(
+        station.current_track.as_deref(),
+        Some("Звезда по имени Солнце")
+    );
+    assert_eq!(station.current_artist.as_deref(), Some("Кино"));
+    assert_eq!(station.listeners, Some(1284));
+    assert_eq!(
+        station.artwork_url.as_deref(),
+        Some("https://info.volna.top/covers/kino-zvezda.jpg")
+    );
+    // Без логотипа обложка становится и логотипом
+    assert_eq!(station.logo, station.artwork_url);
+    assert_eq!(server.hits("/tag/ruwave.json"), 1);
+}
+
+#[tokio::test]
+async fn update_metadata_keeps_existing_logo() {
+    let server = MockServer::start().await;
+    server.route(
+        "/tag/ruwave.json",
+        MockResponse::json(fixture!("amg_tag_ruwave.json")),
+    );
+    let source = AmgSource::with_base_urls(server.url(), server.url());
+
+    let mut station = AmgSource::get_known_stations().remove(0);
+    source.update_metadata(&mut station).await.unwrap();
+
+    assert_eq!(
+        station.logo.as_deref(),
+        Some("https://volna.top/logoradio/ruwave.svg")
+    );
+}
+
+#[tokio::test]
+async fn update_metadata_reports_http_status() {
+    let server = MockServer::start().await;
+    let source = AmgSource::with_base_urls(server.url(), server.url());
+
+    let mut station = AmgSource::get_known_stations().remove(0);
+    let error = source.update_metadata(&mut station).await.unwrap_err();
+
+    assert_eq!(error.kind, crate::error::ErrorKind::Http(404));
+    assert_eq!(station.current_track, None);
+}
@@ -0,0 +1,171 @@
+use crate::error::ErrorKind;
+use crate::mock_server::{MockResponse, MockServer};
+use crate::sources::{CustomSource, StreamProbe};
+
+const STREAM_PATH: &str = "/live.mp3";
+
+fn probe(content_type: &str) -> StreamProbe {
+    StreamProbe {
+        content_type: Some(content_type.to_string()),
+        ..StreamProbe::default()
+    }
+}
+
+#[test]
+fn build_station_prefers_user_name_then_icy_name_then_host() {
+    let url = "http://stream.example.org:8000/live.mp3";
+    let icy = StreamProbe {
+        name: Some("Icy Radio".to_string()),
+        ..probe("audio/mpeg")
+    };
+
+    let named = CustomSource::build_station(url, Some("  Моё радио ".to_string()), None, &icy);
+    assert_eq!(named.name, "Моё радио");
+    let blank = CustomSource::build_station(url, Some("   ".to_string()), None, &icy);
+    assert_eq!(blank.name, "Icy Radio");
+    let unnamed = CustomSource::build_station(url, None, None, &probe("audio/mpeg"));
+    assert_eq!(unnamed.name, "stream.example.org");
+
+    // ID зависит только от URL (без пробелов по краям) — повторное добавление заменяет станцию
+    assert_eq!(named.id, unnamed.id);
+    assert!(named.id.starts_with("custom_"));
+    assert_eq!(named.source, CustomSource::ID);
+    let trimmed = CustomSource::new_station(&format!(" {} ", url), "");
+    assert_eq!(trimmed.id, named.id);
+    assert_ne!(
+        CustomSource::new_station("http://stream.example.org:8000/other.mp3", "").id,
+        named.id
+    );
+}
+
+#[test]
+fn build_station_maps_probe_fields() {
+    let url = "https://example.org/live/playlist.m3u8";
+    let hls = StreamProbe {
+        genre: Some("Jazz; Blues /  Soul,".to_string()),
+        bitrate: Some(64),
+        homepage: Some("https://example.org".to_string()),
+        ..probe("application/vnd.apple.mpegurl")
+    };
+
+    let station = CustomSource::build_station(
+        url,
+        None,
+        Some("https://example.org/logo.png".to_string()),
+        &hls,
+    );
+    assert_eq!(station.stream_url, url);
+    assert_eq!(station.stream_hls.as_deref(), Some(url));
+    assert_eq!(
+        station.logo.as_deref(),
+        Some("https://example.org/logo.png")
+    );
+    assert_eq!(station.tags, ["Jazz", "Blues", "Soul"]);
+    assert_eq!(station.category.as_deref(), Some("Jazz"));
+    assert_eq!(station.bitrate, Some(64));
+    assert_eq!(station.homepage.as_deref(), Some("https://example.org"));
+    // У HLS кодек по Content-Type не определить
+    assert_eq!(station.codec, None);
+
+    let mp3 = CustomSource::build_station(
+        "http://example.org/a",
+        None,
+        Some(" ".to_string()),
+        &probe("audio/mpeg"),
+    );
+    assert_eq!(mp3.stream_hls, None);
+    assert_eq!(mp3.logo, None);
+    assert_eq!(mp3.codec.as_deref(), Some("MP3"));
+    assert!(mp3.tags.is_empty());
+    assert_eq!(mp3.category, None);
+}
+
+#[test]
+fn codec_is_derived_from_content_type() {
+    let codecs: Vec<_> = [
+        "audio/mpeg",
+        "audio/aacp",
+        "application/ogg",
+        "audio/opus",
+        "audio/x-flac",
+        "audio/wav",
+    ]
+    .into_iter()
+    .map(|ct| probe(ct).codec())
+    .collect();
+    assert_eq!(
+        codecs,
+        [
+            Some("MP3".to_string()),
+            Some("AAC".to_string()),
+            Some("OGG".to_string()),
+            Some("OPUS".to_string()),
+            Some("FLAC".to_string()),
+            None,
+        ]
+    );
+    assert!(probe("audio/x-mpegurl").is_hls());
+    assert!(!probe("audio/mpeg").is_hls());
+    assert!(!StreamProbe::default().is_hls());
+}
+
+#[tokio::test]
+async fn probe_maps_icy_headers() {
+    let server = MockServer::start().await;
+    server.route(
+        STREAM_PATH,
+        MockResponse::status(200)
+            .with_content_type("Audio/MPEG; charset=binary")
+            .with_header("icy-name", " Радио Тест ")
+            .with_header("icy-genre", "Rock, Pop")
+            .with_header("icy-br", "128,128")
+            .with_header("icy-url", "http://radio.example.org")
+            .with_header("icy-description", ""),
+    );
+    let url = format!("{}{}", server.url(), STREAM_PATH);
+
+    let probe = CustomSource::new().probe(&url).await.unwrap();
+    assert_eq!(probe.content_type.as_deref(), Some("audio/mpeg"));
+    assert_eq!(probe.name.as_deref(), Some("Радио Тест"));
+    assert_eq!(probe.genre.as_deref(), Some("Rock, Pop"));
+    assert_eq!(probe.bitrate, Some(128));
+    assert_eq!(probe.homepage.as_deref(), Some("http://radio.example.org"));
+    assert_eq!(probe.codec().as_deref(), Some("MP3"));
+
+    // Заголовки без значений не попадают в пробу
+    server.route(
+        STREAM_PATH,
+        MockResponse::status(200)
+            .with_content_type("audio/aac")
+            .with_header("icy-name", "  ")
+            .with_header("icy-br", "нет"),
+    );
+    let probe = CustomSource::new().probe(&url).await.unwrap();
+    assert_eq!(probe.name, None);
+    assert_eq!(probe.bitrate, None);
+    assert_eq!(probe.codec().as_deref(), Some("AAC"));
+}
+
+#[tokio::test]
+async fn probe_rejects_pages_and_unsupported_urls() {
+    let server = MockServer::start().await;
+    server.route(STREAM_PATH, MockResponse::html("<html></html>"));
+    let url = format!("{}{}", server.url(), STREAM_PATH);
+
+    let source = CustomSource::new();
+    let error = source.probe(&url).await.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidInput);
+    assert!(error.message.contains("text/html"), "{}", error);
+
+    let missing = format!("{}/missing.mp3", server.url());
+    assert_eq!(
+        source.probe(&missing).await.unwrap_err().kind,
+        ErrorKind::Http(404)
+    );
+    for url in ["ftp://example.org/live.mp3", "не url"] {
+        assert_eq!(
+            source.probe(url).await.unwrap_err().kind,
+            ErrorKind::InvalidInput
+        );
+    }
+}
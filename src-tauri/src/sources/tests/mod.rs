@@ -0,0 +1,14 @@
+//! Офлайн-тесты источников на записанных ответах (fixtures/) и локальном HTTP-сервере.
+
+mod amg;
+mod custom;
+mod radio_browser;
+mod ru101;
+
+/// Загрузить записанный ответ из fixtures/
+macro_rules! fixture {
+    ($name:literal) => {
+        include_str!(concat!("fixtures/", $name))
+    };
+}
+pub(crate) use fixture;
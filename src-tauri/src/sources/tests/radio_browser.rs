@@ -0,0 +1,116 @@
+use super::fixture;
+use crate::error::ErrorKind;
+use crate::mock_server::{MockResponse, MockServer};
+use crate::sources::{RadioBrowserSource, RadioSourceTrait};
+
+const SEARCH_PATH: &str = "/json/stations/search";
+const JAZZ_UUID: &str = "96062a7b-0601-11e8-ae97-52543be04c81";
+
+async fn source_with_catalog() -> (MockServer, RadioBrowserSource) {
+    let server = MockServer::start().await;
+    server.route(
+        SEARCH_PATH,
+        MockResponse::json(fixture!("radiobrowser_stations.json")),
+    );
+    let source = RadioBrowserSource::with_base_url(server.url());
+    (server, source)
+}
+
+#[tokio::test]
+async fn fetch_stations_maps_catalog_fields() {
+    let (server, source) = source_with_catalog().await;
+
+    let stations = source.fetch_stations().await.unwrap();
+
+    // Без потока и без названия — пропускаются, повтор UUID — тоже; сортировка по названию
+    let names: Vec<_> = stations.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["Chillout HLS", "Радио Джаз"]);
+
+    let jazz = &stations[1];
+    assert_eq!(jazz.id, format!("radiobrowser_{}", JAZZ_UUID));
+    assert_eq!(jazz.source, "radiobrowser");
+    assert_eq!(jazz.internal_id(), JAZZ_UUID);
+    // url_resolved важнее url
+    assert_eq!(
+        jazz.stream_url,
+        "http://nashe1.hostingradio.ru:80/jazz-128.mp3"
+    );
+    assert_eq!(jazz.stream_hls, None);
+    assert_eq!(jazz.country.as_deref(), Some("The Russian Federation"));
+    assert_eq!(jazz.tags, ["jazz", "smooth jazz", "blues"]);
+    assert_eq!(jazz.category.as_deref(), Some("jazz"));
+    assert_eq!(jazz.codec.as_deref(), Some("MP3"));
+    assert_eq!(jazz.bitrate, Some(128));
+    assert_eq!(jazz.homepage.as_deref(), Some("https://radiojazzfm.ru/"));
+    assert_eq!(
+        jazz.logo.as_deref(),
+        Some("https://radiojazzfm.ru/favicon.png")
+    );
+
+    // Пустые поля не превращаются в Some(""), битрейт 0 — неизвестен
+    let chill = &stations[0];
+    assert_eq!(chill.stream_url, "https://example.org/live/chill.m3u8");
+    assert_eq!(chill.stream_hls.as_deref(), Some(chill.stream_url.as_str()));
+    assert_eq!(chill.country, None);
+    assert!(chill.tags.is_empty());
+    assert_eq!(chill.category, None);
+    assert_eq!(chill.bitrate, None);
+    assert_eq!(chill.homepage, None);
+    assert_eq!(chill.logo, None);
+
+    let request = &server.requests(SEARCH_PATH)[0];
+    assert_eq!(request.method, "GET");
+}
+
+#[tokio::test]
+async fn fetch_stations_reports_api_errors() {
+    let server = MockServer::start().await;
+    server.route(SEARCH_PATH, MockResponse::status(503));
+    let source = RadioBrowserSource::with_base_url(server.url());
+
+    let error = source.fetch_stations().await.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Http(503));
+}
+
+#[tokio::test]
+async fn get_stream_url_registers_click_and_falls_back_to_catalog_url() {
+    let (server, source) = source_with_catalog().await;
+    let jazz = source
+        .fetch_stations()
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|s| s.internal_id() == JAZZ_UUID)
+        .unwrap();
+    let click_path = format!("/json/url/{}", JAZZ_UUID);
+
+    // Сервер каталога учёл прослушивание и вернул актуальный URL
+    server.route(
+        &click_path,
+        MockResponse::json(r#"{"ok": true, "message": "retrieved station url", "url": "http://fresh.example.org/jazz.mp3"}"#),
+    );
+    assert_eq!(
+        source.get_stream_url(&jazz).await.unwrap(),
+        "http://fresh.example.org/jazz.mp3"
+    );
+    assert_eq!(server.hits(&click_path), 1);
+
+    // Отказ или ошибка сервера — URL из каталога
+    server.route(
+        &click_path,
+        MockResponse::json(r#"{"ok": false, "message": "station not found"}"#),
+    );
+    assert_eq!(source.get_stream_url(&jazz).await.unwrap(), jazz.stream_url);
+    server.route(&click_path, MockResponse::status(500));
+    assert_eq!(source.get_stream_url(&jazz).await.unwrap(), jazz.stream_url);
+    assert_eq!(server.hits(&click_path), 3);
+
+    // Без URL в каталоге ошибка, а не пустая строка
+    let mut without_url = jazz.clone();
+    without_url.stream_url.clear();
+    let error = source.get_stream_url(&without_url).await.unwrap_err();
+    assert_eq!(
+        error.kind,
+        ErrorKind::MissingField("stream_url".to_string())
+    );
+}
@@ -0,0 +1,281 @@
+use super::fixture;
+use crate::error::ErrorKind;
+use crate::mock_server::{MockResponse, MockServer};
+use crate::models::RadioStation;
+use crate::sources::{RadioSourceTrait, Ru101Source};
+
+const SERVERS_PATH: &str = "/api/channel/getListServersChannel/200";
+const TRACK_PATH: &str = "/api/channel/getTrackOnAir/200";
+
+async fn start_server() -> MockServer {
+    let server = MockServer::start().await;
+    server.route(
+        "/",
+        MockResponse::html("<html></html>")
+            .with_header("Set-Cookie", "srvr101=s21; path=/; HttpOnly"),
+    );
+    server
+}
+
+fn relax_fm() -> RadioStation {
+    Ru101Source::new_station(200, "Relax FM", "")
+}
+
+#[tokio::test]
+async fn fetch_stations_parses_grid_items_and_link_fallback() {
+    let server = start_server().await;
+    // Группа 1 — сетка .grid__item, группа 2 — только ссылки, группа 3 — ошибка сервера
+    server.route(
+        "/radio-top/group/1",
+        MockResponse::html(fixture!("ru101_group_1.html")),
+    );
+    server.route(
+        "/radio-top/group/2",
+        MockResponse::html(fixture!("ru101_group_2.html")),
+    );
+    server.route("/radio-top/group/3", MockResponse::status(500));
+    let source = Ru101Source::with_base_url(server.url());
+
+    let stations = source.fetch_stations().await.unwrap();
+
+    let summary: Vec<_> = stations
+        .iter()
+        .map(|s| (s.channel_id.unwrap(), s.name.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (301, "Classic Rock"),
+            (200, "Relax FM"),
+            (45, "Мелодии кино"),
+            (12, "Русский рок"),
+            (7, "Шансон"),
+        ]
+    );
+    assert_eq!(server.hits("/"), 1);
+    assert_eq!(server.hits("/radio-top/group/38"), 1);
+
+    let logo = |id: u32| {
+        stations
+            .iter()
+            .find(|s| s.channel_id == Some(id))
+            .and_then(|s| s.logo.clone())
+    };
+    // Относительный логотип дополняется адресом сайта
+    assert_eq!(
+        logo(200),
+        Some(format!(
+            "{}/vardata/modules/channel/image/200.png",
+            server.url()
+        ))
+    );
+    // "101.ru" в broadcastDisplayName пропускается, название и логотип из запасных селекторов
+    assert_eq!(
+        logo(45).as_deref(),
+        Some("https://cdn.101.ru/vardata/modules/channel/image/45.jpg")
+    );
+    assert_eq!(
+        logo(7).as_deref(),
+        Some("https://cdn.101.ru/vardata/modules/channel/image/7.webp")
+    );
+    // Станции из ссылок приходят без логотипа
+    assert_eq!(logo(12), None);
+    assert_eq!(stations[0].id, "ru101_301");
+}
+
+#[tokio::test]
+async fn fetch_stations_fails_without_session() {
+    // Порт освобождается сразу после bind — соединение будет отклонено
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+    let source = Ru101Source::with_base_url(&url);
+
+    let error = source.fetch_stations().await.unwrap_err();
+
+    assert_eq!(error.kind, ErrorKind::Network);
+}
+
+#[tokio::test]
+async fn stream_candidates_are_ranked_and_skip_failed_servers() {
+    let server = start_server().await;
+    server.route(
+        SERVERS_PATH,
+        MockResponse::json(fixture!("ru101_servers_200.json")),
+    );
+    let source = Ru101Source::with_base_url(server.url());
+    let station = relax_fm();
+
+    // HTTPS раньше HTTP, затем по качеству; пустые URL отбрасываются
+    let urls: Vec<_> = source
+        .get_stream_candidates(&station)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|c| c.url)
+        .collect();
+    assert_eq!(
+        urls,
+        [
+            "https://srv21.gpmradio.ru:8443/stream/air/mp3/128/200?setst=abc",
+            "https://srv22.gpmradio.ru:8443/stream/air/aac/64/200?setst=abc",
+            "http://srv11.gpmradio.ru:8080/stream/air/aac/64/200?setst=abc",
+        ]
+    );
+
+    // Сбой учитывается по URL без токена
+    source
+        .report_stream_failure(
+            &station,
+            "https://srv21.gpmradio.ru:8443/stream/air/mp3/128/200?setst=other",
+        )
+        .await;
+
+    let candidates = source.get_stream_candidates(&station).await.unwrap();
+    assert_eq!(
+        candidates[0].url,
+        "https://srv22.gpmradio.ru:8443/stream/air/aac/64/200?setst=abc"
+    );
+    assert!(candidates[2].cooling_down);
+    assert_eq!(
+        source.get_stream_url(&station).await.unwrap(),
+        candidates[0].url
+    );
+
+    // Cookie сессии получена один раз
+    assert_eq!(server.hits("/"), 1);
+}
+
+#[tokio::test]
+async fn stream_candidates_fall_back_to_last_known_servers() {
+    let server = start_server().await;
+    let source = Ru101Source::with_base_url(server.url());
+    let station = relax_fm();
+
+    // Без сохранённого списка ошибка API возвращается как есть
+    server.route(SERVERS_PATH, MockResponse::status(503));
+    let error = source.get_stream_candidates(&station).await.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Http(503));
+
+    server.route(
+        SERVERS_PATH,
+        MockResponse::json(fixture!("ru101_servers_200.json")),
+    );
+    let fresh = source.get_stream_candidates(&station).await.unwrap();
+
+    server.route(
+        SERVERS_PATH,
+        MockResponse::status(429).with_header("Retry-After", "30"),
+    );
+    let saved = source.get_stream_candidates(&station).await.unwrap();
+
+    assert_eq!(saved.len(), fresh.len());
+    assert_eq!(saved[0].url, fresh[0].url);
+}
+
+#[tokio::test]
+async fn stream_candidates_fall_back_when_session_fails() {
+    let server = MockServer::start().await;
+    // Сайт не выдал cookie — сессия запрашивается перед каждым списком серверов
+    server.route("/", MockResponse::html("<html></html>"));
+    server.route(
+        SERVERS_PATH,
+        MockResponse::json(fixture!("ru101_servers_200.json")),
+    );
+    let source = Ru101Source::with_base_url(server.url());
+    let station = relax_fm();
+    let fresh = source.get_stream_url(&station).await.unwrap();
+
+    server.route("/", MockResponse::disconnect());
+    assert_eq!(source.get_stream_url(&station).await.unwrap(), fresh);
+    assert_eq!(server.hits("/"), 2);
+    assert_eq!(server.hits(SERVERS_PATH), 1);
+
+    // Без сохранённого списка — ошибка сети
+    let other = Ru101Source::new_station(201, "Другая", "");
+    let error = source.get_stream_url(&other).await.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Network);
+}
+
+#[tokio::test]
+async fn fallback_servers_skip_failures_reported_after_caching() {
+    let server = start_server().await;
+    server.route(
+        SERVERS_PATH,
+        MockResponse::json(fixture!("ru101_servers_200.json")),
+    );
+    let source = Ru101Source::with_base_url(server.url());
+    let station = relax_fm();
+
+    let dead = source.get_stream_url(&station).await.unwrap();
+    source.report_stream_failure(&station, &dead).await;
+
+    // API упало вместе с сервером: сохранённый список пересортирован по свежим сбоям
+    server.route(SERVERS_PATH, MockResponse::status(500));
+    let next = source.get_stream_url(&station).await.unwrap();
+    assert_ne!(next, dead);
+    assert_eq!(
+        next,
+        "https://srv22.gpmradio.ru:8443/stream/air/aac/64/200?setst=abc"
+    );
+
+    let candidates = source.get_stream_candidates(&station).await.unwrap();
+    assert_eq!(candidates.last().unwrap().url, dead);
+    assert!(candidates.last().unwrap().cooling_down);
+    assert!(!candidates[0].cooling_down);
+}
+
+#[tokio::test]
+async fn stream_candidates_require_channel_id() {
+    let server = start_server().await;
+    let source = Ru101Source::with_base_url(server.url());
+    let mut station = relax_fm();
+    station.channel_id = None;
+
+    let error = source.get_stream_url(&station).await.unwrap_err();
+
+    assert_eq!(
+        error.kind,
+        ErrorKind::MissingField("channel_id".to_string())
+    );
+    assert_eq!(server.hits("/"), 0);
+}
+
+#[tokio::test]
+async fn update_metadata_maps_track_on_air() {
+    let server = start_server().await;
+    server.route(
+        TRACK_PATH,
+        MockResponse::json(fixture!("ru101_track_200.json")),
+    );
+    let source = Ru101Source::with_base_url(server.url());
+
+    let mut station = relax_fm();
+    source.update_metadata(&mut station).await.unwrap();
+
+    assert_eq!(station.current_track.as_deref(), Some("Summertime"));
+    assert_eq!(station.current_artist.as_deref(), Some("Ella Fitzgerald"));
+    // Нет cover400 — берётся следующая по качеству обложка
+    assert_eq!(
+        station.artwork_url.as_deref(),
+        Some("https://cdn.101.ru/vardata/modules/musicdb/files/202/300.jpg")
+    );
+    assert_eq!(station.stop_at_ms, Some(1_760_700_215_000));
+    assert_eq!(station.listeners, Some(5321));
+}
+
+#[tokio::test]
+async fn update_metadata_rejects_error_status() {
+    let server = start_server().await;
+    server.route(
+        TRACK_PATH,
+        MockResponse::json(fixture!("ru101_track_error.json")),
+    );
+    let source = Ru101Source::with_base_url(server.url());
+
+    let mut station = relax_fm();
+    let error = source.update_metadata(&mut station).await.unwrap_err();
+
+    assert_eq!(error.kind, ErrorKind::Parse);
+    assert_eq!(station.current_track, None);
+}
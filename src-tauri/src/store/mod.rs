@@ -0,0 +1,546 @@
+//! Встроенная база SQLite: кэш каталогов, пользовательские станции, избранное и история треков.
+//!
+//! Станции хранятся целиком (JSON), а для поиска по ID, источнику и названию есть
+//! отдельные индексированные колонки. В `settings.json` остаются только небольшие настройки.
+//! Избранное и история принадлежат профилю слушателя (см. `set_profile`), каталоги — общие.
+
+#[cfg(test)]
+mod tests;
+
+use crate::error::AppResult;
+use crate::models::RadioStation;
+use crate::services::TrackHistoryEntry;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, RwLock};
+
+/// Профиль, которому принадлежат данные, перенесённые из версий без профилей
+pub const DEFAULT_PROFILE: &str = "default";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS stations (
+    id TEXT PRIMARY KEY,
+    source TEXT NOT NULL,
+    name TEXT NOT NULL COLLATE NOCASE,
+    name_key TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS stations_source ON stations (source, position);
+CREATE INDEX IF NOT EXISTS stations_name_key ON stations (name_key);
+
+CREATE TABLE IF NOT EXISTS favorites (
+    id TEXT PRIMARY KEY,
+    position INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS track_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    station_id TEXT NOT NULL,
+    station_name TEXT NOT NULL,
+    artist TEXT,
+    title TEXT,
+    cover_url TEXT,
+    played_at_ms INTEGER NOT NULL,
+    stop_at_ms INTEGER
+);
+CREATE INDEX IF NOT EXISTS track_history_station ON track_history (station_id, id);
+CREATE INDEX IF NOT EXISTS track_history_played_at ON track_history (played_at_ms);
+";
+
+/// Изменения схемы после `SCHEMA`; номер применённой хранится в `user_version`
+const MIGRATIONS: &[&str] = &[
+    // 1: избранное и история по профилям
+    "
+    CREATE TABLE profile_favorites (
+        profile TEXT NOT NULL,
+        id TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        data TEXT NOT NULL,
+        PRIMARY KEY (profile, id)
+    );
+    INSERT INTO profile_favorites (profile, id, position, data)
+        SELECT 'default', id, position, data FROM favorites;
+    DROP TABLE favorites;
+    ALTER TABLE profile_favorites RENAME TO favorites;
+
+    ALTER TABLE track_history ADD COLUMN profile TEXT NOT NULL DEFAULT 'default';
+    DROP INDEX track_history_station;
+    CREATE INDEX track_history_station ON track_history (profile, station_id, id);
+    CREATE INDEX track_history_profile ON track_history (profile, id);
+    ",
+];
+
+/// Хранилище станций и истории
+pub struct Store {
+    conn: Mutex<Connection>,
+    /// Текущий профиль слушателя
+    profile: RwLock<String>,
+    /// База только в памяти: данные пропадут при выходе
+    in_memory: bool,
+}
+
+impl Store {
+    /// Открыть базу (`None` — только в памяти)
+    pub fn open(path: Option<&Path>) -> AppResult<Self> {
+        let mut conn = match path {
+            Some(path) => {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                let conn = Connection::open(path)?;
+                // WAL: консольный режим может читать базу, пока открыто окно
+                conn.pragma_update(None, "journal_mode", "WAL")?;
+                conn.busy_timeout(std::time::Duration::from_secs(5))?;
+                conn
+            }
+            None => Connection::open_in_memory()?,
+        };
+        conn.execute_batch(SCHEMA)?;
+        migrate(&mut conn)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            profile: RwLock::new(DEFAULT_PROFILE.to_string()),
+            in_memory: path.is_none(),
+        })
+    }
+
+    /// Открыть базу, а если файл повреждён — работать в памяти (с предупреждением)
+    pub fn open_or_memory(path: Option<&Path>) -> Self {
+        Self::open(path).unwrap_or_else(|e| {
+            eprintln!("⚠️ База станций недоступна, данные не сохранятся: {}", e);
+            Self::open(None).expect("не удалось открыть базу в памяти")
+        })
+    }
+
+    /// База в памяти (файл не задан или повреждён) — данные не сохранятся
+    pub fn is_in_memory(&self) -> bool {
+        self.in_memory
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // ==================== Профили ====================
+
+    /// Текущий профиль: к нему относятся избранное и история
+    pub fn profile(&self) -> String {
+        self.profile
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn set_profile(&self, profile: &str) {
+        *self.profile.write().unwrap_or_else(|e| e.into_inner()) = profile.to_string();
+    }
+
+    /// Удалить избранное и историю профиля
+    pub fn delete_profile_data(&self, profile: &str) -> AppResult<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM favorites WHERE profile = ?1", [profile])?;
+        tx.execute("DELETE FROM track_history WHERE profile = ?1", [profile])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    // ==================== Станции ====================
+
+    /// Заменить станции источника (обновление каталога)
+    pub fn replace_source_stations(
+        &self,
+        source_id: &str,
+        stations: &[RadioStation],
+    ) -> AppResult<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        write_source_stations(&tx, source_id, stations)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Станции источника в порядке каталога (None — источник ещё не загружался)
+    pub fn source_stations(&self, source_id: &str) -> AppResult<Option<Vec<RadioStation>>> {
+        let stations = self.query_stations(
+            "SELECT data FROM stations WHERE source = ?1 ORDER BY position",
+            [source_id],
+        )?;
+        Ok(Some(stations).filter(|s| !s.is_empty()))
+    }
+
+    /// Все станции всех источников
+    pub fn all_stations(&self) -> AppResult<Vec<RadioStation>> {
+        self.query_stations("SELECT data FROM stations ORDER BY source, position", [])
+    }
+
+    /// Станция по ID
+    pub fn find_station(&self, id: &str) -> AppResult<Option<RadioStation>> {
+        Ok(self
+            .query_stations("SELECT data FROM stations WHERE id = ?1", [id])?
+            .into_iter()
+            .next())
+    }
+
+    /// Поиск станций по началу названия без учёта регистра (и кириллицы тоже)
+    pub fn search_stations(
+        &self,
+        query: &str,
+        source_id: Option<&str>,
+        limit: usize,
+    ) -> AppResult<Vec<RadioStation>> {
+        // Префикс как диапазон ключей: [prefix, prefix + максимальный символ)
+        let prefix = name_key(query.trim());
+        let upper = format!("{}{}", prefix, char::MAX);
+        let conn = self.conn();
+        let mut statement = conn.prepare(
+            "SELECT data FROM stations
+             WHERE name_key >= ?1 AND name_key < ?2 AND (?3 IS NULL OR source = ?3)
+             ORDER BY name_key LIMIT ?4",
+        )?;
+        let rows = statement.query_map(
+            params![prefix, upper, source_id, limit as i64],
+            station_from_row,
+        )?;
+        collect_stations(rows)
+    }
+
+    /// Добавить или заменить станцию (в конце списка источника)
+    pub fn upsert_station(&self, station: &RadioStation) -> AppResult<()> {
+        self.conn().execute(
+            "INSERT INTO stations (id, source, name, name_key, position, data)
+             VALUES (?1, ?2, ?3, ?4,
+                     (SELECT COALESCE(MAX(position) + 1, 0) FROM stations WHERE source = ?2), ?5)
+             ON CONFLICT (id) DO UPDATE
+                 SET name = excluded.name, name_key = excluded.name_key, data = excluded.data",
+            params![
+                station.id,
+                station.source,
+                station.name,
+                name_key(&station.name),
+                serde_json::to_string(station)?
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Удалить станцию. Возвращает false, если её не было.
+    pub fn remove_station(&self, id: &str) -> AppResult<bool> {
+        Ok(self
+            .conn()
+            .execute("DELETE FROM stations WHERE id = ?1", [id])?
+            > 0)
+    }
+
+    fn query_stations(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+    ) -> AppResult<Vec<RadioStation>> {
+        let conn = self.conn();
+        let mut statement = conn.prepare_cached(sql)?;
+        let rows = statement.query_map(params, station_from_row)?;
+        collect_stations(rows)
+    }
+
+    // ==================== Избранное ====================
+
+    /// Избранное в пользовательском порядке
+    pub fn favorites(&self) -> AppResult<Vec<RadioStation>> {
+        self.query_stations(
+            "SELECT data FROM favorites WHERE profile = ?1 ORDER BY position",
+            [self.profile()],
+        )
+    }
+
+    /// Станция из избранного по ID
+    pub fn find_favorite(&self, id: &str) -> AppResult<Option<RadioStation>> {
+        Ok(self
+            .query_stations(
+                "SELECT data FROM favorites WHERE profile = ?1 AND id = ?2",
+                [self.profile().as_str(), id],
+            )?
+            .into_iter()
+            .next())
+    }
+
+    /// Станция из избранного любого профиля (сначала — текущего)
+    pub fn find_favorite_in_any_profile(&self, id: &str) -> AppResult<Option<RadioStation>> {
+        Ok(self
+            .query_stations(
+                "SELECT data FROM favorites WHERE id = ?2 ORDER BY profile = ?1 DESC LIMIT 1",
+                [self.profile().as_str(), id],
+            )?
+            .into_iter()
+            .next())
+    }
+
+    pub fn is_favorite(&self, id: &str) -> AppResult<bool> {
+        let found = self
+            .conn()
+            .query_row(
+                "SELECT 1 FROM favorites WHERE profile = ?1 AND id = ?2",
+                [self.profile().as_str(), id],
+                |_| Ok(()),
+            )
+            .optional()?;
+        Ok(found.is_some())
+    }
+
+    /// Добавить в конец избранного. Возвращает false, если станция уже там.
+    pub fn add_favorite(&self, station: &RadioStation) -> AppResult<bool> {
+        let inserted = self.conn().execute(
+            "INSERT OR IGNORE INTO favorites (profile, id, position, data)
+             VALUES (?1, ?2,
+                     (SELECT COALESCE(MAX(position) + 1, 0) FROM favorites WHERE profile = ?1), ?3)",
+            params![self.profile(), station.id, serde_json::to_string(station)?],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    /// Удалить из избранного. Возвращает false, если станции там не было.
+    pub fn remove_favorite(&self, id: &str) -> AppResult<bool> {
+        Ok(self.conn().execute(
+            "DELETE FROM favorites WHERE profile = ?1 AND id = ?2",
+            [self.profile().as_str(), id],
+        )? > 0)
+    }
+
+    /// Заменить избранное целиком (например, после сортировки)
+    pub fn replace_favorites(&self, stations: &[RadioStation]) -> AppResult<()> {
+        let profile = self.profile();
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        write_favorites(&tx, &profile, stations)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    // ==================== Резервная копия ====================
+
+    /// Восстановить резервную копию одной транзакцией: избранное профиля, станции
+    /// источника `custom_source` и история (записи — от старых к новым).
+    /// Если что-то не записалось, не меняется ничего.
+    pub fn restore_backup(
+        &self,
+        favorites: &[RadioStation],
+        custom_source: &str,
+        custom_stations: &[RadioStation],
+        history: &[TrackHistoryEntry],
+        max_entries: usize,
+    ) -> AppResult<()> {
+        let profile = self.profile();
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        write_favorites(&tx, &profile, favorites)?;
+        write_source_stations(&tx, custom_source, custom_stations)?;
+        write_history(&tx, &profile, history, max_entries, true)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    // ==================== История треков ====================
+
+    /// Последняя запись станции (ID записи и сама запись)
+    pub fn last_history_entry(
+        &self,
+        station_id: &str,
+    ) -> AppResult<Option<(i64, TrackHistoryEntry)>> {
+        let profile = self.profile();
+        let conn = self.conn();
+        let entry = conn
+            .query_row(
+                "SELECT id, station_id, station_name, artist, title, cover_url, played_at_ms, stop_at_ms
+                 FROM track_history WHERE profile = ?1 AND station_id = ?2
+                 ORDER BY id DESC LIMIT 1",
+                [profile.as_str(), station_id],
+                |row| Ok((row.get(0)?, history_from_row(row, 1)?)),
+            )
+            .optional()?;
+        Ok(entry)
+    }
+
+    /// Добавить записи и оставить не больше `max_entries` последних
+    pub fn append_history(
+        &self,
+        entries: &[TrackHistoryEntry],
+        max_entries: usize,
+    ) -> AppResult<()> {
+        let profile = self.profile();
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        write_history(&tx, &profile, entries, max_entries, false)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Уточнить время окончания трека
+    pub fn set_history_stop(&self, entry_id: i64, stop_at_ms: Option<i64>) -> AppResult<()> {
+        self.conn().execute(
+            "UPDATE track_history SET stop_at_ms = ?2 WHERE id = ?1",
+            params![entry_id, stop_at_ms],
+        )?;
+        Ok(())
+    }
+
+    /// Записи от новых к старым с фильтром по станции и времени
+    pub fn history(
+        &self,
+        station_id: Option<&str>,
+        since_ms: Option<i64>,
+        limit: usize,
+    ) -> AppResult<Vec<TrackHistoryEntry>> {
+        let profile = self.profile();
+        let conn = self.conn();
+        let mut statement = conn.prepare_cached(
+            "SELECT station_id, station_name, artist, title, cover_url, played_at_ms, stop_at_ms
+             FROM track_history
+             WHERE profile = ?1 AND (?2 IS NULL OR station_id = ?2)
+                   AND (?3 IS NULL OR played_at_ms >= ?3)
+             ORDER BY id DESC LIMIT ?4",
+        )?;
+        let rows = statement.query_map(
+            params![profile, station_id, since_ms, limit as i64],
+            |row| history_from_row(row, 0),
+        )?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Очистить историю (всю или одной станции). Возвращает число удалённых записей.
+    pub fn clear_history(&self, station_id: Option<&str>) -> AppResult<usize> {
+        let profile = self.profile();
+        let removed = self.conn().execute(
+            "DELETE FROM track_history WHERE profile = ?1 AND (?2 IS NULL OR station_id = ?2)",
+            params![profile, station_id],
+        )?;
+        Ok(removed)
+    }
+}
+
+/// Применить миграции схемы, которых ещё не было в этой базе
+fn migrate(conn: &mut Connection) -> AppResult<()> {
+    let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    for (index, sql) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)?;
+        tx.pragma_update(None, "user_version", index as i64 + 1)?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+/// Заменить станции источника (внутри транзакции вызывающего)
+fn write_source_stations(
+    conn: &Connection,
+    source_id: &str,
+    stations: &[RadioStation],
+) -> AppResult<()> {
+    conn.execute("DELETE FROM stations WHERE source = ?1", [source_id])?;
+    let mut insert = conn.prepare(
+        "INSERT OR REPLACE INTO stations (id, source, name, name_key, position, data)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?;
+    for (position, station) in stations.iter().enumerate() {
+        insert.execute(params![
+            station.id,
+            source_id,
+            station.name,
+            name_key(&station.name),
+            position as i64,
+            serde_json::to_string(station)?
+        ])?;
+    }
+    Ok(())
+}
+
+/// Заменить избранное профиля (внутри транзакции вызывающего)
+fn write_favorites(conn: &Connection, profile: &str, stations: &[RadioStation]) -> AppResult<()> {
+    conn.execute("DELETE FROM favorites WHERE profile = ?1", [profile])?;
+    let mut insert = conn.prepare(
+        "INSERT OR IGNORE INTO favorites (profile, id, position, data)
+         VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for (position, station) in stations.iter().enumerate() {
+        insert.execute(params![
+            profile,
+            station.id,
+            position as i64,
+            serde_json::to_string(station)?
+        ])?;
+    }
+    Ok(())
+}
+
+/// Добавить записи истории профиля (`clear` — вместо текущих) и обрезать до `max_entries`
+fn write_history(
+    conn: &Connection,
+    profile: &str,
+    entries: &[TrackHistoryEntry],
+    max_entries: usize,
+    clear: bool,
+) -> AppResult<()> {
+    if clear {
+        conn.execute("DELETE FROM track_history WHERE profile = ?1", [profile])?;
+    }
+    let mut insert = conn.prepare(
+        "INSERT INTO track_history
+         (profile, station_id, station_name, artist, title, cover_url, played_at_ms, stop_at_ms)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+    )?;
+    for entry in entries {
+        insert.execute(params![
+            profile,
+            entry.station_id,
+            entry.station_name,
+            entry.artist,
+            entry.title,
+            entry.cover_url,
+            entry.played_at_ms,
+            entry.stop_at_ms
+        ])?;
+    }
+    conn.execute(
+        "DELETE FROM track_history WHERE profile = ?1 AND id NOT IN
+         (SELECT id FROM track_history WHERE profile = ?1 ORDER BY id DESC LIMIT ?2)",
+        params![profile, max_entries as i64],
+    )?;
+    Ok(())
+}
+
+/// Ключ поиска: название в нижнем регистре (Unicode — LOWER и NOCASE
+/// в SQLite понимают только латиницу)
+fn name_key(name: &str) -> String {
+    name.to_lowercase()
+}
+
+fn station_from_row(row: &Row<'_>) -> rusqlite::Result<String> {
+    row.get(0)
+}
+
+/// Разобрать станции; повреждённые записи пропускаются
+fn collect_stations(
+    rows: impl Iterator<Item = rusqlite::Result<String>>,
+) -> AppResult<Vec<RadioStation>> {
+    let mut stations = Vec::new();
+    for data in rows {
+        match serde_json::from_str(&data?) {
+            Ok(station) => stations.push(station),
+            Err(e) => eprintln!("⚠️ Пропущена повреждённая запись станции: {}", e),
+        }
+    }
+    Ok(stations)
+}
+
+fn history_from_row(row: &Row<'_>, offset: usize) -> rusqlite::Result<TrackHistoryEntry> {
+    Ok(TrackHistoryEntry {
+        station_id: row.get(offset)?,
+        station_name: row.get(offset + 1)?,
+        artist: row.get(offset + 2)?,
+        title: row.get(offset + 3)?,
+        cover_url: row.get(offset + 4)?,
+        played_at_ms: row.get(offset + 5)?,
+        stop_at_ms: row.get(offset + 6)?,
+    })
+}
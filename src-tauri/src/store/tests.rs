@@ -0,0 +1,345 @@
+use super::*;
+
+fn station(source: &str, slug: &str, name: &str) -> RadioStation {
+    RadioStation::new(source, slug, name, "http://stream")
+}
+
+fn ids(stations: &[RadioStation]) -> Vec<&str> {
+    stations.iter().map(|s| s.id.as_str()).collect()
+}
+
+fn entry(station_id: &str, title: &str, played_at_ms: i64) -> TrackHistoryEntry {
+    TrackHistoryEntry {
+        station_id: station_id.to_string(),
+        station_name: station_id.to_string(),
+        artist: None,
+        title: Some(title.to_string()),
+        cover_url: None,
+        played_at_ms,
+        stop_at_ms: None,
+    }
+}
+
+#[test]
+fn catalogs_are_replaced_per_source_and_found_by_id_and_name() {
+    let store = Store::open(None).unwrap();
+    assert!(store.is_in_memory());
+    assert!(store.source_stations("amg").unwrap().is_none());
+
+    store
+        .replace_source_stations(
+            "amg",
+            &[
+                station("amg", "ruwave", "Русская Волна"),
+                station("amg", "hypefm", "Hype FM"),
+            ],
+        )
+        .unwrap();
+    store
+        .replace_source_stations("ru101", &[station("ru101", "100", "Хит FM")])
+        .unwrap();
+    // Обновление каталога заменяет только его станции, порядок сохраняется
+    store
+        .replace_source_stations(
+            "amg",
+            &[
+                station("amg", "hypefm", "Hype FM"),
+                station("amg", "rock", "Rock FM"),
+            ],
+        )
+        .unwrap();
+
+    let amg = store.source_stations("amg").unwrap().unwrap();
+    assert_eq!(ids(&amg), vec!["amg_hypefm", "amg_rock"]);
+    assert_eq!(store.all_stations().unwrap().len(), 3);
+    assert_eq!(
+        store.find_station("ru101_100").unwrap().unwrap().name,
+        "Хит FM"
+    );
+    assert!(store.find_station("amg_ruwave").unwrap().is_none());
+
+    let found = store.search_stations("h", None, 10).unwrap();
+    assert_eq!(ids(&found), vec!["amg_hypefm"]);
+    let found = store.search_stations("Хит", Some("amg"), 10).unwrap();
+    assert!(found.is_empty());
+    // Регистр кириллицы не важен
+    for query in ["хит", "ХИТ", " хИт f"] {
+        let found = store.search_stations(query, None, 10).unwrap();
+        assert_eq!(ids(&found), vec!["ru101_100"], "{}", query);
+    }
+    store
+        .upsert_station(&station("custom", "a", "ЁЛКА-радио"))
+        .unwrap();
+    let found = store.search_stations("ёлка", Some("custom"), 10).unwrap();
+    assert_eq!(ids(&found), vec!["custom_a"]);
+    assert_eq!(store.search_stations("", None, 10).unwrap().len(), 4);
+    // Спецсимволы LIKE ищутся буквально
+    assert!(store.search_stations("%", None, 10).unwrap().is_empty());
+}
+
+#[test]
+fn custom_stations_are_upserted_and_removed() {
+    let store = Store::open(None).unwrap();
+    let mut custom = station("custom", "a", "Моя станция");
+    store.upsert_station(&custom).unwrap();
+    store
+        .upsert_station(&station("custom", "b", "Вторая"))
+        .unwrap();
+    custom.name = "Переименована".to_string();
+    store.upsert_station(&custom).unwrap();
+
+    let stations = store.source_stations("custom").unwrap().unwrap();
+    assert_eq!(ids(&stations), vec!["custom_a", "custom_b"]);
+    assert_eq!(stations[0].name, "Переименована");
+
+    assert!(store.remove_station("custom_a").unwrap());
+    assert!(!store.remove_station("custom_a").unwrap());
+}
+
+#[test]
+fn favorites_keep_order() {
+    let store = Store::open(None).unwrap();
+    let a = station("amg", "a", "A");
+    let b = station("ru101", "b", "B");
+    let c = station("custom", "c", "C");
+
+    assert!(store.add_favorite(&a).unwrap());
+    assert!(store.add_favorite(&b).unwrap());
+    assert!(!store.add_favorite(&a).unwrap());
+    assert!(store.is_favorite("ru101_b").unwrap());
+    assert_eq!(ids(&store.favorites().unwrap()), vec!["amg_a", "ru101_b"]);
+
+    store.replace_favorites(&[c.clone(), a.clone()]).unwrap();
+    assert_eq!(ids(&store.favorites().unwrap()), vec!["custom_c", "amg_a"]);
+    assert!(store.remove_favorite("custom_c").unwrap());
+    store.add_favorite(&b).unwrap();
+    assert_eq!(ids(&store.favorites().unwrap()), vec!["amg_a", "ru101_b"]);
+    assert!(store.find_favorite("custom_c").unwrap().is_none());
+}
+
+#[test]
+fn history_is_filtered_and_trimmed() {
+    let store = Store::open(None).unwrap();
+    store
+        .append_history(
+            &[
+                entry("amg_a", "1", 1000),
+                entry("amg_b", "2", 2000),
+                entry("amg_a", "3", 3000),
+            ],
+            10,
+        )
+        .unwrap();
+
+    let titles = |entries: Vec<TrackHistoryEntry>| -> Vec<String> {
+        entries.into_iter().filter_map(|e| e.title).collect()
+    };
+    assert_eq!(
+        titles(store.history(None, None, 10).unwrap()),
+        vec!["3", "2", "1"]
+    );
+    assert_eq!(
+        titles(store.history(Some("amg_a"), None, 10).unwrap()),
+        vec!["3", "1"]
+    );
+    assert_eq!(
+        titles(store.history(None, Some(2000), 1).unwrap()),
+        vec!["3"]
+    );
+
+    let (id, last) = store.last_history_entry("amg_a").unwrap().unwrap();
+    assert_eq!(last.title.as_deref(), Some("3"));
+    store.set_history_stop(id, Some(4000)).unwrap();
+    assert_eq!(
+        store
+            .last_history_entry("amg_a")
+            .unwrap()
+            .unwrap()
+            .1
+            .stop_at_ms,
+        Some(4000)
+    );
+
+    // Лишние старые записи удаляются
+    store
+        .append_history(&[entry("amg_b", "4", 5000)], 2)
+        .unwrap();
+    assert_eq!(
+        titles(store.history(None, None, 10).unwrap()),
+        vec!["4", "3"]
+    );
+
+    assert_eq!(store.clear_history(Some("amg_b")).unwrap(), 1);
+    assert_eq!(store.clear_history(None).unwrap(), 1);
+}
+
+#[test]
+fn backup_restore_is_all_or_nothing() {
+    let store = Store::open(None).unwrap();
+    store.add_favorite(&station("amg", "a", "A")).unwrap();
+    store
+        .replace_source_stations("custom", &[station("custom", "c", "C")])
+        .unwrap();
+    store
+        .append_history(&[entry("amg_a", "1", 1000)], 10)
+        .unwrap();
+
+    // История не записывается — избранное и свои станции тоже остаются прежними
+    store
+        .conn()
+        .execute_batch(
+            "CREATE TEMP TRIGGER fail_history BEFORE INSERT ON track_history
+             BEGIN SELECT RAISE(ABORT, 'сбой записи'); END;",
+        )
+        .unwrap();
+    let favorites = [station("ru101", "b", "B")];
+    let custom = [station("custom", "d", "D")];
+    let history = [entry("ru101_b", "2", 2000)];
+    assert!(store
+        .restore_backup(&favorites, "custom", &custom, &history, 10)
+        .is_err());
+    assert_eq!(ids(&store.favorites().unwrap()), vec!["amg_a"]);
+    assert_eq!(
+        ids(&store.source_stations("custom").unwrap().unwrap()),
+        vec!["custom_c"]
+    );
+    assert_eq!(store.history(None, None, 10).unwrap().len(), 1);
+
+    store
+        .conn()
+        .execute_batch("DROP TRIGGER fail_history;")
+        .unwrap();
+    store
+        .restore_backup(&favorites, "custom", &custom, &history, 10)
+        .unwrap();
+    assert_eq!(ids(&store.favorites().unwrap()), vec!["ru101_b"]);
+    assert_eq!(
+        ids(&store.source_stations("custom").unwrap().unwrap()),
+        vec!["custom_d"]
+    );
+    let titles: Vec<_> = store
+        .history(None, None, 10)
+        .unwrap()
+        .into_iter()
+        .filter_map(|e| e.title)
+        .collect();
+    assert_eq!(titles, vec!["2"]);
+}
+
+#[test]
+fn database_file_survives_reopen() {
+    let path = std::env::temp_dir().join(format!("radivo-store-{}.db", std::process::id()));
+    {
+        let store = Store::open(Some(&path)).unwrap();
+        store.add_favorite(&station("amg", "a", "A")).unwrap();
+    }
+    let store = Store::open(Some(&path)).unwrap();
+    assert!(store.is_favorite("amg_a").unwrap());
+    drop(store);
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+}
+
+#[test]
+fn favorites_and_history_belong_to_profile() {
+    let store = Store::open(None).unwrap();
+    let a = station("amg", "a", "A");
+    store.add_favorite(&a).unwrap();
+    store
+        .append_history(&[entry("amg_a", "1", 1000)], 10)
+        .unwrap();
+
+    store.set_profile("anna");
+    assert!(store.favorites().unwrap().is_empty());
+    assert!(!store.is_favorite("amg_a").unwrap());
+    assert!(store.history(None, None, 10).unwrap().is_empty());
+    // Та же станция может быть в избранном разных профилей
+    assert!(store.add_favorite(&a).unwrap());
+    store
+        .append_history(&[entry("amg_a", "2", 2000), entry("amg_a", "3", 3000)], 1)
+        .unwrap();
+    assert_eq!(store.history(None, None, 10).unwrap().len(), 1);
+
+    // Обрезка и удаление профиля не трогают чужие данные
+    store.delete_profile_data("anna").unwrap();
+    assert!(store.favorites().unwrap().is_empty());
+    store.set_profile(DEFAULT_PROFILE);
+    assert_eq!(ids(&store.favorites().unwrap()), vec!["amg_a"]);
+    assert_eq!(store.history(None, None, 10).unwrap().len(), 1);
+}
+
+#[test]
+fn favorite_is_found_in_any_profile_preferring_current() {
+    let store = Store::open(None).unwrap();
+    let mut a = station("amg", "a", "A");
+    store.add_favorite(&a).unwrap();
+
+    store.set_profile("anna");
+    assert!(store.find_favorite("amg_a").unwrap().is_none());
+    assert_eq!(
+        store
+            .find_favorite_in_any_profile("amg_a")
+            .unwrap()
+            .unwrap()
+            .name,
+        "A"
+    );
+
+    a.name = "A у Анны".to_string();
+    store.add_favorite(&a).unwrap();
+    assert_eq!(
+        store
+            .find_favorite_in_any_profile("amg_a")
+            .unwrap()
+            .unwrap()
+            .name,
+        "A у Анны"
+    );
+    store.set_profile(DEFAULT_PROFILE);
+    assert_eq!(
+        store
+            .find_favorite_in_any_profile("amg_a")
+            .unwrap()
+            .unwrap()
+            .name,
+        "A"
+    );
+    assert!(store
+        .find_favorite_in_any_profile("amg_b")
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn database_without_profiles_is_migrated() {
+    let path = std::env::temp_dir().join(format!("radivo-store-v0-{}.db", std::process::id()));
+    {
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        conn.execute(
+            "INSERT INTO favorites (id, position, data) VALUES ('amg_a', 0, ?1)",
+            [serde_json::to_string(&station("amg", "a", "A")).unwrap()],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO track_history (station_id, station_name, played_at_ms)
+             VALUES ('amg_a', 'A', 1000)",
+            [],
+        )
+        .unwrap();
+    }
+
+    let store = Store::open(Some(&path)).unwrap();
+    assert!(!store.is_in_memory());
+    assert_eq!(ids(&store.favorites().unwrap()), vec!["amg_a"]);
+    assert_eq!(store.history(None, None, 10).unwrap().len(), 1);
+    drop(store);
+    // Повторное открытие не применяет миграции заново
+    let store = Store::open(Some(&path)).unwrap();
+    assert!(store.is_favorite("amg_a").unwrap());
+    drop(store);
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+}
@@ -0,0 +1,220 @@
+//! Опрос текущего трека в бэкенде.
+//!
+//! Пока станция играет, её метаданные запрашиваются заново сразу после `stop_at_ms`
+//! (101.ru) или с адаптивным интервалом (AMG). Источники, читающие метаданные из потока
+//! (ICY), опрашиваются редко: каждый запрос — ещё одно соединение рядом с играющим. Повторы одного
+//! и того же трека (исполнитель и название) не считаются сменой: обработчик лишь уточняет
+//! время окончания и отмечает для скробблера, что трек ещё звучит. Новый трек он пишет
+//! в историю, скробблит и отправляет фронтенду событием `track-changed`.
+
+#[cfg(test)]
+mod tests;
+
+use crate::models::RadioStation;
+use crate::player::{NowPlaying, Player};
+use crate::services::StationService;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// Событие фронтенду с обновлённой станцией
+pub const TRACK_CHANGED_EVENT: &str = "track-changed";
+
+/// Запас после `stop_at_ms`: источник обновляет данные не мгновенно
+const AFTER_STOP: Duration = Duration::from_secs(2);
+/// Повтор, если `stop_at_ms` прошёл, а источник отдаёт прежний трек
+const STALE_RETRY: Duration = Duration::from_secs(5);
+/// Самый частый опрос без `stop_at_ms`
+const MIN_INTERVAL: Duration = Duration::from_secs(10);
+/// Самый редкий опрос без `stop_at_ms` (сразу после смены трека)
+const MAX_INTERVAL: Duration = Duration::from_secs(60);
+/// Самый частый опрос источников с метаданными в потоке (ICY)
+const STREAM_METADATA_INTERVAL: Duration = Duration::from_secs(2 * 60);
+/// Дольше не ждём, даже если `stop_at_ms` далеко
+const MAX_WAIT: Duration = Duration::from_secs(10 * 60);
+/// Пауза после ошибки (удваивается до `MAX_ERROR_DELAY`)
+const ERROR_DELAY: Duration = Duration::from_secs(15);
+const MAX_ERROR_DELAY: Duration = Duration::from_secs(2 * 60);
+
+/// Результат очередного опроса
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackUpdate {
+    /// Начался другой трек
+    Changed,
+    /// Тот же трек, но уточнились время окончания или обложка
+    Refined,
+    /// Тот же трек без изменений (он ещё звучит)
+    Same,
+}
+
+/// Обработчик результата опроса
+pub type TrackCallback = Arc<dyn Fn(&RadioStation, TrackUpdate) + Send + Sync>;
+
+/// Что считается сменой трека
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TrackKey {
+    artist: Option<String>,
+    track: Option<String>,
+}
+
+impl TrackKey {
+    fn of(station: &RadioStation) -> Self {
+        Self {
+            artist: station.current_artist.clone(),
+            track: station.current_track.clone(),
+        }
+    }
+}
+
+/// Сравнить ответ источника с прошлым опросом (`last` — последний трек, `previous` — станция)
+fn track_update(
+    last: Option<&TrackKey>,
+    previous: &RadioStation,
+    updated: &RadioStation,
+) -> TrackUpdate {
+    if last != Some(&TrackKey::of(updated)) {
+        TrackUpdate::Changed
+    } else if previous.stop_at_ms != updated.stop_at_ms
+        || previous.artwork_url != updated.artwork_url
+    {
+        TrackUpdate::Refined
+    } else {
+        TrackUpdate::Same
+    }
+}
+
+struct ActivePoll {
+    station_id: String,
+    stop: oneshot::Sender<()>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+/// Следить за плеером и опрашивать играющую станцию (до закрытия приложения)
+pub async fn run(
+    player: Arc<Player>,
+    station_service: Arc<StationService>,
+    on_change: TrackCallback,
+) {
+    let mut changes = player.subscribe();
+    let mut active: Option<ActivePoll> = None;
+
+    loop {
+        let target = polled_station(&changes.borrow_and_update(), &station_service);
+        let active_id = active.as_ref().map(|a| a.station_id.as_str());
+        if active_id != target.as_ref().map(|s| s.id.as_str()) {
+            if let Some(previous) = active.take() {
+                let _ = previous.stop.send(());
+                let _ = previous.task.await;
+            }
+            active = target.map(|station| {
+                let (stop, stop_rx) = oneshot::channel();
+                ActivePoll {
+                    station_id: station.id.clone(),
+                    stop,
+                    task: tokio::spawn(poll_station(
+                        station_service.clone(),
+                        station,
+                        on_change.clone(),
+                        stop_rx,
+                    )),
+                }
+            });
+        }
+
+        if changes.changed().await.is_err() {
+            break;
+        }
+    }
+
+    if let Some(active) = active {
+        let _ = active.stop.send(());
+    }
+}
+
+/// Станция, которую нужно опрашивать: играет и её источник отдаёт метаданные
+fn polled_station(state: &NowPlaying, station_service: &StationService) -> Option<RadioStation> {
+    state
+        .station
+        .clone()
+        .filter(|station| state.playing && station_service.supports_metadata(&station.source))
+}
+
+async fn poll_station(
+    station_service: Arc<StationService>,
+    mut station: RadioStation,
+    on_change: TrackCallback,
+    mut stop: oneshot::Receiver<()>,
+) {
+    let stream_metadata = station_service.reads_stream_metadata(&station.source);
+    let mut last: Option<TrackKey> = None;
+    let mut unchanged = 0u32;
+    let mut errors = 0u32;
+
+    loop {
+        let mut updated = station.clone();
+        // Запрос может зависнуть до таймаута — остановка не должна его ждать
+        let result = tokio::select! {
+            _ = &mut stop => return,
+            result = station_service.update_metadata(&mut updated) => result,
+        };
+        let delay = match result {
+            Ok(()) => {
+                errors = 0;
+                let update = track_update(last.as_ref(), &station, &updated);
+                if update == TrackUpdate::Changed {
+                    last = Some(TrackKey::of(&updated));
+                    unchanged = 0;
+                } else {
+                    unchanged += 1;
+                }
+                on_change(&updated, update);
+                station = updated;
+                let delay = next_delay(station.stop_at_ms, now_ms(), unchanged);
+                if stream_metadata {
+                    delay.max(STREAM_METADATA_INTERVAL)
+                } else {
+                    delay
+                }
+            }
+            Err(e) => {
+                errors += 1;
+                if errors == 1 {
+                    eprintln!("⚠️ Метаданные {} не получены: {}", station.name, e);
+                }
+                error_delay(errors)
+            }
+        };
+
+        tokio::select! {
+            _ = &mut stop => return,
+            _ = tokio::time::sleep(delay) => {}
+        }
+    }
+}
+
+/// Когда опросить снова. `unchanged` — сколько опросов подряд трек не менялся.
+/// Без `stop_at_ms` интервал сокращается от `MAX_INTERVAL` до `MIN_INTERVAL`:
+/// сразу после смены трека следующая смена нескоро.
+fn next_delay(stop_at_ms: Option<i64>, now_ms: i64, unchanged: u32) -> Duration {
+    match stop_at_ms {
+        Some(stop_at_ms) if stop_at_ms > now_ms => {
+            (Duration::from_millis((stop_at_ms - now_ms) as u64) + AFTER_STOP).min(MAX_WAIT)
+        }
+        Some(_) => STALE_RETRY.saturating_mul(unchanged + 1).min(MAX_INTERVAL),
+        None => (MAX_INTERVAL / 2u32.saturating_pow(unchanged)).max(MIN_INTERVAL),
+    }
+}
+
+/// Пауза после `errors` ошибок подряд
+fn error_delay(errors: u32) -> Duration {
+    ERROR_DELAY
+        .saturating_mul(2u32.saturating_pow(errors.saturating_sub(1)))
+        .min(MAX_ERROR_DELAY)
+}
+
+fn now_ms() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
@@ -0,0 +1,217 @@
+use super::*;
+use crate::error::AppResult;
+use crate::sources::{
+    AmgSource, CustomSource, RadioSourceTrait, SourceCapabilities, SourceRegistry,
+};
+use crate::store::Store;
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Как источник отвечает на запрос метаданных
+#[derive(Clone, Copy)]
+enum Answer {
+    /// Один и тот же трек
+    SameTrack,
+    /// Ответа нет
+    Hang,
+}
+
+struct FakeSource {
+    id: &'static str,
+    metadata: bool,
+    answer: Answer,
+    requests: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl RadioSourceTrait for FakeSource {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Тест"
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            catalog: true,
+            metadata: self.metadata,
+            resolves_stream_url: false,
+            hls: false,
+            track_end_time: false,
+            stream_metadata: false,
+        }
+    }
+
+    async fn fetch_stations(&self) -> AppResult<Vec<RadioStation>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_stream_url(&self, station: &RadioStation) -> AppResult<String> {
+        Ok(station.stream_url.clone())
+    }
+
+    async fn update_metadata(&self, station: &mut RadioStation) -> AppResult<()> {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+        if let Answer::Hang = self.answer {
+            std::future::pending::<()>().await;
+        }
+        station.current_artist = Some("Кино".to_string());
+        station.current_track = Some("Группа крови".to_string());
+        Ok(())
+    }
+}
+
+fn service(requests: &Arc<AtomicUsize>, answer: Answer) -> Arc<StationService> {
+    let mut registry = SourceRegistry::new();
+    registry.register(Box::new(FakeSource {
+        id: "amg",
+        metadata: true,
+        answer,
+        requests: requests.clone(),
+    }));
+    registry.register(Box::new(FakeSource {
+        id: "custom",
+        metadata: false,
+        answer,
+        requests: requests.clone(),
+    }));
+    Arc::new(StationService::with_registry(
+        registry,
+        Arc::new(Store::open(None).unwrap()),
+    ))
+}
+
+#[test]
+fn delay_follows_stop_time_or_shortens_while_track_repeats() {
+    let now = 1_000_000;
+    // 101.ru: сразу после окончания трека (с запасом)
+    assert_eq!(
+        next_delay(Some(now + 90_000), now, 0),
+        Duration::from_secs(92)
+    );
+    assert_eq!(next_delay(Some(now + 86_400_000), now, 0), MAX_WAIT);
+    // Трек должен был смениться, а источник отдаёт прежний
+    assert_eq!(next_delay(Some(now - 1), now, 1), Duration::from_secs(10));
+    assert_eq!(next_delay(Some(now - 1), now, 100), MAX_INTERVAL);
+
+    // Без времени окончания: 60, 30, 15, затем 10 секунд
+    let delays: Vec<u64> = (0..5)
+        .map(|unchanged| next_delay(None, now, unchanged).as_secs())
+        .collect();
+    assert_eq!(delays, vec![60, 30, 15, 10, 10]);
+    assert_eq!(next_delay(None, now, u32::MAX), MIN_INTERVAL);
+
+    assert_eq!(error_delay(1), Duration::from_secs(15));
+    assert_eq!(error_delay(2), Duration::from_secs(30));
+    assert_eq!(error_delay(100), MAX_ERROR_DELAY);
+}
+
+#[tokio::test]
+async fn playing_station_is_polled_until_playback_stops() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let player = Arc::new(Player::new(50));
+    let changed: Arc<Mutex<Vec<RadioStation>>> = Arc::default();
+    let changed_in_callback = changed.clone();
+    let poller = tokio::spawn(run(
+        player.clone(),
+        service(&requests, Answer::SameTrack),
+        Arc::new(move |station, update| {
+            if update == TrackUpdate::Changed {
+                changed_in_callback.lock().unwrap().push(station.clone());
+            }
+        }),
+    ));
+
+    // Источник без метаданных не опрашивается
+    let custom = CustomSource::new_station("http://stream/custom", "Своя");
+    player.set_playback(Some(custom), true);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(requests.load(Ordering::SeqCst), 0);
+
+    let station = AmgSource::new_station("ruwave", "Русская Волна", "http://stream");
+    player.set_playback(Some(station.clone()), true);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    {
+        let changed = changed.lock().unwrap();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].id, station.id);
+        assert_eq!(changed[0].current_track.as_deref(), Some("Группа крови"));
+    }
+
+    // Обновление метаданных той же станции не перезапускает опрос
+    player.update_metadata(&changed.lock().unwrap()[0]);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+    // После паузы и повторного запуска трек запрашивается заново
+    player.set_playback(Some(station.clone()), false);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    player.set_playback(Some(station), true);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+    assert_eq!(changed.lock().unwrap().len(), 2);
+
+    poller.abort();
+}
+
+#[test]
+fn only_artist_and_title_change_the_track() {
+    let mut previous = AmgSource::new_station("ruwave", "Русская Волна", "http://stream");
+    previous.current_artist = Some("Кино".to_string());
+    previous.current_track = Some("Группа крови".to_string());
+    previous.stop_at_ms = Some(1000);
+    let last = TrackKey::of(&previous);
+
+    assert_eq!(
+        track_update(None, &previous, &previous),
+        TrackUpdate::Changed
+    );
+    assert_eq!(
+        track_update(Some(&last), &previous, &previous),
+        TrackUpdate::Same
+    );
+
+    // Уточнённое время окончания или обложка — тот же трек
+    let mut refined = previous.clone();
+    refined.stop_at_ms = Some(2000);
+    assert_eq!(
+        track_update(Some(&last), &previous, &refined),
+        TrackUpdate::Refined
+    );
+    refined.artwork_url = Some("http://cover".to_string());
+    assert_eq!(
+        track_update(Some(&last), &previous, &refined),
+        TrackUpdate::Refined
+    );
+
+    let mut next = previous.clone();
+    next.current_track = Some("Звезда по имени Солнце".to_string());
+    assert_eq!(
+        track_update(Some(&last), &previous, &next),
+        TrackUpdate::Changed
+    );
+}
+
+#[tokio::test]
+async fn stop_does_not_wait_for_metadata_request() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let (stop, stop_rx) = oneshot::channel();
+    let station = AmgSource::new_station("ruwave", "Русская Волна", "http://stream");
+    let task = tokio::spawn(poll_station(
+        service(&requests, Answer::Hang),
+        station,
+        Arc::new(|_, _| panic!("ответа не было")),
+        stop_rx,
+    ));
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+    let _ = stop.send(());
+    tokio::time::timeout(Duration::from_secs(1), task)
+        .await
+        .expect("опрос не остановился")
+        .unwrap();
+}
@@ -315,7 +315,6 @@ let currentTrackInfo = {
   videoUrl: ''
 };
 
-let metadataPollTimeout = null;
 let trackCountdownTimer = null;
 let hasAutoPlayedLastStation = false;
 const appWindow = getCurrentWindow();
@@ -531,22 +530,6 @@ function startTrackCountdown(stopAtMs) {
   trackCountdownTimer = setInterval(tick, 1000);
 }
 
-function scheduleMetadataPoll(stopAtMs) {
-  if (metadataPollTimeout) {
-    clearTimeout(metadataPollTimeout);
-    metadataPollTimeout = null;
-  }
-  const now = Date.now();
-  const extraDelayMs = 12000;
-  let delayMs = 10000;
-  if (stopAtMs && stopAtMs > now) {
-    delayMs = Math.max(1000, stopAtMs - now + extraDelayMs);
-  }
-  metadataPollTimeout = setTimeout(() => {
-    fetchCurrentTrackFromAMG();
-  }, delayMs);
-}
-
 // Drag and Drop состояние
 let dragState = {
   element: null,           // Исходный элемент
@@ -1002,7 +985,37 @@ window.toggleCurrentTrackPlay = function() {
   }
 };
 
-// Получение текущей композиции (AMG или 101.ru)
+// Показать трек из метаданных бэкенда (событие track-changed или запрос 101.ru)
+async function showStationTrack(station, updatedStation) {
+  // stop_at_ms приходит как Unix timestamp в миллисекундах
+  const stopAtMs = updatedStation.stop_at_ms || null;
+
+  const trackInfo = {
+    title: updatedStation.current_track || station.name,
+    artist: updatedStation.current_artist || 'Прямой эфир',
+    cover: (updatedStation.artwork_url && updatedStation.artwork_url.length > 5 ? updatedStation.artwork_url : null)
+           || (updatedStation.logo && updatedStation.logo.length > 5 ? updatedStation.logo : null)
+           || 'http://localhost:1420/logo.png',
+    station: station,
+    isPlaying: audio && !audio.paused,
+    listeners: updatedStation.listeners || null,
+    timeText: '',
+    nextTitle: '',
+    nextArtist: '',
+    stopAtMs: stopAtMs,
+    videoNow: '',
+    videoUrl: ''
+  };
+
+  const newTrackKey = `${trackInfo.artist} - ${trackInfo.title}`.trim();
+  const oldTrackKey = `${currentTrackInfo.artist} - ${currentTrackInfo.title}`.trim();
+  const withAnimation = newTrackKey && newTrackKey !== oldTrackKey;
+  updateCurrentTrackDisplay(trackInfo, withAnimation);
+  startTrackCountdown(stopAtMs);
+  await persistLastStation(station, { title: trackInfo.title, artist: trackInfo.artist, cover: trackInfo.cover });
+}
+
+// Получение текущей композиции (AMG или 101.ru). Дальше трек обновляется по событию track-changed.
 async function fetchCurrentTrackFromAMG() {
   if (!currentStation) return;
 
@@ -1025,34 +1038,7 @@ async function fetchCurrentTrackFromAMG() {
         return;
       }
 
-      // stop_at_ms приходит как Unix timestamp в миллисекундах
-      const stopAtMs = updatedStation.stop_at_ms || null;
-
-      const trackInfo = {
-        title: updatedStation.current_track || station.name,
-        artist: updatedStation.current_artist || 'Прямой эфир',
-        cover: (updatedStation.artwork_url && updatedStation.artwork_url.length > 5 ? updatedStation.artwork_url : null)
-               || (updatedStation.logo && updatedStation.logo.length > 5 ? updatedStation.logo : null)
-               || 'http://localhost:1420/logo.png',
-        station: station,
-        isPlaying: audio && !audio.paused,
-        listeners: updatedStation.listeners || null,
-        timeText: '',
-        nextTitle: '',
-        nextArtist: '',
-        stopAtMs: stopAtMs,
-        videoNow: '',
-        videoUrl: ''
-      };
-
-      const newTrackKey = `${trackInfo.artist} - ${trackInfo.title}`.trim();
-      const oldTrackKey = `${currentTrackInfo.artist} - ${currentTrackInfo.title}`.trim();
-      const withAnimation = newTrackKey && newTrackKey !== oldTrackKey;
-      updateCurrentTrackDisplay(trackInfo, withAnimation);
-      startTrackCountdown(stopAtMs);
-      // Используем время окончания трека для следующего опроса, или 15 секунд
-      scheduleMetadataPoll(stopAtMs);
-      await persistLastStation(station, { title: trackInfo.title, artist: trackInfo.artist, cover: trackInfo.cover });
+      await showStationTrack(station, updatedStation);
       return;
     } catch (error) {
       // Игнорируем ошибки получения метаданных
@@ -1102,7 +1088,6 @@ async function fetchCurrentTrackFromAMG() {
         const withAnimation = newTrackKey && newTrackKey !== oldTrackKey;
         updateCurrentTrackDisplay(trackInfo, withAnimation);
         startTrackCountdown(metadata.stopAtMs);
-        scheduleMetadataPoll(metadata.stopAtMs);
         await persistLastStation(verifiedStation, metadata);
         return;
       }
@@ -1128,7 +1113,6 @@ async function fetchCurrentTrackFromAMG() {
 
   updateCurrentTrackDisplay(trackInfo, !currentTrackInfo.title || currentTrackInfo.title === 'Выберите станцию');
   startTrackCountdown(null);
-  scheduleMetadataPoll(null);
   updateWindowTitle(currentStation ? currentStation.name : '');
 }
 
@@ -1163,10 +1147,6 @@ window.playStation = async function(index) {
 
   // Останавливаем предыдущую станцию
   resetAudio();
-  if (metadataPollTimeout) {
-    clearTimeout(metadataPollTimeout);
-    metadataPollTimeout = null;
-  }
   if (trackCountdownTimer) {
     clearInterval(trackCountdownTimer);
     trackCountdownTimer = null;
@@ -1275,6 +1255,7 @@ window.playStation = async function(index) {
       currentTrackInfo.isPlaying = true;
       updateCurrentTrackDisplay(currentTrackInfo);
     }
+    reportPlaybackState(true);
   });
 
   audio.addEventListener('pause', () => {
@@ -1282,6 +1263,7 @@ window.playStation = async function(index) {
       currentTrackInfo.isPlaying = false;
       updateCurrentTrackDisplay(currentTrackInfo);
     }
+    reportPlaybackState(false);
   });
 
   // Обновляем UI
@@ -1389,6 +1371,13 @@ async function loadSettings() {
   try {
     const settings = await invoke('get_settings');
 
+    // Файл настроек не прочитался — сообщаем, где лежит его копия
+    const settingsError = await invoke('get_settings_error');
+    if (settingsError) {
+      console.error('❌ Ошибка загрузки настроек:', settingsError);
+      alert(settingsError.message);
+    }
+
     // Загружаем режим стриминга
     if (settings.streaming_mode && (settings.streaming_mode === 'mp3' || settings.streaming_mode === 'hls')) {
       streamingMode = settings.streaming_mode;
@@ -2315,6 +2304,90 @@ window.showAboutModal = function() {
   }
 })();
 
+// ========== Управление плеером из бэкенда (HTTP API, MPRIS) ==========
+// Сообщаем бэкенду, что играет
+function reportPlaybackState(playing) {
+  invoke('set_playback_state', { station: currentStation, playing }).catch(err => {
+    console.error('Ошибка передачи состояния плеера:', err);
+  });
+}
+
+function applyVolume(volume) {
+  if (volumeSlider) volumeSlider.value = volume;
+  if (volumeValue) volumeValue.textContent = `${volume}%`;
+  if (audio) audio.volume = volume / 100;
+}
+
+(async () => {
+  try {
+    await listen('player-command', async ({ payload }) => {
+      switch (payload?.action) {
+        case 'play': {
+          const station = payload.station;
+          if (!stations.some(s => s.id === station.id) && !stationById.has(station.id)) {
+            stationById.set(station.id, station);
+          }
+          await window.playStationById(station.id);
+          break;
+        }
+        case 'resume':
+          if (audio && currentStation) {
+            audio.play().catch(() => {});
+          } else if (currentStation) {
+            await window.playStationById(currentStation.id);
+          }
+          break;
+        case 'pause':
+        case 'stop':
+          if (audio) audio.pause();
+          break;
+        case 'set_volume':
+          applyVolume(payload.volume);
+          break;
+      }
+    });
+  } catch (err) {
+    console.error('Ошибка подписки на player-command:', err);
+  }
+})();
+
+// Смена профиля: у профиля свои громкость, режим потока и избранное
+(async () => {
+  try {
+    await listen('profile-changed', ({ payload }) => {
+      if (!payload) return;
+      if (payload.streaming_mode === 'mp3' || payload.streaming_mode === 'hls') {
+        streamingMode = payload.streaming_mode;
+        updateStreamingModeUI();
+      }
+      if (payload.volume !== undefined) {
+        applyVolume(payload.volume);
+      }
+      const favorites = Array.isArray(payload.favorite_stations) ? payload.favorite_stations : [];
+      favoriteStations = favorites.map(station => station.id);
+      window.favoriteStationsData = favorites;
+      rehydrateFavorites();
+      // Во вкладке источника обновятся отметки избранного
+      renderStations();
+    });
+  } catch (err) {
+    console.error('Ошибка подписки на profile-changed:', err);
+  }
+})();
+
+// Смена трека: бэкенд сам опрашивает метаданные играющей станции
+(async () => {
+  try {
+    await listen('track-changed', async ({ payload }) => {
+      if (!currentStation || payload?.id !== currentStation.id) return;
+      const station = stationById.get(currentStation.id) || currentStation;
+      await showStationTrack(station, payload);
+    });
+  } catch (err) {
+    console.error('Ошибка подписки на track-changed:', err);
+  }
+})();
+
 // Закрытие модального окна по Escape
 document.addEventListener('keydown', (e) => {
   if (e.key === 'Escape') {